@@ -0,0 +1,156 @@
+pub mod angle {
+
+    use std::f64::consts::{PI, TAU};
+
+    // Range that wrapped (non-continuous) angles are reported in
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum AngleRange {
+        // [-π, π)
+        #[default]
+        SignedPi,
+        // [0, 2π)
+        ZeroToTwoPi,
+    }
+
+    pub fn wrap_angle(angle: f32, range: AngleRange) -> f32 {
+        // Bounds as f32, so that -π and 2π round the same way as the angles they are compared with
+        let (lower, upper) = match range {
+            AngleRange::SignedPi => (-PI as f32, PI as f32),
+            AngleRange::ZeroToTwoPi => (0.0, TAU as f32),
+        };
+        if (lower..upper).contains(&angle) {
+            return angle;
+        }
+        let wrapped = (angle as f64 - lower as f64).rem_euclid(TAU) + lower as f64;
+        // Rounding (in rem_euclid or the cast back to f32) can land exactly on the open upper bound
+        let wrapped = wrapped as f32;
+        if wrapped >= upper {
+            lower
+        } else {
+            wrapped
+        }
+    }
+
+    pub fn wrap_angles(angles: [f32; 3], range: AngleRange) -> [f32; 3] {
+        angles.map(|angle| wrap_angle(angle, range))
+    }
+
+    // Continuous angles, plus how many whole turns each one has made since the first sample.
+    // `angles[i]` equals the wrapped angle plus `wraps[i]` * 2π.
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub struct UnwrappedAngles {
+        pub angles: [f64; 3],
+        pub wraps: [i64; 3],
+    }
+
+    // Removes the 2π jumps from a stream of wrapped angles by assuming that no angle moves by more
+    // than π between consecutive samples
+    #[derive(Debug, Clone, Default)]
+    pub struct Unwrapper {
+        previous: Option<[f64; 3]>,
+        continuous: [f64; 3],
+    }
+
+    impl Unwrapper {
+        pub fn new() -> Self {
+            Unwrapper::default()
+        }
+
+        pub fn push(&mut self, angles: [f32; 3]) -> UnwrappedAngles {
+            let angles = angles.map(|angle| angle as f64);
+            match self.previous {
+                None => self.continuous = angles,
+                Some(previous) => {
+                    for i in 0..3 {
+                        let step = (angles[i] - previous[i] + PI).rem_euclid(TAU) - PI;
+                        self.continuous[i] += step;
+                    }
+                }
+            }
+            self.previous = Some(angles);
+
+            let mut wraps = [0i64; 3];
+            for i in 0..3 {
+                wraps[i] = ((self.continuous[i] - angles[i]) / TAU).round() as i64;
+            }
+            UnwrappedAngles {
+                angles: self.continuous,
+                wraps,
+            }
+        }
+
+        pub fn reset(&mut self) {
+            *self = Unwrapper::default();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::angle::*;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn test_wrap_angle_signed() {
+        assert!((wrap_angle(3.0 * PI / 2.0, AngleRange::SignedPi) + PI / 2.0).abs() < 1e-6);
+        assert!((wrap_angle(PI, AngleRange::SignedPi) + PI).abs() < 1e-6);
+        assert_eq!(wrap_angle(-PI, AngleRange::SignedPi), -PI);
+        assert!((wrap_angle(0.5, AngleRange::SignedPi) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_wrap_angle_unsigned() {
+        assert!((wrap_angle(-PI / 2.0, AngleRange::ZeroToTwoPi) - 3.0 * PI / 2.0).abs() < 1e-6);
+        assert_eq!(wrap_angle(0.0, AngleRange::ZeroToTwoPi), 0.0);
+        let wrapped = wrap_angle(-1e-12, AngleRange::ZeroToTwoPi);
+        assert!((0.0..2.0 * PI).contains(&wrapped));
+        assert_eq!(
+            wrap_angles([-0.5, 0.0, 7.0], AngleRange::ZeroToTwoPi)[2],
+            wrap_angle(7.0, AngleRange::ZeroToTwoPi)
+        );
+    }
+
+    #[test]
+    fn test_unwrapper_crossing_pi() {
+        let mut unwrapper = Unwrapper::new();
+        let first = unwrapper.push([0.0, 0.0, 3.0]);
+        assert_eq!(first.wraps, [0, 0, 0]);
+
+        // Yaw goes from 3.0 past π and reappears near -π
+        let second = unwrapper.push([0.0, 0.0, -3.0]);
+        let expected = 2.0 * std::f64::consts::PI - 3.0;
+        assert!((second.angles[2] - expected).abs() < 1e-6);
+        assert_eq!(second.wraps, [0, 0, 1]);
+
+        // And back again
+        let third = unwrapper.push([0.0, 0.0, 3.0]);
+        assert!((third.angles[2] - 3.0).abs() < 1e-6);
+        assert_eq!(third.wraps, [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_unwrapper_multiple_turns() {
+        let mut unwrapper = Unwrapper::new();
+        let mut last = UnwrappedAngles::default();
+        // Spin backwards about roll for three full turns in steps of 0.5 rad
+        let steps = (3.0 * 2.0 * std::f64::consts::PI / 0.5) as usize;
+        for step in 0..=steps {
+            let angle = -(step as f64) * 0.5;
+            let wrapped = wrap_angle(angle as f32, AngleRange::SignedPi);
+            last = unwrapper.push([wrapped, 0.0, 0.0]);
+        }
+        assert!((last.angles[0] + steps as f64 * 0.5).abs() < 1e-4);
+        assert_eq!(last.wraps[0], -3);
+    }
+
+    #[test]
+    fn test_unwrapper_reset() {
+        let mut unwrapper = Unwrapper::new();
+        unwrapper.push([3.0, 0.0, 0.0]);
+        unwrapper.push([-3.0, 0.0, 0.0]);
+        unwrapper.reset();
+        let result = unwrapper.push([-3.0, 0.0, 0.0]);
+        assert_eq!(result.wraps, [0, 0, 0]);
+        assert!((result.angles[0] + 3.0).abs() < 1e-6);
+    }
+}
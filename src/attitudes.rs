@@ -0,0 +1,829 @@
+pub mod attitude {
+
+    use nalgebra::{Matrix3, Matrix3x6, Matrix6, Rotation3, UnitQuaternion, Vector3, Vector6, U3};
+    use std::f32::consts::{PI, TAU};
+    use std::time::Duration;
+
+    use crate::frames::frame::FrameKind;
+    use crate::orientations::orientation::{EulerAngles, EulerConvention};
+
+    // How thread 2 turns decoded frames into orientations
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub enum AttitudeFilter {
+        // Each attitude frame is passed on as it is; rate frames are ignored
+        #[default]
+        Passthrough,
+        // Integrates angular rates and pulls towards attitude frames
+        Complementary(ComplementaryConfig),
+        // AHRS on raw gyro, accelerometer and magnetometer frames; attitude frames are ignored
+        Madgwick(MadgwickConfig),
+        Mahony(MahonyConfig),
+        // Extended Kalman filter on any of these frames, which also tracks its own uncertainty
+        Ekf(EkfConfig),
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct ComplementaryConfig {
+        // Hz; below it the attitude frames dominate, above it the integrated rates do
+        pub crossover_frequency: f32,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct MadgwickConfig {
+        // rad/s; how fast the gradient step pulls towards the accelerometer and magnetometer
+        pub beta: f32,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct MahonyConfig {
+        // 1/s; proportional and integral feedback of the accelerometer and magnetometer error
+        pub proportional_gain: f32,
+        pub integral_gain: f32,
+    }
+
+    // Noise levels of the extended Kalman filter; all are standard deviations
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct EkfConfig {
+        // Gyro white noise (rad/s/√Hz) and the random walk of its bias (rad/s/√s)
+        pub gyro_noise: f32,
+        pub gyro_bias_noise: f32,
+        // Attitude frames (rad), and the accelerometer and magnetometer directions (unit vectors)
+        pub attitude_noise: f32,
+        pub acceleration_noise: f32,
+        pub magnetic_noise: f32,
+        // Uncertainty of the first estimate's attitude (rad) and gyro bias (rad/s)
+        pub initial_attitude_noise: f32,
+        pub initial_bias_noise: f32,
+    }
+
+    impl Default for EkfConfig {
+        fn default() -> Self {
+            EkfConfig {
+                gyro_noise: 0.01,
+                gyro_bias_noise: 1e-4,
+                attitude_noise: 0.02,
+                acceleration_noise: 0.05,
+                magnetic_noise: 0.1,
+                initial_attitude_noise: 0.1,
+                initial_bias_noise: 0.01,
+            }
+        }
+    }
+
+    // Below this norm a vector has no usable direction
+    const MIN_NORM: f32 = 1e-6;
+
+    // Time between two timestamps in seconds; never negative, so out-of-order frames do no harm
+    pub(crate) fn seconds_between(earlier: Option<Duration>, later: Duration) -> f32 {
+        earlier.map_or(0.0, |earlier| later.saturating_sub(earlier).as_secs_f32())
+    }
+
+    // Rotates `attitude` by a body-frame angular rate (rad/s) held for `dt` seconds
+    pub fn integrate_rate(
+        attitude: UnitQuaternion<f32>,
+        rate: Vector3<f32>,
+        dt: f32,
+    ) -> UnitQuaternion<f32> {
+        attitude * UnitQuaternion::from_scaled_axis(rate * dt)
+    }
+
+    // First-order complementary filter on unit quaternions
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ComplementaryFilter {
+        pub config: ComplementaryConfig,
+        estimate: Option<UnitQuaternion<f32>>,
+        // Latest gyro reading, held until the next one
+        last_rate: Vector3<f32>,
+        // When the estimate was last propagated, and last corrected
+        last_update: Option<Duration>,
+        last_correction: Option<Duration>,
+    }
+
+    impl ComplementaryFilter {
+        pub fn new(config: ComplementaryConfig) -> Self {
+            ComplementaryFilter {
+                config,
+                estimate: None,
+                last_rate: Vector3::zeros(),
+                last_update: None,
+                last_correction: None,
+            }
+        }
+
+        pub fn estimate(&self) -> Option<UnitQuaternion<f32>> {
+            self.estimate
+        }
+
+        // Propagates with a gyro reading; there is nothing to propagate before the first attitude
+        pub fn update_rate(
+            &mut self,
+            rate: Vector3<f32>,
+            time: Duration,
+        ) -> Option<UnitQuaternion<f32>> {
+            let estimate = self.estimate?;
+            self.last_rate = rate;
+            let dt = seconds_between(self.last_update, time);
+            self.last_update = Some(time.max(self.last_update.unwrap_or_default()));
+            let estimate = integrate_rate(estimate, rate, dt);
+            self.estimate = Some(estimate);
+            Some(estimate)
+        }
+
+        // Corrects with an absolute attitude, weighted by how long it has been since the last one
+        pub fn update_attitude(
+            &mut self,
+            attitude: UnitQuaternion<f32>,
+            time: Duration,
+        ) -> UnitQuaternion<f32> {
+            let estimate = match self.estimate {
+                None => attitude,
+                Some(estimate) => {
+                    // Bring the estimate up to the attitude's time with the latest rate first
+                    let elapsed = seconds_between(self.last_update, time);
+                    let estimate = integrate_rate(estimate, self.last_rate, elapsed);
+                    let dt = seconds_between(self.last_correction, time);
+                    let tau = 1.0 / (TAU * self.config.crossover_frequency);
+                    // 1 - e^(-dt/τ), which is dt / τ for small steps; zero crossover never corrects
+                    let alpha = if self.config.crossover_frequency > 0.0 {
+                        1.0 - (-dt / tau).exp()
+                    } else {
+                        0.0
+                    };
+                    estimate.slerp(&attitude, alpha)
+                }
+            };
+            self.estimate = Some(estimate);
+            self.last_update = Some(time.max(self.last_update.unwrap_or_default()));
+            self.last_correction = Some(time);
+            estimate
+        }
+    }
+
+    // The AHRS earth frame is x towards magnetic north, y west and z up, so a level accelerometer at
+    // rest reads +z
+    fn up() -> Vector3<f32> {
+        Vector3::z()
+    }
+
+    // Orientation from a single accelerometer (and magnetometer) reading; without a magnetometer the
+    // heading is arbitrary
+    pub fn initial_attitude(
+        acceleration: Vector3<f32>,
+        magnetic_field: Option<Vector3<f32>>,
+    ) -> Option<UnitQuaternion<f32>> {
+        let up_body = acceleration.try_normalize(MIN_NORM)?;
+        let west_body =
+            magnetic_field.and_then(|field| up_body.cross(&field).try_normalize(MIN_NORM));
+        Some(match west_body {
+            Some(west_body) => {
+                let north_body = west_body.cross(&up_body);
+                // Rows are the earth axes seen from the body, i.e. the body-to-earth rotation
+                let matrix = Matrix3::from_rows(&[
+                    north_body.transpose(),
+                    west_body.transpose(),
+                    up_body.transpose(),
+                ]);
+                UnitQuaternion::from_rotation_matrix(&Rotation3::from_matrix_unchecked(matrix))
+            }
+            // Upside down there is no shortest rotation; any half turn about a level axis will do
+            None => UnitQuaternion::rotation_between(&up_body, &up())
+                .unwrap_or_else(|| UnitQuaternion::from_axis_angle(&Vector3::x_axis(), PI)),
+        })
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum AhrsAlgorithm {
+        Madgwick(MadgwickConfig),
+        Mahony(MahonyConfig),
+    }
+
+    // Attitude and heading reference system: integrates gyro frames and corrects them with the
+    // latest accelerometer and magnetometer frames, in the vector form of either algorithm
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Ahrs {
+        pub algorithm: AhrsAlgorithm,
+        estimate: Option<UnitQuaternion<f32>>,
+        // Latest readings as unit vectors
+        acceleration: Option<Vector3<f32>>,
+        magnetic_field: Option<Vector3<f32>>,
+        // Mahony's integral term, which ends up cancelling the gyro bias
+        integral_error: Vector3<f32>,
+        last_update: Option<Duration>,
+    }
+
+    impl Ahrs {
+        pub fn new(algorithm: AhrsAlgorithm) -> Self {
+            Ahrs {
+                algorithm,
+                estimate: None,
+                acceleration: None,
+                magnetic_field: None,
+                integral_error: Vector3::zeros(),
+                last_update: None,
+            }
+        }
+
+        pub fn estimate(&self) -> Option<UnitQuaternion<f32>> {
+            self.estimate
+        }
+
+        // A zero reading carries no direction and is ignored
+        pub fn update_acceleration(&mut self, acceleration: Vector3<f32>) {
+            if let Some(direction) = acceleration.try_normalize(MIN_NORM) {
+                self.acceleration = Some(direction);
+            }
+        }
+
+        pub fn update_magnetic_field(&mut self, magnetic_field: Vector3<f32>) {
+            if let Some(direction) = magnetic_field.try_normalize(MIN_NORM) {
+                self.magnetic_field = Some(direction);
+            }
+        }
+
+        // Propagates with a gyro reading. The first one after an accelerometer reading only
+        // initialises the estimate; before that there is nothing to report.
+        pub fn update_rate(
+            &mut self,
+            rate: Vector3<f32>,
+            time: Duration,
+        ) -> Option<UnitQuaternion<f32>> {
+            let estimate = match self.estimate {
+                Some(estimate) => estimate,
+                None => {
+                    let estimate = initial_attitude(self.acceleration?, self.magnetic_field)?;
+                    self.estimate = Some(estimate);
+                    self.last_update = Some(time);
+                    return Some(estimate);
+                }
+            };
+            let dt = seconds_between(self.last_update, time);
+            self.last_update = Some(time.max(self.last_update.unwrap_or_default()));
+
+            // The readings are as recent as this gyro frame, so they are compared with the
+            // estimate propagated to its time
+            let predicted = integrate_rate(estimate, rate, dt);
+            let error = self.measurement_error(predicted);
+            let correction = match self.algorithm {
+                // The normalised gradient of Madgwick's objective is this error's direction
+                AhrsAlgorithm::Madgwick(config) => error
+                    .try_normalize(MIN_NORM)
+                    .map_or(Vector3::zeros(), |direction| {
+                        direction * (2.0 * config.beta)
+                    }),
+                AhrsAlgorithm::Mahony(config) => {
+                    self.integral_error += error * (config.integral_gain * dt);
+                    error * config.proportional_gain + self.integral_error
+                }
+            };
+            let estimate = integrate_rate(predicted, correction, dt);
+            self.estimate = Some(estimate);
+            Some(estimate)
+        }
+
+        // Sum of measured × predicted directions of gravity and the magnetic field, in the body
+        // frame: the rotation that would bring the estimate in line with the readings
+        fn measurement_error(&self, estimate: UnitQuaternion<f32>) -> Vector3<f32> {
+            let Some(acceleration) = self.acceleration else {
+                return Vector3::zeros();
+            };
+            let mut error = acceleration.cross(&(estimate.inverse() * up()));
+            if let Some(magnetic_field) = self.magnetic_field {
+                // Only the heading comes from the magnetometer: the reference field is the
+                // measured one rotated into the earth frame, with its horizontal part due north
+                let earth = estimate * magnetic_field;
+                let reference = Vector3::new(earth.x.hypot(earth.y), 0.0, earth.z);
+                error += magnetic_field.cross(&(estimate.inverse() * reference));
+            }
+            error
+        }
+    }
+
+    // Multiplicative extended Kalman filter: the state is the attitude and the gyro bias, and the
+    // covariance is that of a small body-frame rotation error (the true attitude is the estimate
+    // followed by that rotation) and of the bias error
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Ekf {
+        pub config: EkfConfig,
+        estimate: Option<UnitQuaternion<f32>>,
+        bias: Vector3<f32>,
+        covariance: Matrix6<f32>,
+        // Latest gyro reading, held until the next one
+        last_rate: Vector3<f32>,
+        // Accelerometer and magnetometer readings not yet applied; they are applied once the
+        // estimate has been propagated to the next gyro frame
+        acceleration: Option<Vector3<f32>>,
+        magnetic_field: Option<Vector3<f32>>,
+        last_update: Option<Duration>,
+    }
+
+    impl Ekf {
+        pub fn new(config: EkfConfig) -> Self {
+            Ekf {
+                config,
+                estimate: None,
+                bias: Vector3::zeros(),
+                covariance: Matrix6::zeros(),
+                last_rate: Vector3::zeros(),
+                acceleration: None,
+                magnetic_field: None,
+                last_update: None,
+            }
+        }
+
+        pub fn estimate(&self) -> Option<UnitQuaternion<f32>> {
+            self.estimate
+        }
+
+        pub fn bias(&self) -> Vector3<f32> {
+            self.bias
+        }
+
+        // Covariance of the body-frame attitude error (rad²)
+        pub fn attitude_covariance(&self) -> Option<Matrix3<f32>> {
+            self.estimate
+                .map(|_| self.covariance.fixed_slice::<U3, U3>(0, 0).into_owned())
+        }
+
+        fn initialise(&mut self, attitude: UnitQuaternion<f32>, time: Duration) {
+            let attitude_variance = self.config.initial_attitude_noise.powi(2);
+            let bias_variance = self.config.initial_bias_noise.powi(2);
+            self.estimate = Some(attitude);
+            self.bias = Vector3::zeros();
+            self.covariance = Matrix6::from_diagonal(&Vector6::new(
+                attitude_variance,
+                attitude_variance,
+                attitude_variance,
+                bias_variance,
+                bias_variance,
+                bias_variance,
+            ));
+            self.last_update = Some(time);
+        }
+
+        // Propagates with a gyro reading, then applies any accelerometer and magnetometer readings
+        // received since the previous one. Without an estimate yet, the first gyro frame after an
+        // accelerometer reading initialises one.
+        pub fn update_rate(
+            &mut self,
+            rate: Vector3<f32>,
+            time: Duration,
+        ) -> Option<UnitQuaternion<f32>> {
+            self.last_rate = rate;
+            if self.estimate.is_none() {
+                let attitude = initial_attitude(self.acceleration?, self.magnetic_field)?;
+                self.initialise(attitude, time);
+                self.acceleration = None;
+                self.magnetic_field = None;
+                return self.estimate;
+            }
+            self.predict(rate, time);
+            if let Some(acceleration) = self.acceleration.take() {
+                self.correct_direction(acceleration, up(), self.config.acceleration_noise);
+            }
+            if let Some(magnetic_field) = self.magnetic_field.take() {
+                // As in the AHRS, only the heading comes from the magnetometer
+                let earth = self.estimate? * magnetic_field;
+                let reference = Vector3::new(earth.x.hypot(earth.y), 0.0, earth.z);
+                self.correct_direction(magnetic_field, reference, self.config.magnetic_noise);
+            }
+            self.estimate
+        }
+
+        // A zero reading carries no direction and is ignored
+        pub fn update_acceleration(&mut self, acceleration: Vector3<f32>) {
+            if let Some(direction) = acceleration.try_normalize(MIN_NORM) {
+                self.acceleration = Some(direction);
+            }
+        }
+
+        pub fn update_magnetic_field(&mut self, magnetic_field: Vector3<f32>) {
+            if let Some(direction) = magnetic_field.try_normalize(MIN_NORM) {
+                self.magnetic_field = Some(direction);
+            }
+        }
+
+        // Corrects with an absolute attitude, after propagating to its time with the latest rate
+        pub fn update_attitude(
+            &mut self,
+            attitude: UnitQuaternion<f32>,
+            time: Duration,
+        ) -> UnitQuaternion<f32> {
+            let Some(estimate) = self.estimate else {
+                self.initialise(attitude, time);
+                return attitude;
+            };
+            self.predict(self.last_rate, time);
+            let estimate = self.estimate.unwrap_or(estimate);
+            let residual = (estimate.inverse() * attitude).scaled_axis();
+            let mut observation = Matrix3x6::zeros();
+            observation
+                .fixed_slice_mut::<U3, U3>(0, 0)
+                .copy_from(&Matrix3::identity());
+            self.correct(residual, observation, self.config.attitude_noise);
+            self.estimate.unwrap_or(estimate)
+        }
+
+        fn predict(&mut self, rate: Vector3<f32>, time: Duration) {
+            let Some(estimate) = self.estimate else {
+                return;
+            };
+            let dt = seconds_between(self.last_update, time);
+            self.last_update = Some(time.max(self.last_update.unwrap_or_default()));
+            if dt <= 0.0 {
+                return;
+            }
+            let step = UnitQuaternion::from_scaled_axis((rate - self.bias) * dt);
+            self.estimate = Some(estimate * step);
+
+            // The error rotates back by the step, and picks up the bias error over it
+            let mut transition = Matrix6::identity();
+            transition
+                .fixed_slice_mut::<U3, U3>(0, 0)
+                .copy_from(step.inverse().to_rotation_matrix().matrix());
+            transition
+                .fixed_slice_mut::<U3, U3>(0, 3)
+                .copy_from(&(Matrix3::identity() * -dt));
+            let gyro_variance = self.config.gyro_noise.powi(2) * dt;
+            let bias_variance = self.config.gyro_bias_noise.powi(2) * dt;
+            let process_noise = Matrix6::from_diagonal(&Vector6::new(
+                gyro_variance,
+                gyro_variance,
+                gyro_variance,
+                bias_variance,
+                bias_variance,
+                bias_variance,
+            ));
+            self.covariance = transition * self.covariance * transition.transpose() + process_noise;
+        }
+
+        // A measured body-frame direction of the earth-frame `reference`
+        fn correct_direction(
+            &mut self,
+            measured: Vector3<f32>,
+            reference: Vector3<f32>,
+            noise: f32,
+        ) {
+            let Some(estimate) = self.estimate else {
+                return;
+            };
+            let Some(reference) = reference.try_normalize(MIN_NORM) else {
+                return;
+            };
+            let predicted = estimate.inverse() * reference;
+            // A small error rotation δθ turns the prediction into predicted + predicted × δθ
+            let mut observation = Matrix3x6::zeros();
+            observation
+                .fixed_slice_mut::<U3, U3>(0, 0)
+                .copy_from(&predicted.cross_matrix());
+            self.correct(measured - predicted, observation, noise);
+        }
+
+        fn correct(&mut self, residual: Vector3<f32>, observation: Matrix3x6<f32>, noise: f32) {
+            let Some(estimate) = self.estimate else {
+                return;
+            };
+            let measurement_noise = Matrix3::identity() * noise.powi(2);
+            let innovation =
+                observation * self.covariance * observation.transpose() + measurement_noise;
+            let Some(inverse) = innovation.try_inverse() else {
+                return;
+            };
+            let gain = self.covariance * observation.transpose() * inverse;
+            let correction = gain * residual;
+            self.estimate = Some(
+                estimate
+                    * UnitQuaternion::from_scaled_axis(correction.fixed_rows::<U3>(0).into_owned()),
+            );
+            self.bias += correction.fixed_rows::<U3>(3);
+            // Joseph form, which keeps the covariance symmetric and positive
+            let reduction = Matrix6::identity() - gain * observation;
+            self.covariance = reduction * self.covariance * reduction.transpose()
+                + gain * measurement_noise * gain.transpose();
+        }
+    }
+
+    // Per-sensor state of whichever filter is configured
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum AttitudeEstimator {
+        Passthrough,
+        Complementary(ComplementaryFilter),
+        Ahrs(Ahrs),
+        Ekf(Ekf),
+    }
+
+    impl AttitudeEstimator {
+        pub fn new(filter: AttitudeFilter) -> Self {
+            match filter {
+                AttitudeFilter::Passthrough => AttitudeEstimator::Passthrough,
+                AttitudeFilter::Complementary(config) => {
+                    AttitudeEstimator::Complementary(ComplementaryFilter::new(config))
+                }
+                AttitudeFilter::Madgwick(config) => {
+                    AttitudeEstimator::Ahrs(Ahrs::new(AhrsAlgorithm::Madgwick(config)))
+                }
+                AttitudeFilter::Mahony(config) => {
+                    AttitudeEstimator::Ahrs(Ahrs::new(AhrsAlgorithm::Mahony(config)))
+                }
+                AttitudeFilter::Ekf(config) => AttitudeEstimator::Ekf(Ekf::new(config)),
+            }
+        }
+
+        // Of the latest estimate, for estimators that track one
+        pub fn attitude_covariance(&self) -> Option<Matrix3<f32>> {
+            match self {
+                AttitudeEstimator::Ekf(ekf) => ekf.attitude_covariance(),
+                _ => None,
+            }
+        }
+
+        // Feeds in one decoded frame; returns the orientation to report, if this frame yields one.
+        // Attitude frames are Euler angles in `convention`.
+        pub fn update(
+            &mut self,
+            kind: FrameKind,
+            values: [f32; 3],
+            convention: EulerConvention,
+            time: Duration,
+        ) -> Option<UnitQuaternion<f32>> {
+            let vector = Vector3::from(values);
+            let attitude = || EulerAngles::with_convention(values, convention).to_quaternion();
+            match (self, kind) {
+                (AttitudeEstimator::Passthrough, FrameKind::Attitude) => Some(attitude()),
+                (AttitudeEstimator::Complementary(filter), FrameKind::Attitude) => {
+                    Some(filter.update_attitude(attitude(), time))
+                }
+                (AttitudeEstimator::Complementary(filter), FrameKind::AngularRate) => {
+                    filter.update_rate(vector, time)
+                }
+                (AttitudeEstimator::Ahrs(ahrs), FrameKind::AngularRate) => {
+                    ahrs.update_rate(vector, time)
+                }
+                (AttitudeEstimator::Ahrs(ahrs), FrameKind::Acceleration) => {
+                    ahrs.update_acceleration(vector);
+                    None
+                }
+                (AttitudeEstimator::Ahrs(ahrs), FrameKind::MagneticField) => {
+                    ahrs.update_magnetic_field(vector);
+                    None
+                }
+                (AttitudeEstimator::Ekf(ekf), FrameKind::Attitude) => {
+                    Some(ekf.update_attitude(attitude(), time))
+                }
+                (AttitudeEstimator::Ekf(ekf), FrameKind::AngularRate) => {
+                    ekf.update_rate(vector, time)
+                }
+                (AttitudeEstimator::Ekf(ekf), FrameKind::Acceleration) => {
+                    ekf.update_acceleration(vector);
+                    None
+                }
+                (AttitudeEstimator::Ekf(ekf), FrameKind::MagneticField) => {
+                    ekf.update_magnetic_field(vector);
+                    None
+                }
+                // Frames the configured filter has no use for
+                _ => None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::attitude::*;
+    use crate::frames::frame::FrameKind;
+    use crate::generators::generator::Rng;
+    use crate::orientations::orientation::EulerConvention;
+    use nalgebra::{UnitQuaternion, Vector3};
+    use std::time::Duration;
+
+    fn yaw(angle: f32) -> UnitQuaternion<f32> {
+        UnitQuaternion::from_axis_angle(&Vector3::z_axis(), angle)
+    }
+
+    #[test]
+    fn test_integrate_rate() {
+        let attitude = integrate_rate(yaw(0.1), Vector3::new(0.0, 0.0, 0.5), 0.2);
+        assert!(attitude.angle_to(&yaw(0.2)) < 1e-6);
+    }
+
+    #[test]
+    fn test_complementary_filter_tracks_rotation() {
+        let mut filter = ComplementaryFilter::new(ComplementaryConfig {
+            crossover_frequency: 0.5,
+        });
+        let rate = Vector3::new(0.0, 0.0, 1.0);
+        assert!(filter.update_rate(rate, Duration::ZERO).is_none());
+        filter.update_attitude(yaw(0.0), Duration::ZERO);
+
+        // Gyro at 100 Hz, attitude at 10 Hz, for two seconds of constant yaw rate
+        for step in 1..=200u64 {
+            let time = Duration::from_millis(10 * step);
+            filter.update_rate(rate, time);
+            if step % 10 == 0 {
+                filter.update_attitude(yaw(time.as_secs_f32()), time);
+            }
+        }
+        assert!(filter.estimate().unwrap().angle_to(&yaw(2.0)) < 1e-3);
+    }
+
+    #[test]
+    fn test_complementary_filter_smooths_noisy_attitude() {
+        let mut filter = ComplementaryFilter::new(ComplementaryConfig {
+            crossover_frequency: 0.2,
+        });
+        let mut rng = Rng::new(4);
+        let mut raw_error = 0.0;
+        let mut filtered_error = 0.0;
+        for step in 0..500u64 {
+            let time = Duration::from_millis(10 * step);
+            filter.update_rate(Vector3::zeros(), time);
+            let noisy = yaw((rng.gaussian() * 0.05) as f32);
+            let estimate = filter.update_attitude(noisy, time);
+            if step >= 100 {
+                raw_error += noisy.angle();
+                filtered_error += estimate.angle();
+            }
+        }
+        assert!(filtered_error < raw_error / 5.0);
+    }
+
+    #[test]
+    fn test_complementary_filter_zero_crossover_is_gyro_only() {
+        let mut filter = ComplementaryFilter::new(ComplementaryConfig {
+            crossover_frequency: 0.0,
+        });
+        filter.update_attitude(yaw(0.0), Duration::ZERO);
+        let estimate = filter.update_attitude(yaw(1.0), Duration::from_secs(1));
+        assert_eq!(estimate, yaw(0.0));
+    }
+
+    // What a level-referenced IMU with attitude `truth` reads, with the field dipping 60° down
+    fn imu_readings(truth: UnitQuaternion<f32>) -> (Vector3<f32>, Vector3<f32>) {
+        let field = Vector3::new(0.5, 0.0, -(3.0f32).sqrt() / 2.0);
+        (truth.inverse() * Vector3::z(), truth.inverse() * field)
+    }
+
+    #[test]
+    fn test_initial_attitude() {
+        let truth = UnitQuaternion::from_euler_angles(0.2, -0.3, 1.0);
+        let (acceleration, magnetic_field) = imu_readings(truth);
+        let attitude = initial_attitude(acceleration * 9.81, Some(magnetic_field)).unwrap();
+        assert!(attitude.angle_to(&truth) < 1e-5);
+
+        // Without a magnetometer only the tilt is known
+        let level = initial_attitude(Vector3::new(0.0, 0.0, 1.0), None).unwrap();
+        assert!(level.angle() < 1e-6);
+        let upside_down = initial_attitude(Vector3::new(0.0, 0.0, -1.0), None).unwrap();
+        assert!((upside_down * Vector3::z() + Vector3::z()).norm() < 1e-6);
+        assert!(initial_attitude(Vector3::zeros(), None).is_none());
+    }
+
+    // Runs an AHRS that starts 0.5 rad off for `seconds` at 100 Hz on a body turning about z
+    fn run_ahrs(algorithm: AhrsAlgorithm, gyro_bias: Vector3<f32>, seconds: u64) -> f32 {
+        let mut ahrs = Ahrs::new(algorithm);
+        let rate = Vector3::new(0.0, 0.0, 0.4);
+        let truth_at = |time: f32| {
+            UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 0.4 * time)
+                * UnitQuaternion::from_euler_angles(0.3, 0.1, 0.0)
+        };
+        let wrong = UnitQuaternion::from_euler_angles(0.5, 0.0, 0.0) * truth_at(0.0);
+        let (acceleration, magnetic_field) = imu_readings(wrong);
+        ahrs.update_acceleration(acceleration);
+        ahrs.update_magnetic_field(magnetic_field);
+        assert!(ahrs.update_rate(rate, Duration::ZERO).is_some());
+
+        for step in 1..=100 * seconds {
+            let time = Duration::from_millis(10 * step);
+            let truth = truth_at(time.as_secs_f32());
+            let (acceleration, magnetic_field) = imu_readings(truth);
+            ahrs.update_acceleration(acceleration);
+            ahrs.update_magnetic_field(magnetic_field);
+            // The body rate of a turn about the earth's z axis
+            ahrs.update_rate(truth.inverse() * rate + gyro_bias, time);
+        }
+        ahrs.estimate().unwrap().angle_to(&truth_at(seconds as f32))
+    }
+
+    #[test]
+    fn test_madgwick_converges() {
+        let algorithm = AhrsAlgorithm::Madgwick(MadgwickConfig { beta: 0.2 });
+        // The fixed-size gradient step keeps it within about 2β·dt of the truth
+        assert!(run_ahrs(algorithm, Vector3::zeros(), 10) < 0.01);
+        // A gyro bias leaves a small steady-state error instead of drifting off
+        assert!(run_ahrs(algorithm, Vector3::new(0.01, 0.0, 0.01), 10) < 0.05);
+    }
+
+    #[test]
+    fn test_mahony_converges_and_cancels_gyro_bias() {
+        let proportional = AhrsAlgorithm::Mahony(MahonyConfig {
+            proportional_gain: 2.0,
+            integral_gain: 0.0,
+        });
+        // Heading is slower than tilt: the magnetometer's horizontal part is only half the field
+        assert!(run_ahrs(proportional, Vector3::zeros(), 30) < 1e-3);
+        // Without the integral term a gyro bias leaves a steady error
+        assert!(run_ahrs(proportional, Vector3::new(0.02, -0.01, 0.02), 30) > 0.01);
+
+        // The integral term learns the bias, so the error still goes to zero
+        let integral = AhrsAlgorithm::Mahony(MahonyConfig {
+            proportional_gain: 2.0,
+            integral_gain: 0.5,
+        });
+        assert!(run_ahrs(integral, Vector3::new(0.02, -0.01, 0.02), 60) < 1e-3);
+    }
+
+    #[test]
+    fn test_attitude_estimator_routes_frames() {
+        let convention = EulerConvention::default();
+        let mut passthrough = AttitudeEstimator::new(AttitudeFilter::Passthrough);
+        let attitude = passthrough.update(
+            FrameKind::Attitude,
+            [0.0, 0.0, 0.5],
+            convention,
+            Duration::ZERO,
+        );
+        assert!(attitude.unwrap().angle_to(&yaw(0.5)) < 1e-6);
+        assert!(passthrough
+            .update(
+                FrameKind::AngularRate,
+                [0.0, 0.0, 1.0],
+                convention,
+                Duration::ZERO
+            )
+            .is_none());
+
+        // An AHRS reports on gyro frames once it has seen the accelerometer
+        let mut ahrs = AttitudeEstimator::new(AttitudeFilter::Mahony(MahonyConfig {
+            proportional_gain: 1.0,
+            integral_gain: 0.0,
+        }));
+        let time = Duration::ZERO;
+        assert!(ahrs
+            .update(FrameKind::AngularRate, [0.0; 3], convention, time)
+            .is_none());
+        assert!(ahrs
+            .update(FrameKind::Acceleration, [0.0, 0.0, 1.0], convention, time)
+            .is_none());
+        assert!(ahrs
+            .update(FrameKind::Attitude, [1.0, 0.0, 0.0], convention, time)
+            .is_none());
+        let level = ahrs.update(FrameKind::AngularRate, [0.0; 3], convention, time);
+        assert!(level.unwrap().angle() < 1e-6);
+    }
+
+    #[test]
+    fn test_ekf_estimates_gyro_bias_and_covariance() {
+        let config = EkfConfig {
+            attitude_noise: 0.05,
+            ..Default::default()
+        };
+        let mut ekf = Ekf::new(config);
+        assert!(ekf.attitude_covariance().is_none());
+        ekf.update_attitude(yaw(0.0), Duration::ZERO);
+        let initial = ekf.attitude_covariance().unwrap();
+        assert!((initial[(0, 0)] - 0.01).abs() < 1e-6);
+
+        // Gyro at 100 Hz reading 0.02 rad/s high about z, noisy attitude at 10 Hz, for a minute
+        let mut rng = Rng::new(11);
+        let rate = Vector3::new(0.0, 0.0, 0.3);
+        let bias = Vector3::new(0.0, 0.0, 0.02);
+        for step in 1..=6000u64 {
+            let time = Duration::from_millis(10 * step);
+            ekf.update_rate(rate + bias, time);
+            if step % 10 == 0 {
+                let noise = (rng.gaussian() * 0.05) as f32;
+                ekf.update_attitude(yaw(0.3 * time.as_secs_f32() + noise), time);
+            }
+        }
+        assert!((ekf.bias() - bias).norm() < 5e-3);
+        assert!(ekf.estimate().unwrap().angle_to(&yaw(18.0)) < 0.05);
+
+        // The uncertainty has settled well below the initial one, and grows without corrections
+        let settled = ekf.attitude_covariance().unwrap();
+        assert!(settled[(2, 2)] < initial[(2, 2)] / 10.0);
+        assert!((settled - settled.transpose()).norm() < 1e-9);
+        ekf.update_rate(rate + bias, Duration::from_secs(61));
+        assert!(ekf.attitude_covariance().unwrap()[(2, 2)] > settled[(2, 2)]);
+    }
+
+    #[test]
+    fn test_ekf_on_accelerometer_and_magnetometer() {
+        let mut estimator = AttitudeEstimator::new(AttitudeFilter::Ekf(EkfConfig::default()));
+        let convention = EulerConvention::default();
+        let truth = UnitQuaternion::from_euler_angles(0.2, -0.1, 0.7);
+        let (acceleration, magnetic_field) = imu_readings(truth);
+        let [ax, ay, az] = [acceleration.x, acceleration.y, acceleration.z];
+        let [mx, my, mz] = [magnetic_field.x, magnetic_field.y, magnetic_field.z];
+        for step in 0..200u64 {
+            let time = Duration::from_millis(10 * step);
+            estimator.update(FrameKind::Acceleration, [ax, ay, az], convention, time);
+            estimator.update(FrameKind::MagneticField, [mx, my, mz], convention, time);
+            let estimate = estimator.update(FrameKind::AngularRate, [0.0; 3], convention, time);
+            assert!(estimate.unwrap().angle_to(&truth) < 1e-3);
+        }
+        let covariance = estimator.attitude_covariance().unwrap();
+        assert!(
+            (0..3).all(|axis| covariance[(axis, axis)] > 0.0 && covariance[(axis, axis)] < 1e-3)
+        );
+    }
+}
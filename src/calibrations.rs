@@ -0,0 +1,246 @@
+pub mod calibration {
+
+    use nalgebra::{Matrix3, Quaternion, UnitQuaternion};
+    use std::fmt;
+    use std::fs;
+    use std::io;
+    use std::path::{Path, PathBuf};
+
+    use crate::orientations::orientation::EulerConvention;
+    use crate::sensors::sensor::SensorId;
+
+    // Corrections applied to one sensor's reconstructed orientation
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Calibration {
+        // Per-axis errors of the sensor's angles, in its input convention: it reports
+        // scale · true + bias for each
+        pub bias: [f32; 3],
+        pub scale: [f32; 3],
+        // Rotation of the sensor relative to the body it is mounted on (body-to-sensor)
+        pub mounting: UnitQuaternion<f32>,
+    }
+
+    impl Calibration {
+        pub fn identity() -> Self {
+            Calibration {
+                bias: [0.0; 3],
+                scale: [1.0; 3],
+                mounting: UnitQuaternion::identity(),
+            }
+        }
+
+        fn corrects_angles(&self) -> bool {
+            self.bias != [0.0; 3] || self.scale != [1.0; 3]
+        }
+
+        // The sensor's angles with its bias and scale taken out
+        pub fn correct(&self, angles: [f32; 3]) -> [f32; 3] {
+            [0, 1, 2].map(|i| (angles[i] - self.bias[i]) / self.scale[i])
+        }
+
+        // The body orientation, given the sensor's
+        pub fn apply(&self, sensor: UnitQuaternion<f32>) -> UnitQuaternion<f32> {
+            sensor * self.mounting.inverse()
+        }
+
+        // The body orientation, given the sensor's angles as reconstructed from its frame
+        pub fn apply_to_angles(
+            &self,
+            angles: [f32; 3],
+            convention: EulerConvention,
+        ) -> UnitQuaternion<f32> {
+            self.apply(convention.to_quaternion(self.correct(angles)))
+        }
+
+        // The body orientation, given the sensor's as a quaternion. Bias and scale are per angle,
+        // so they are taken out of the quaternion's angles; without them it is used as is.
+        pub fn apply_to_quaternion(
+            &self,
+            sensor: UnitQuaternion<f32>,
+            convention: EulerConvention,
+        ) -> UnitQuaternion<f32> {
+            if self.corrects_angles() {
+                self.apply_to_angles(convention.from_quaternion(sensor), convention)
+            } else {
+                self.apply(sensor)
+            }
+        }
+
+        // An attitude error covariance about the sensor axes, re-expressed about the body axes
+        pub fn apply_to_covariance(&self, sensor: Matrix3<f32>) -> Matrix3<f32> {
+            let mounting = self.mounting.to_rotation_matrix();
+            mounting.matrix() * sensor * mounting.matrix().transpose()
+        }
+
+        // Reads the text form written by `Display`: `bias`, `scale` and `mounting` (w x y z)
+        // lines, each optional; blank lines and lines starting with `#` are skipped
+        pub fn parse(text: &str) -> Result<Self, &'static str> {
+            let mut calibration = Calibration::identity();
+            for line in text.lines().map(str::trim) {
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                let mut words = line.split_whitespace();
+                let key = words.next().unwrap_or_default();
+                let values = words
+                    .map(|word| word.parse::<f32>())
+                    .collect::<Result<Vec<f32>, _>>()
+                    .map_err(|_| "Calibration values must be numbers.")?;
+                match (key, values.as_slice()) {
+                    ("bias", [x, y, z]) => calibration.bias = [*x, *y, *z],
+                    ("scale", [x, y, z]) => calibration.scale = [*x, *y, *z],
+                    ("mounting", [w, x, y, z]) => {
+                        let quaternion = Quaternion::new(*w, *x, *y, *z);
+                        if quaternion.norm() == 0.0 || !quaternion.norm().is_finite() {
+                            return Err("Calibration mounting must be a non-zero quaternion.");
+                        }
+                        calibration.mounting = UnitQuaternion::new_normalize(quaternion);
+                    }
+                    ("bias" | "scale" | "mounting", _) => {
+                        return Err("Calibration bias and scale take three values, mounting four.")
+                    }
+                    _ => return Err("Unknown calibration entry."),
+                }
+            }
+            if calibration
+                .scale
+                .iter()
+                .any(|scale| !(scale.is_finite() && *scale != 0.0))
+            {
+                return Err("Calibration scale must be non-zero.");
+            }
+            Ok(calibration)
+        }
+
+        pub fn load(path: &Path) -> io::Result<Self> {
+            let text = fs::read_to_string(path)?;
+            Calibration::parse(&text)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+        }
+
+        pub fn save(&self, path: &Path) -> io::Result<()> {
+            fs::write(path, self.to_string())
+        }
+    }
+
+    impl Default for Calibration {
+        fn default() -> Self {
+            Calibration::identity()
+        }
+    }
+
+    impl fmt::Display for Calibration {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let [bx, by, bz] = self.bias;
+            let [sx, sy, sz] = self.scale;
+            let q = self.mounting;
+            writeln!(
+                f,
+                "# Angles read scale * true + bias; mounting is body-to-sensor (w x y z)"
+            )?;
+            writeln!(f, "bias {} {} {}", bx, by, bz)?;
+            writeln!(f, "scale {} {} {}", sx, sy, sz)?;
+            writeln!(f, "mounting {} {} {} {}", q.w, q.i, q.j, q.k)
+        }
+    }
+
+    // Where a sensor's calibration is kept within a directory of calibrations
+    pub fn calibration_path(directory: &Path, sensor: SensorId) -> PathBuf {
+        directory.join(format!("sensor-{}.cal", sensor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::calibration::*;
+    use crate::orientations::orientation::EulerConvention;
+    use crate::sensors::sensor::SensorId;
+    use nalgebra::{Matrix3, UnitQuaternion, Vector3};
+
+    #[test]
+    fn test_calibration_mounting() {
+        let body = UnitQuaternion::from_euler_angles(0.1, -0.2, 0.3);
+        let mounting = UnitQuaternion::from_euler_angles(0.0, 0.0, std::f32::consts::FRAC_PI_2);
+        let calibration = Calibration {
+            mounting,
+            ..Default::default()
+        };
+        let recovered = calibration.apply(body * mounting);
+        assert!(recovered.angle_to(&body) < 1e-5);
+        assert_eq!(Calibration::default().apply(body), body);
+    }
+
+    #[test]
+    fn test_calibration_covariance() {
+        // A quarter turn about z swaps the x and y variances
+        let mounting = UnitQuaternion::from_euler_angles(0.0, 0.0, std::f32::consts::FRAC_PI_2);
+        let calibration = Calibration {
+            mounting,
+            ..Default::default()
+        };
+        let sensor = Matrix3::from_diagonal(&Vector3::new(1.0, 4.0, 9.0));
+        let body = calibration.apply_to_covariance(sensor);
+        assert!((body - Matrix3::from_diagonal(&Vector3::new(4.0, 1.0, 9.0))).norm() < 1e-5);
+        assert_eq!(Calibration::default().apply_to_covariance(sensor), sensor);
+    }
+
+    #[test]
+    fn test_calibration_bias_and_scale() {
+        let convention = EulerConvention::default();
+        let truth = [0.2f32, -0.1, 0.6];
+        let mounting = UnitQuaternion::from_euler_angles(0.0, 0.05, -0.02);
+        let calibration = Calibration {
+            bias: [0.01, -0.02, 0.03],
+            scale: [1.02, 0.99, 1.01],
+            mounting,
+        };
+        let reported = [0, 1, 2].map(|i| calibration.scale[i] * truth[i] + calibration.bias[i]);
+        let corrected = calibration.correct(reported);
+        assert!((0..3).all(|i| (corrected[i] - truth[i]).abs() < 1e-6));
+
+        // In the vehicle frame, whether the sensor's angles arrive as angles or as a quaternion
+        let body = convention.to_quaternion(truth) * mounting.inverse();
+        let from_angles = calibration.apply_to_angles(reported, convention);
+        assert!(from_angles.angle_to(&body) < 1e-3);
+        let from_quaternion =
+            calibration.apply_to_quaternion(convention.to_quaternion(reported), convention);
+        assert!(from_quaternion.angle_to(&body) < 1e-3);
+    }
+
+    #[test]
+    fn test_calibration_file_round_trip() {
+        let calibration = Calibration {
+            bias: [0.01, -0.02, 0.03],
+            scale: [1.02, 0.99, 1.01],
+            mounting: UnitQuaternion::from_euler_angles(0.1, 0.0, -0.3),
+        };
+        let parsed = Calibration::parse(&calibration.to_string()).unwrap();
+        assert_eq!(parsed.bias, calibration.bias);
+        assert_eq!(parsed.scale, calibration.scale);
+        assert!(parsed.mounting.angle_to(&calibration.mounting) < 1e-6);
+
+        // Entries that are left out stay uncalibrated
+        let partial = Calibration::parse("# bench 2\n\nbias 0.5 0 0\n").unwrap();
+        assert_eq!(partial.bias, [0.5, 0.0, 0.0]);
+        assert_eq!(partial.scale, [1.0; 3]);
+
+        assert!(Calibration::parse("bias 1 2").is_err());
+        assert!(Calibration::parse("scale 1 0 1").is_err());
+        assert!(Calibration::parse("offset 1 2 3").is_err());
+        assert!(Calibration::parse("mounting 0 0 0 0").is_err());
+
+        let directory =
+            std::env::temp_dir().join(format!("ravn-calibration-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = calibration_path(&directory, SensorId(4));
+        assert!(path.ends_with("sensor-4.cal"));
+        calibration.save(&path).unwrap();
+        assert_eq!(Calibration::load(&path).unwrap().bias, calibration.bias);
+        std::fs::write(&path, "scale 1 2").unwrap();
+        assert_eq!(
+            Calibration::load(&path).unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,506 @@
+pub mod calibrator {
+
+    use nalgebra::{DMatrix, DVector, UnitQuaternion, Vector3};
+    use std::fmt;
+    use std::fs;
+    use std::io;
+    use std::path::Path;
+
+    use crate::calibrations::calibration::{calibration_path, Calibration};
+    use crate::frames::frame::{decode_frame, FrameLayout};
+    use crate::orientations::orientation::{
+        wrap_to_pi, Axis, EulerAngles, EulerConvention, RotationFrame,
+    };
+    use crate::sensors::sensor::SensorId;
+
+    // Bias, scale and the two identifiable components of the mounting rotation vector
+    const PARAMETERS: usize = 8;
+    // Step for the numerical derivative with respect to the mounting
+    const MOUNTING_STEP: f32 = 1e-3;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct CalibrateConfig {
+        // Layout and convention of the captured frames
+        pub layout: FrameLayout,
+        pub convention: EulerConvention,
+        pub max_iterations: usize,
+        // The fit has converged once a step changes no parameter by more than this
+        pub tolerance: f64,
+    }
+
+    impl Default for CalibrateConfig {
+        fn default() -> Self {
+            CalibrateConfig {
+                layout: FrameLayout::default(),
+                convention: EulerConvention::default(),
+                max_iterations: 50,
+                tolerance: 1e-7,
+            }
+        }
+    }
+
+    // Frames captured while the sensor's body was held still in a known orientation
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct CapturePosition {
+        pub orientation: UnitQuaternion<f32>,
+        pub frames: Vec<Vec<u32>>,
+    }
+
+    // How well the fitted calibration explains one position
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct PositionResidual {
+        // Frames that decoded, out of those captured
+        pub frames: usize,
+        pub corrupted_frames: usize,
+        // Mean measured angles less the angles the calibration predicts (rad)
+        pub residual: [f32; 3],
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct CalibrationFit {
+        pub calibration: Calibration,
+        pub residuals: Vec<PositionResidual>,
+        // Root mean square of every residual angle (rad)
+        pub rms: f32,
+        pub iterations: usize,
+    }
+
+    impl fmt::Display for CalibrationFit {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            writeln!(
+                f,
+                "Calibrated from {} positions in {} iterations; RMS residual {:.6} rad.",
+                self.residuals.len(),
+                self.iterations,
+                self.rms
+            )?;
+            for (index, position) in self.residuals.iter().enumerate() {
+                let [x, y, z] = position.residual;
+                writeln!(
+                    f,
+                    "Position {} ({} frames, {} corrupted): residual {:.6} {:.6} {:.6} rad.",
+                    index + 1,
+                    position.frames,
+                    position.corrupted_frames,
+                    x,
+                    y,
+                    z
+                )?;
+            }
+            write!(f, "{}", self.calibration)
+        }
+    }
+
+    // Mean of each angle over a position's frames, taken on the circle so that angles either side
+    // of ±π average correctly
+    fn mean_angles(
+        frames: &[Vec<u32>],
+        layout: &FrameLayout,
+    ) -> Result<([f64; 3], usize), &'static str> {
+        let mut sums = [(0.0f64, 0.0f64); 3];
+        let mut decoded = 0;
+        for frame in frames {
+            if let Ok(frame) = decode_frame(frame, layout) {
+                for (sum, angle) in sums.iter_mut().zip(frame.angles) {
+                    sum.0 += (angle as f64).sin();
+                    sum.1 += (angle as f64).cos();
+                }
+                decoded += 1;
+            }
+        }
+        if decoded == 0 {
+            return Err("Every capture position needs at least one frame that decodes.");
+        }
+        Ok((sums.map(|(sin, cos)| sin.atan2(cos)), decoded))
+    }
+
+    // The angles a sensor with this calibration reports when its body has this orientation
+    fn predict(
+        calibration: &Calibration,
+        body: UnitQuaternion<f32>,
+        convention: EulerConvention,
+    ) -> [f64; 3] {
+        let sensor = convention.from_quaternion(body * calibration.mounting);
+        [0, 1, 2].map(|i| (calibration.scale[i] * sensor[i] + calibration.bias[i]) as f64)
+    }
+
+    fn residuals(
+        calibration: &Calibration,
+        positions: &[CapturePosition],
+        measured: &[[f64; 3]],
+        convention: EulerConvention,
+    ) -> DVector<f64> {
+        let mut residuals = DVector::zeros(3 * positions.len());
+        for (k, (position, measured)) in positions.iter().zip(measured).enumerate() {
+            let predicted = predict(calibration, position.orientation, convention);
+            for i in 0..3 {
+                residuals[3 * k + i] = wrap_to_pi(measured[i] - predicted[i]);
+            }
+        }
+        residuals
+    }
+
+    // Turning the mounting about the axis of the convention's last body-axis rotation only adds
+    // to that angle, which its bias already covers. The fit leaves that turn out of the mounting:
+    // this gives the angle's index and the two axes the mounting may turn about.
+    fn bias_axis(convention: EulerConvention) -> (usize, [Axis; 2]) {
+        let axes = convention.sequence.axes();
+        let index = match convention.frame {
+            RotationFrame::Intrinsic => 2,
+            RotationFrame::Extrinsic => 0,
+        };
+        let mut free = [Axis::X, Axis::Y, Axis::Z]
+            .into_iter()
+            .filter(|axis| *axis != axes[index]);
+        (index, [free.next().unwrap(), free.next().unwrap()])
+    }
+
+    // Moves any turn about the bias axis out of the mounting and into the bias
+    fn without_bias_turn(calibration: &Calibration, convention: EulerConvention) -> Calibration {
+        let (index, _) = bias_axis(convention);
+        let turn = convention.from_quaternion(calibration.mounting)[index];
+        let axis = convention.sequence.axes()[index];
+        let mut calibration = Calibration {
+            mounting: calibration.mounting
+                * UnitQuaternion::from_axis_angle(&axis.unit_vector(), -turn),
+            ..*calibration
+        };
+        calibration.bias[index] += calibration.scale[index] * turn;
+        calibration
+    }
+
+    // The mounting turned by a small rotation vector about the sensor's own axes
+    fn perturbed(calibration: &Calibration, rotation: Vector3<f32>) -> Calibration {
+        Calibration {
+            mounting: calibration.mounting * UnitQuaternion::from_scaled_axis(rotation),
+            ..*calibration
+        }
+    }
+
+    fn jacobian(
+        calibration: &Calibration,
+        positions: &[CapturePosition],
+        convention: EulerConvention,
+    ) -> DMatrix<f64> {
+        let (_, free) = bias_axis(convention);
+        let mut jacobian = DMatrix::zeros(3 * positions.len(), PARAMETERS);
+        for (k, position) in positions.iter().enumerate() {
+            let sensor = convention.from_quaternion(position.orientation * calibration.mounting);
+            for (column, axis) in free.iter().enumerate() {
+                let step = axis.unit_vector().into_inner() * MOUNTING_STEP;
+                let plus = predict(
+                    &perturbed(calibration, step),
+                    position.orientation,
+                    convention,
+                );
+                let minus = predict(
+                    &perturbed(calibration, -step),
+                    position.orientation,
+                    convention,
+                );
+                for i in 0..3 {
+                    jacobian[(3 * k + i, 6 + column)] =
+                        wrap_to_pi(plus[i] - minus[i]) / (2.0 * MOUNTING_STEP as f64);
+                }
+            }
+            for i in 0..3 {
+                jacobian[(3 * k + i, i)] = 1.0;
+                jacobian[(3 * k + i, 3 + i)] = sensor[i] as f64;
+            }
+        }
+        jacobian
+    }
+
+    // Fits bias, scale and mounting to a stationary capture by Gauss-Newton least squares on the
+    // mean angles of each position. Eight parameters need at least three positions, and the
+    // orientations must differ enough to tell the mounting from the bias. The mounting comes out
+    // with no turn about the bias axis (see `bias_axis`); any such misalignment is in the bias.
+    pub fn calibrate(
+        positions: &[CapturePosition],
+        config: &CalibrateConfig,
+    ) -> Result<CalibrationFit, &'static str> {
+        config.layout.validate()?;
+        if positions.len() < 3 {
+            return Err("Calibration needs at least three capture positions.");
+        }
+        let mut measured = Vec::with_capacity(positions.len());
+        let mut counts = Vec::with_capacity(positions.len());
+        for position in positions {
+            let (mean, decoded) = mean_angles(&position.frames, &config.layout)?;
+            measured.push(mean);
+            counts.push((decoded, position.frames.len() - decoded));
+        }
+
+        let convention = config.convention;
+        let (_, free) = bias_axis(convention);
+        let mut calibration = Calibration::identity();
+        let mut iterations = 0;
+        while iterations < config.max_iterations {
+            iterations += 1;
+            let jacobian = jacobian(&calibration, positions, convention);
+            let residual = residuals(&calibration, positions, &measured, convention);
+            let svd = jacobian.svd(true, true);
+            let largest = svd.singular_values.max();
+            if svd.singular_values.min() <= largest * 1e-6 {
+                return Err(
+                    "Capture positions do not pin down the calibration; use more distinct orientations.",
+                );
+            }
+            let step = svd.solve(&residual, 0.0)?;
+            for i in 0..3 {
+                calibration.bias[i] += step[i] as f32;
+                calibration.scale[i] += step[3 + i] as f32;
+            }
+            let rotation = free[0].unit_vector().into_inner() * step[6] as f32
+                + free[1].unit_vector().into_inner() * step[7] as f32;
+            calibration = perturbed(&calibration, rotation);
+            if step.amax() <= config.tolerance {
+                break;
+            }
+        }
+        if calibration
+            .scale
+            .iter()
+            .any(|scale| !scale.is_finite() || *scale == 0.0)
+        {
+            return Err("Calibration fit did not give a usable scale.");
+        }
+        let calibration = without_bias_turn(&calibration, convention);
+
+        let residual = residuals(&calibration, positions, &measured, convention);
+        let rms = (residual.norm_squared() / residual.len() as f64).sqrt() as f32;
+        let residuals = counts
+            .iter()
+            .enumerate()
+            .map(|(k, (frames, corrupted_frames))| PositionResidual {
+                frames: *frames,
+                corrupted_frames: *corrupted_frames,
+                residual: [0, 1, 2].map(|i| residual[3 * k + i] as f32),
+            })
+            .collect();
+        Ok(CalibrationFit {
+            calibration,
+            residuals,
+            rms,
+            iterations,
+        })
+    }
+
+    // Reads a capture: an `orientation` line with the body's roll, pitch and yaw (in the
+    // default convention) starts each position, and the lines after it are that position's
+    // frames as comma-separated bits. Blank lines and lines starting with `#` are skipped.
+    pub fn parse_capture(text: &str) -> Result<Vec<CapturePosition>, &'static str> {
+        let mut positions: Vec<CapturePosition> = Vec::new();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(angles) = line.strip_prefix("orientation") {
+                let angles = angles
+                    .split_whitespace()
+                    .map(|word| word.parse::<f32>())
+                    .collect::<Result<Vec<f32>, _>>()
+                    .map_err(|_| "Capture orientations must be numbers.")?;
+                let [roll, pitch, yaw] = angles[..] else {
+                    return Err("Capture orientations take roll, pitch and yaw.");
+                };
+                positions.push(CapturePosition {
+                    orientation: EulerAngles::new(roll, pitch, yaw).to_quaternion(),
+                    frames: Vec::new(),
+                });
+                continue;
+            }
+            let frame = line
+                .split(',')
+                .map(|bit| bit.trim().parse::<u32>())
+                .collect::<Result<Vec<u32>, _>>()
+                .map_err(|_| "Capture frames must be comma-separated bits.")?;
+            positions
+                .last_mut()
+                .ok_or("Capture frames must follow an orientation line.")?
+                .frames
+                .push(frame);
+        }
+        Ok(positions)
+    }
+
+    // The `calibrate` mode: fits the capture file and writes the result where
+    // `SimulationConfig::load_calibrations` will find it for this sensor
+    pub fn calibrate_capture(
+        capture: &Path,
+        directory: &Path,
+        sensor: SensorId,
+        config: &CalibrateConfig,
+    ) -> io::Result<CalibrationFit> {
+        let invalid = |error| io::Error::new(io::ErrorKind::InvalidData, error);
+        let positions = parse_capture(&fs::read_to_string(capture)?).map_err(invalid)?;
+        let fit = calibrate(&positions, config).map_err(invalid)?;
+        fit.calibration.save(&calibration_path(directory, sensor))?;
+        Ok(fit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::calibrator::*;
+    use crate::calibrations::calibration::{calibration_path, Calibration};
+    use crate::conversions::conversion::ConversionPolicy;
+    use crate::frames::frame::{Encoder, FrameLayout, SpareByte};
+    use crate::orientations::orientation::{EulerAngles, EulerConvention};
+    use crate::sensors::sensor::SensorId;
+    use nalgebra::UnitQuaternion;
+
+    const FRAMES: usize = 32;
+
+    fn layout() -> FrameLayout {
+        FrameLayout {
+            fractional_bits: 5,
+            signed: true,
+            spare: SpareByte::Unused,
+        }
+    }
+
+    fn orientations() -> Vec<UnitQuaternion<f32>> {
+        [
+            (0.0, 0.0, 0.0),
+            (0.8, 0.0, 0.0),
+            (-0.8, 0.3, 0.0),
+            (0.0, 0.7, 0.5),
+            (0.0, -0.7, -0.5),
+            (0.4, 0.4, 1.2),
+            (-0.5, -0.2, -1.2),
+        ]
+        .iter()
+        .map(|(roll, pitch, yaw)| EulerAngles::new(*roll, *pitch, *yaw).to_quaternion())
+        .collect()
+    }
+
+    // A stationary capture from a sensor with this calibration. The angles are dithered across
+    // one field step over each position's frames, as sensor noise would, so that their mean is
+    // finer than the field resolution.
+    fn capture(calibration: &Calibration) -> Vec<CapturePosition> {
+        let convention = EulerConvention::default();
+        let mut encoder = Encoder::new(layout(), convention, ConversionPolicy::default()).unwrap();
+        let step = layout().resolution();
+        orientations()
+            .into_iter()
+            .map(|orientation| {
+                let sensor = convention.from_quaternion(orientation * calibration.mounting);
+                let frames = (0..FRAMES)
+                    .map(|j| {
+                        let dither = ((j as f32 + 0.5) / FRAMES as f32 - 0.5) * step;
+                        let reported = [0, 1, 2].map(|i| {
+                            calibration.scale[i] * sensor[i] + calibration.bias[i] + dither
+                        });
+                        encoder.encode_angles(reported).unwrap()
+                    })
+                    .collect();
+                CapturePosition {
+                    orientation,
+                    frames,
+                }
+            })
+            .collect()
+    }
+
+    fn truth() -> Calibration {
+        Calibration {
+            bias: [0.05, -0.08, 0.12],
+            scale: [1.04, 0.97, 1.02],
+            mounting: EulerConvention::default().to_quaternion([0.0, -0.02, 0.06]),
+        }
+    }
+
+    fn config() -> CalibrateConfig {
+        CalibrateConfig {
+            layout: layout(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_calibrate_recovers_bias_scale_and_mounting() {
+        let truth = truth();
+        let fit = calibrate(&capture(&truth), &config()).unwrap();
+        let calibration = fit.calibration;
+        for i in 0..3 {
+            assert!(
+                (calibration.bias[i] - truth.bias[i]).abs() < 2e-3,
+                "{:?}",
+                fit
+            );
+            assert!(
+                (calibration.scale[i] - truth.scale[i]).abs() < 2e-3,
+                "{:?}",
+                fit
+            );
+        }
+        assert!(calibration.mounting.angle_to(&truth.mounting) < 2e-3);
+        assert!(fit.rms < 2e-3);
+        assert!(fit.iterations < config().max_iterations);
+        assert_eq!(fit.residuals.len(), orientations().len());
+        assert!(fit
+            .residuals
+            .iter()
+            .all(|position| position.frames == FRAMES && position.corrupted_frames == 0));
+        let report = fit.to_string();
+        assert!(report.starts_with("Calibrated from 7 positions in"));
+        assert!(report.contains("Position 7 (32 frames, 0 corrupted): residual"));
+
+        // A mounting turn about the sensor's x axis only adds to its roll, so it ends up in the
+        // roll bias instead
+        let rolled = Calibration {
+            mounting: truth.mounting * UnitQuaternion::from_euler_angles(0.03, 0.0, 0.0),
+            ..truth
+        };
+        let fit = calibrate(&capture(&rolled), &config()).unwrap();
+        let expected = truth.bias[0] + truth.scale[0] * 0.03;
+        assert!((fit.calibration.bias[0] - expected).abs() < 2e-3);
+        assert!(fit.calibration.mounting.angle_to(&truth.mounting) < 2e-3);
+        assert!(fit.rms < 2e-3);
+    }
+
+    #[test]
+    fn test_calibrate_rejects_underdetermined_captures() {
+        let mut positions = capture(&truth());
+        assert!(calibrate(&positions[..2], &config()).is_err());
+        // The same orientation over and over cannot separate bias from scale
+        let first = positions[0].clone();
+        positions
+            .iter_mut()
+            .for_each(|position| *position = first.clone());
+        assert!(calibrate(&positions, &config()).is_err());
+        positions[0].frames = vec![vec![0, 1]];
+        assert!(calibrate(&positions, &config()).is_err());
+    }
+
+    #[test]
+    fn test_calibrate_capture_file() {
+        let truth = truth();
+        let mut text = String::from("# bench capture, sensor 3\n");
+        for position in capture(&truth) {
+            let [roll, pitch, yaw] =
+                EulerConvention::default().from_quaternion(position.orientation);
+            text.push_str(&format!("orientation {} {} {}\n", roll, pitch, yaw));
+            for frame in &position.frames {
+                let bits: Vec<String> = frame.iter().map(u32::to_string).collect();
+                text.push_str(&bits.join(","));
+                text.push('\n');
+            }
+        }
+        assert_eq!(parse_capture(&text).unwrap().len(), orientations().len());
+        assert!(parse_capture("1,0,1").is_err());
+        assert!(parse_capture("orientation 1 2").is_err());
+
+        let directory =
+            std::env::temp_dir().join(format!("ravn-calibrator-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let capture_path = directory.join("capture.txt");
+        std::fs::write(&capture_path, text).unwrap();
+        let fit = calibrate_capture(&capture_path, &directory, SensorId(3), &config()).unwrap();
+        let saved = Calibration::load(&calibration_path(&directory, SensorId(3))).unwrap();
+        assert_eq!(saved.bias, fit.calibration.bias);
+        assert!((saved.bias[2] - truth.bias[2]).abs() < 2e-3);
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -2,6 +2,9 @@ pub mod conversion {
 
     use nalgebra::Rotation3;
 
+    use crate::fixed_points::fixed_point::{Fixed, FixedStorage, I16F16};
+    use crate::orientations::orientation::{EulerAngles, EulerConvention, SingularityConfig};
+
     pub fn bits_to_u32(bits: &[u32]) -> Result<u32, &'static str> {
         if bits.len() < 8 {
             return Err("Not enough bits to create a u32 from the first 8 bits.");
@@ -32,75 +35,233 @@ pub mod conversion {
         Ok((x, y, z))
     }
 
-    pub fn convert_fixed32_to_float(fixed_value: u32, fractional_bits: u32) -> f32 {
-        // Convert the unsigned integer (fixed-point) back to a floating point value
-        fixed_value as f32 / (1u32 << fractional_bits) as f32
+    // How values outside the range of the fixed-point type are handled
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum OverflowPolicy {
+        #[default]
+        Error,
+        // Clamp to the smallest or largest representable value
+        Saturate,
+        // Keep the low bits, like an integer cast (infinities still saturate)
+        Wrap,
+    }
+
+    // How NaN inputs are handled
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum NanPolicy {
+        #[default]
+        Error,
+        Zero,
+    }
+
+    // How frames whose fields cannot be read at all (too few bits, or bits other than 0 and 1)
+    // are handled where they enter the pipeline
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum MalformedPolicy {
+        #[default]
+        Error,
+        // Drop the frame, as if it had never arrived
+        Skip,
     }
 
-    pub fn convert_float_to_fixed32(value: f32, fractional_bits: u32) -> Result<u32, &'static str> {
-        // Calculate the fixed-point representation
-        let scaled_value = value * (1u32 << fractional_bits) as f32;
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct ConversionPolicy {
+        pub overflow: OverflowPolicy,
+        pub nan: NanPolicy,
+        pub malformed: MalformedPolicy,
+    }
 
-        // Check if the scaled value fits within the u32 range
-        // Note: Depending on the application, you might want to set to u32::MIN or u32::MAX instead of returning an error
-        if scaled_value < 0.0 {
-            return Err("Underflow: value is too small for u32");
-        } else if scaled_value > u32::MAX as f32 {
-            return Err("Overflow: value is too large for u32");
+    impl ConversionPolicy {
+        pub fn saturating() -> Self {
+            ConversionPolicy {
+                overflow: OverflowPolicy::Saturate,
+                nan: NanPolicy::Zero,
+                malformed: MalformedPolicy::Skip,
+            }
         }
+    }
 
-        // Convert to u32 and return
-        Ok(scaled_value.round() as u32)
+    // Counts of values that a lenient policy altered instead of converting exactly
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct ConversionStats {
+        pub saturated: usize,
+        pub wrapped: usize,
+        pub nan_zeroed: usize,
+        // Frames dropped because their fields could not be read
+        pub malformed: usize,
     }
 
-    pub fn fixed_points_triplet_from_bits_euler_angles(euler_angles: Vec<u32>) -> Vec<u32> {
-        // Access the Euler angles (convert bits to three floating points stored as u32)
+    impl ConversionStats {
+        pub fn merge(&mut self, other: &ConversionStats) {
+            self.saturated += other.saturated;
+            self.wrapped += other.wrapped;
+            self.nan_zeroed += other.nan_zeroed;
+            self.malformed += other.malformed;
+        }
+    }
+
+    // The raw integer of a fixed-point value with `fractional_bits` fractional bits. This is the
+    // one place where the overflow and NaN policies are applied, whether the target is a `Fixed`
+    // type or a frame field whose layout is only known at run time. Wrapping keeps the value
+    // modulo the size of `[min, max]`, which is two's complement for signed ranges.
+    pub fn float_to_raw_with_policy(
+        value: f64,
+        fractional_bits: u32,
+        (min, max): (i128, i128),
+        policy: ConversionPolicy,
+        stats: &mut ConversionStats,
+    ) -> Result<i128, &'static str> {
+        if value.is_nan() {
+            return match policy.nan {
+                NanPolicy::Error => Err("Invalid value: NaN cannot be converted to fixed-point"),
+                NanPolicy::Zero => {
+                    stats.nan_zeroed += 1;
+                    Ok(0)
+                }
+            };
+        }
+
+        // Powers of two are exact, so only the final rounding loses precision
+        let scaled = (value * 2f64.powi(fractional_bits as i32)).round();
+        if scaled >= min as f64 && scaled <= max as f64 {
+            return Ok(scaled as i128);
+        }
+
+        match policy.overflow {
+            OverflowPolicy::Error if scaled < 0.0 => {
+                Err("Underflow: value is too small for the fixed-point range")
+            }
+            OverflowPolicy::Error => Err("Overflow: value is too large for the fixed-point range"),
+            OverflowPolicy::Wrap if scaled.is_finite() => {
+                stats.wrapped += 1;
+                let modulus = (max - min + 1) as f64;
+                Ok(min + (scaled - min as f64).rem_euclid(modulus) as i128)
+            }
+            OverflowPolicy::Saturate | OverflowPolicy::Wrap => {
+                stats.saturated += 1;
+                Ok(if scaled < 0.0 { min } else { max })
+            }
+        }
+    }
+
+    // The value of a raw fixed-point integer, scaled in f64 so that it is exact for every raw
+    // value up to 53 bits
+    pub fn raw_to_float(raw: i128, fractional_bits: u32) -> f64 {
+        raw as f64 / 2f64.powi(fractional_bits as i32)
+    }
+
+    pub fn convert_float_to_fixed32_with_policy(
+        value: f32,
+        fractional_bits: u32,
+        policy: ConversionPolicy,
+        stats: &mut ConversionStats,
+    ) -> Result<u32, &'static str> {
+        let range = (u32::MIN as i128, u32::MAX as i128);
+        let raw = float_to_raw_with_policy(value as f64, fractional_bits, range, policy, stats)?;
+        Ok(raw as u32)
+    }
+
+    pub fn convert_float_to_fixed<I: FixedStorage, const FRAC: u32>(
+        value: f32,
+        policy: ConversionPolicy,
+        stats: &mut ConversionStats,
+    ) -> Result<Fixed<I, FRAC>, &'static str> {
+        let range = (I::min_i128(), I::max_i128());
+        let raw = float_to_raw_with_policy(value as f64, FRAC, range, policy, stats)?;
+        // 64-bit bounds are not exact in f64, so clamp what rounded just past them
+        Ok(Fixed::from_bits(I::saturating_from_i128(raw)))
+    }
+
+    // Signed, so that angles from signed frame layouts survive the trip to the display thread
+    pub type FixedTriplet = [I16F16; 3];
+
+    pub fn fixed_points_triplet_from_bits_euler_angles(euler_angles: Vec<u32>) -> FixedTriplet {
+        // Access the Euler angles (convert bits to three fixed-point values)
         let (x, y, z): (u32, u32, u32) = bits_to_u32_triplet(&euler_angles)
             .expect("Unable to get correct conversion from Euler angles.");
 
-        // Convert the Euler angles to floating point values
-        let fractional_bits = 16;
-        let fixed_values_triplet: (u32, u32, u32) = (x, y, z);
-        let result_x: f32 = convert_fixed32_to_float(fixed_values_triplet.0, fractional_bits);
-        let result_y: f32 = convert_fixed32_to_float(fixed_values_triplet.1, fractional_bits);
-        let result_z: f32 = convert_fixed32_to_float(fixed_values_triplet.2, fractional_bits);
+        // Each 8-bit field is the raw Q16.16 representation of the angle
+        [
+            I16F16::from_bits(x as i32),
+            I16F16::from_bits(y as i32),
+            I16F16::from_bits(z as i32),
+        ]
+    }
 
-        // Convert the floating point values to fixed-point representation
-        let fixed_x: u32 = convert_float_to_fixed32(result_x, fractional_bits)
-            .expect("Unable to get correct conversion from Euler angles.");
-        let fixed_y: u32 = convert_float_to_fixed32(result_y, fractional_bits)
-            .expect("Unable to get correct conversion from Euler angles.");
-        let fixed_z: u32 = convert_float_to_fixed32(result_z, fractional_bits)
-            .expect("Unable to get correct conversion from Euler angles.");
+    pub fn float_euler_angles_from_bits(
+        euler_angles: &[u32],
+    ) -> Result<(f32, f32, f32), &'static str> {
+        // Each 8-bit field is the raw Q16.16 representation of the angle, read through the same
+        // signed type as `FixedTriplet`
+        let (x, y, z): (u32, u32, u32) = bits_to_u32_triplet(euler_angles)?;
+        Ok((
+            I16F16::from_bits(x as i32).to_f32(),
+            I16F16::from_bits(y as i32).to_f32(),
+            I16F16::from_bits(z as i32).to_f32(),
+        ))
+    }
 
-        vec![fixed_x, fixed_y, fixed_z]
+    pub fn fixed_points_triplet_from_float_euler_angles(
+        euler_angles: (f32, f32, f32),
+        policy: ConversionPolicy,
+        stats: &mut ConversionStats,
+    ) -> Result<FixedTriplet, &'static str> {
+        let (x, y, z) = euler_angles;
+        Ok([
+            convert_float_to_fixed(x, policy, stats)?,
+            convert_float_to_fixed(y, policy, stats)?,
+            convert_float_to_fixed(z, policy, stats)?,
+        ])
     }
 
-    pub fn reconstructed_euler_angles_from_fixed_points(euler_angles: Vec<u32>) -> (f32, f32, f32) {
-        // Access the Euler angles (three u32 values used to represent the floating point values)
-        let x: u32 = euler_angles[0]; // Assuming this represents the roll
-        let y: u32 = euler_angles[1]; // Assuming this represents the pitch
-        let z: u32 = euler_angles[2]; // Assuming this represents the yaw
+    pub fn reconstructed_euler_angles_from_fixed_points(
+        euler_angles: FixedTriplet,
+    ) -> (f32, f32, f32) {
+        // Access the Euler angles (three fixed-point values)
+        let [x, y, z] = euler_angles; // Assuming these represent roll, pitch and yaw
 
         // Convert the Euler angles to floating point values
-        let fractional_bits = 16;
-        let fixed_values_triplet: (u32, u32, u32) = (x, y, z);
-        let result_x: f32 = convert_fixed32_to_float(fixed_values_triplet.0, fractional_bits);
-        let result_y: f32 = convert_fixed32_to_float(fixed_values_triplet.1, fractional_bits);
-        let result_z: f32 = convert_fixed32_to_float(fixed_values_triplet.2, fractional_bits);
+        let result_x: f32 = x.to_f32();
+        let result_y: f32 = y.to_f32();
+        let result_z: f32 = z.to_f32();
 
         // Creates a new rotation from the given Euler angles (in order roll, pitch, yaw)
         let rotation = Rotation3::from_euler_angles(result_x, result_y, result_z);
         let (roll, pitch, yaw) = rotation.euler_angles();
         (roll, pitch, yaw)
     }
+
+    pub fn reconstructed_euler_angles_with_conventions(
+        euler_angles: FixedTriplet,
+        input_convention: EulerConvention,
+        output_convention: EulerConvention,
+        singularity: &SingularityConfig,
+        previous_third: Option<f32>,
+    ) -> EulerAngles {
+        // The fixed-point angles are in the sensor's convention; re-express them in the requested one
+        let [x, y, z] = euler_angles;
+        let input =
+            EulerAngles::with_convention([x.to_f32(), y.to_f32(), z.to_f32()], input_convention);
+        output_convention.extract(input.to_quaternion(), singularity, previous_third)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::conversion::*;
+    use crate::fixed_points::fixed_point::{I16F16, U16F16};
     use std::f32::consts::PI;
 
+    // Out-of-range values are errors, while NaN converts to zero
+    fn float_to_u16f16(value: f32) -> Result<u32, &'static str> {
+        let policy = ConversionPolicy {
+            nan: NanPolicy::Zero,
+            ..Default::default()
+        };
+        convert_float_to_fixed::<u32, 16>(value, policy, &mut ConversionStats::default())
+            .map(U16F16::to_bits)
+    }
+
     #[test]
     fn test_bits_to_u32_triplet_valid() {
         let bits: Vec<u32> = vec![
@@ -189,99 +350,91 @@ mod tests {
     }
 
     #[test]
-    fn test_convert_fixed32_to_float() {
-        let fractional_bits = 16;
-
+    fn test_u16f16_to_float() {
         // Test conversion of a fixed-point value back to float
         let fixed_value = 205887;
-        let result = convert_fixed32_to_float(fixed_value, fractional_bits);
+        let result = U16F16::from_bits(fixed_value).to_f32();
         println!("res 1: {:?}", result);
         assert!((result - PI).abs() < 1e-5); // Expected float value (within tolerance)
 
         // Test conversion of zero
         let fixed_value = 0;
-        let result = convert_fixed32_to_float(fixed_value, fractional_bits);
+        let result = U16F16::from_bits(fixed_value).to_f32();
         println!("res 2: {:?}", result);
         assert_eq!(result, 0.0); // Expected float value
 
         // Test conversion of a small fixed-point value
         let fixed_value = 7;
-        let result = convert_fixed32_to_float(fixed_value, fractional_bits);
+        let result = U16F16::from_bits(fixed_value).to_f32();
         println!("res 3: {:?}", result);
         assert!((result - 0.0001).abs() < 1e-5); // Expected float value (within tolerance)
     }
 
     #[test]
-    fn test_convert_fixed32_to_float_large_value() {
+    fn test_u16f16_to_float_large_value() {
         let fractional_bits = 16;
         let fixed_value = u32::MAX; // Maximum value for u32
-        let result = convert_fixed32_to_float(fixed_value, fractional_bits);
+        let result = U16F16::from_bits(fixed_value).to_f32();
         let expected = (u32::MAX as f32) / ((1u32 << fractional_bits) as f32);
         assert!((result - expected).abs() < 1e-5);
     }
 
     #[test]
-    fn test_convert_fixed32_to_float_negative() {
+    fn test_u16f16_to_float_negative() {
         // Since u32 cannot represent negative numbers, this test is not applicable.
         // We skip the negative case because u32 does not support negative values.
         let value = -PI;
-        let fractional_bits = 16;
-        let result = convert_float_to_fixed32(value, fractional_bits);
+        let result = float_to_u16f16(value);
         assert!(result.is_err());
     }
 
     #[test]
-    fn test_convert_fixed32_to_float_small_value() {
+    fn test_u16f16_to_float_small_value() {
         let fractional_bits = 16;
         let fixed_value = 1; // Smallest possible value for a non-zero fixed-point number
-        let result = convert_fixed32_to_float(fixed_value, fractional_bits);
+        let result = U16F16::from_bits(fixed_value).to_f32();
         let expected = 1.0 / (1u32 << fractional_bits) as f32;
         assert!((result - expected).abs() < 1e-7);
     }
 
     #[test]
-    fn test_convert_float_to_fixed32_zero() {
+    fn test_float_to_u16f16_zero() {
         let value = 0.0;
-        let fractional_bits = 16;
-        let result = convert_float_to_fixed32(value, fractional_bits);
+        let result = float_to_u16f16(value);
         assert_eq!(result.unwrap(), 0);
     }
 
     #[test]
-    fn test_convert_float_to_fixed32_positive() {
+    fn test_float_to_u16f16_positive() {
         let value = PI;
-        let fractional_bits = 16;
-        let result = convert_float_to_fixed32(value, fractional_bits);
+        let result = float_to_u16f16(value);
         assert_eq!(result.unwrap(), 205887);
     }
 
     #[test]
-    fn test_convert_float_to_fixed32_negative() {
+    fn test_float_to_u16f16_negative() {
         let value = -PI;
-        let fractional_bits = 16;
-        let result = convert_float_to_fixed32(value, fractional_bits);
+        let result = float_to_u16f16(value);
         // The behavior is not defined here since we don't handle negative values in this example.
         // Skipping this test.
         assert!(result.is_err());
     }
 
     #[test]
-    fn test_convert_float_to_fixed32_small_value() {
+    fn test_float_to_u16f16_small_value() {
         let value = 0.0001;
-        let fractional_bits = 16;
-        let result = convert_float_to_fixed32(value, fractional_bits);
+        let result = float_to_u16f16(value);
         assert_eq!(result.unwrap(), 7);
     }
 
     #[test]
-    fn test_convert_float_to_fixed32_large_value() {
+    fn test_float_to_u16f16_large_value() {
         let value = 1e10;
-        let fractional_bits = 16;
-        let result = convert_float_to_fixed32(value, fractional_bits);
+        let result = float_to_u16f16(value);
         assert!(result.is_err());
         assert_eq!(
             result.err().unwrap(),
-            "Overflow: value is too large for u32"
+            "Overflow: value is too large for the fixed-point range"
         );
     }
 
@@ -295,7 +448,14 @@ mod tests {
         ];
 
         let result = fixed_points_triplet_from_bits_euler_angles(euler_angles);
-        assert_eq!(result, vec![178, 11, 241]);
+        assert_eq!(
+            result,
+            [
+                I16F16::from_bits(178),
+                I16F16::from_bits(11),
+                I16F16::from_bits(241)
+            ]
+        );
     }
 
     #[test]
@@ -307,9 +467,226 @@ mod tests {
             1, 1, 1, 0, 1, 0, 0, 0, // Last 8 bits unused
         ];
 
-        let result = reconstructed_euler_angles_from_fixed_points(euler_angles);
+        let result = reconstructed_euler_angles_from_fixed_points(
+            fixed_points_triplet_from_bits_euler_angles(euler_angles),
+        );
         assert!((result.0 - 0.00001).abs() <= 1e-1); // Expected roll value (within tolerance)
         assert!((result.1 - 0.00001).abs() <= 1e-1); // Expected pitch value (within tolerance)
         assert!((result.2 - 0.00001).abs() < 1e-1); // Expected yaw value (within tolerance)
     }
+
+    #[test]
+    fn test_float_to_u16f16_nan_is_zero() {
+        // The legacy conversion has always turned NaN into zero
+        let result = float_to_u16f16(f32::NAN);
+        assert_eq!(result.unwrap(), 0);
+    }
+
+    #[test]
+    fn test_convert_float_to_fixed32_with_policy_saturate() {
+        let policy = ConversionPolicy {
+            overflow: OverflowPolicy::Saturate,
+            nan: NanPolicy::Error,
+            ..Default::default()
+        };
+        let mut stats = ConversionStats::default();
+
+        let result = convert_float_to_fixed32_with_policy(-PI, 16, policy, &mut stats);
+        assert_eq!(result.unwrap(), u32::MIN);
+        let result = convert_float_to_fixed32_with_policy(1e10, 16, policy, &mut stats);
+        assert_eq!(result.unwrap(), u32::MAX);
+        let result = convert_float_to_fixed32_with_policy(PI, 16, policy, &mut stats);
+        assert_eq!(result.unwrap(), 205887);
+
+        assert_eq!(stats.saturated, 2);
+        assert_eq!(stats.wrapped, 0);
+    }
+
+    #[test]
+    fn test_convert_float_to_fixed32_with_policy_wrap() {
+        let policy = ConversionPolicy {
+            overflow: OverflowPolicy::Wrap,
+            nan: NanPolicy::Error,
+            ..Default::default()
+        };
+        let mut stats = ConversionStats::default();
+
+        // -1/65536 wraps around to the largest u32
+        let value = -1.0 / 65536.0;
+        let result = convert_float_to_fixed32_with_policy(value, 16, policy, &mut stats);
+        assert_eq!(result.unwrap(), u32::MAX);
+        // 65537.0 is one whole unit past the u32 range
+        let result = convert_float_to_fixed32_with_policy(65537.0, 16, policy, &mut stats);
+        assert_eq!(result.unwrap(), 65536);
+        // Infinity has no low bits to keep, so it saturates
+        let result = convert_float_to_fixed32_with_policy(f32::INFINITY, 16, policy, &mut stats);
+        assert_eq!(result.unwrap(), u32::MAX);
+
+        assert_eq!(stats.wrapped, 2);
+        assert_eq!(stats.saturated, 1);
+    }
+
+    #[test]
+    fn test_convert_float_to_fixed32_with_policy_nan() {
+        let mut stats = ConversionStats::default();
+
+        let result = convert_float_to_fixed32_with_policy(
+            f32::NAN,
+            16,
+            ConversionPolicy::default(),
+            &mut stats,
+        );
+        assert_eq!(
+            result.err().unwrap(),
+            "Invalid value: NaN cannot be converted to fixed-point"
+        );
+
+        let result = convert_float_to_fixed32_with_policy(
+            f32::NAN,
+            16,
+            ConversionPolicy::saturating(),
+            &mut stats,
+        );
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(stats.nan_zeroed, 1);
+    }
+
+    #[test]
+    fn test_convert_float_to_fixed_policies() {
+        let mut stats = ConversionStats::default();
+
+        let result: Result<U16F16, &str> =
+            convert_float_to_fixed(-1.0, ConversionPolicy::default(), &mut stats);
+        assert_eq!(
+            result.err().unwrap(),
+            "Underflow: value is too small for the fixed-point range"
+        );
+
+        let result: U16F16 =
+            convert_float_to_fixed(-1.0, ConversionPolicy::saturating(), &mut stats).unwrap();
+        assert_eq!(result, U16F16::zero());
+
+        let wrap = ConversionPolicy {
+            overflow: OverflowPolicy::Wrap,
+            nan: NanPolicy::Error,
+            ..Default::default()
+        };
+        let result: U16F16 = convert_float_to_fixed(65536.5, wrap, &mut stats).unwrap();
+        assert_eq!(result.to_f32(), 0.5);
+
+        assert_eq!(stats.saturated, 1);
+        assert_eq!(stats.wrapped, 1);
+    }
+
+    #[test]
+    fn test_fixed_points_triplet_from_float_euler_angles() {
+        let mut stats = ConversionStats::default();
+
+        let result = fixed_points_triplet_from_float_euler_angles(
+            (0.5, -40000.0, 70000.0),
+            ConversionPolicy::default(),
+            &mut stats,
+        );
+        assert!(result.is_err());
+
+        let result = fixed_points_triplet_from_float_euler_angles(
+            (0.5, -40000.0, 70000.0),
+            ConversionPolicy::saturating(),
+            &mut stats,
+        )
+        .unwrap();
+        assert_eq!(result[0].to_f32(), 0.5);
+        assert_eq!(result[1], I16F16::min_value());
+        assert_eq!(result[2], I16F16::max_value());
+
+        // Negative angles are carried as they are
+        let result = fixed_points_triplet_from_float_euler_angles(
+            (-0.25, 0.0, 0.0),
+            ConversionPolicy::default(),
+            &mut stats,
+        )
+        .unwrap();
+        assert_eq!(result[0].to_f32(), -0.25);
+        assert_eq!(stats.saturated, 2);
+    }
+
+    #[test]
+    fn test_float_euler_angles_from_bits() {
+        let euler_angles: Vec<u32> = vec![
+            1, 0, 1, 1, 0, 0, 1, 0, // x: 178
+            0, 0, 0, 0, 1, 0, 1, 1, // y: 11
+            1, 1, 1, 1, 0, 0, 0, 1, // z: 241
+            1, 1, 1, 0, 1, 0, 0, 0, // Last 8 bits unused
+        ];
+
+        let (x, y, z) = float_euler_angles_from_bits(&euler_angles).unwrap();
+        assert_eq!(x, 178.0 / 65536.0);
+        assert_eq!(y, 11.0 / 65536.0);
+        assert_eq!(z, 241.0 / 65536.0);
+    }
+
+    #[test]
+    fn test_reconstructed_euler_angles_with_conventions() {
+        use crate::orientations::orientation::{EulerConvention, EulerSequence, SingularityConfig};
+
+        let euler_angles = [
+            I16F16::checked_from_f64(0.1).unwrap(),
+            I16F16::checked_from_f64(0.2).unwrap(),
+            I16F16::checked_from_f64(0.3).unwrap(),
+        ];
+
+        // The default conventions agree with the original reconstruction
+        let expected = reconstructed_euler_angles_from_fixed_points(euler_angles);
+        let result = reconstructed_euler_angles_with_conventions(
+            euler_angles,
+            EulerConvention::default(),
+            EulerConvention::default(),
+            &SingularityConfig::default(),
+            None,
+        );
+        assert!((result.angles[0] - expected.0).abs() < 1e-5);
+        assert!((result.angles[1] - expected.1).abs() < 1e-5);
+        assert!((result.angles[2] - expected.2).abs() < 1e-5);
+
+        // A ZYX vendor's (yaw, pitch, roll) come out as our (roll, pitch, yaw)
+        let result = reconstructed_euler_angles_with_conventions(
+            euler_angles,
+            EulerConvention::intrinsic(EulerSequence::ZYX),
+            EulerConvention::default(),
+            &SingularityConfig::default(),
+            None,
+        );
+        assert!((result.angles[0] - 0.3).abs() < 1e-4);
+        assert!((result.angles[1] - 0.2).abs() < 1e-4);
+        assert!((result.angles[2] - 0.1).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_reconstructed_euler_angles_flags_gimbal_lock() {
+        use crate::orientations::orientation::{
+            EulerConvention, EulerSequence, SingularityConfig, SingularityStrategy,
+        };
+
+        // A ZYX sensor reporting 90° pitch is in gimbal lock in our roll-pitch-yaw output too
+        let half_pi = I16F16::checked_from_f64(std::f64::consts::FRAC_PI_2).unwrap();
+        let euler_angles = [
+            I16F16::checked_from_f64(0.5).unwrap(),
+            half_pi,
+            I16F16::zero(),
+        ];
+        let singularity = SingularityConfig {
+            threshold: 0.01,
+            strategy: SingularityStrategy::ZeroThird,
+        };
+
+        let result = reconstructed_euler_angles_with_conventions(
+            euler_angles,
+            EulerConvention::intrinsic(EulerSequence::ZYX),
+            EulerConvention::default(),
+            &singularity,
+            None,
+        );
+        assert!(result.near_singularity);
+        assert_eq!(result.angles[2], 0.0);
+    }
 }
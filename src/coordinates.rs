@@ -0,0 +1,341 @@
+pub mod coordinate {
+
+    use nalgebra::{Matrix3, Unit, UnitQuaternion, Vector3};
+    use std::f32::consts::{FRAC_1_SQRT_2, PI};
+    use std::fmt;
+    use std::marker::PhantomData;
+
+    // Axes of the earth-fixed frame an attitude is measured against
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum WorldAxes {
+        // North, east, down: aerospace
+        Ned,
+        // East, north, up: ROS and most robotics
+        Enu,
+        // North, west, up: the AHRS earth frame
+        Nwu,
+    }
+
+    impl WorldAxes {
+        // Takes coordinates along the NED axes to coordinates along these
+        pub fn from_ned(self) -> UnitQuaternion<f32> {
+            match self {
+                WorldAxes::Ned => UnitQuaternion::identity(),
+                // Swaps north and east and flips down to up: a half turn about north-east
+                WorldAxes::Enu => UnitQuaternion::from_axis_angle(
+                    &Unit::new_unchecked(Vector3::new(FRAC_1_SQRT_2, FRAC_1_SQRT_2, 0.0)),
+                    PI,
+                ),
+                WorldAxes::Nwu => UnitQuaternion::from_axis_angle(&Vector3::x_axis(), PI),
+            }
+        }
+    }
+
+    impl fmt::Display for WorldAxes {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                WorldAxes::Ned => f.write_str("ned"),
+                WorldAxes::Enu => f.write_str("enu"),
+                WorldAxes::Nwu => f.write_str("nwu"),
+            }
+        }
+    }
+
+    // Axes fixed to the vehicle
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum BodyAxes {
+        // Forward, right, down: aerospace
+        Frd,
+        // Forward, left, up: ROS
+        Flu,
+    }
+
+    impl BodyAxes {
+        // Takes coordinates along the FRD axes to coordinates along these
+        pub fn from_frd(self) -> UnitQuaternion<f32> {
+            match self {
+                BodyAxes::Frd => UnitQuaternion::identity(),
+                BodyAxes::Flu => UnitQuaternion::from_axis_angle(&Vector3::x_axis(), PI),
+            }
+        }
+    }
+
+    impl fmt::Display for BodyAxes {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                BodyAxes::Frd => f.write_str("frd"),
+                BodyAxes::Flu => f.write_str("flu"),
+            }
+        }
+    }
+
+    // The frames a stream's attitudes are expressed in: each attitude takes coordinates along the
+    // body axes to coordinates along the world axes
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct Frames {
+        pub world: WorldAxes,
+        pub body: BodyAxes,
+    }
+
+    impl Frames {
+        pub const NED_FRD: Frames = Frames {
+            world: WorldAxes::Ned,
+            body: BodyAxes::Frd,
+        };
+
+        pub const ENU_FLU: Frames = Frames {
+            world: WorldAxes::Enu,
+            body: BodyAxes::Flu,
+        };
+
+        // What Ravn has always produced without saying so: the AHRS earth frame, with the body
+        // axes of a sensor whose accelerometer reads +z when level
+        pub const NWU_FLU: Frames = Frames {
+            world: WorldAxes::Nwu,
+            body: BodyAxes::Flu,
+        };
+
+        // Takes body coordinates along `to.body` to body coordinates along `self.body`
+        fn body_from(self, to: Frames) -> UnitQuaternion<f32> {
+            self.body.from_frd() * to.body.from_frd().inverse()
+        }
+
+        // The same attitude, expressed in the `to` frames
+        pub fn convert(self, attitude: UnitQuaternion<f32>, to: Frames) -> UnitQuaternion<f32> {
+            let world = to.world.from_ned() * self.world.from_ned().inverse();
+            world * attitude * self.body_from(to)
+        }
+
+        // An attitude error covariance about these body axes, re-expressed about `to.body`
+        pub fn convert_covariance(self, covariance: Matrix3<f32>, to: Frames) -> Matrix3<f32> {
+            let rotation = to.body_from(self).to_rotation_matrix();
+            rotation.matrix() * covariance * rotation.matrix().transpose()
+        }
+    }
+
+    impl Default for Frames {
+        fn default() -> Self {
+            Frames::NWU_FLU
+        }
+    }
+
+    impl fmt::Display for Frames {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}/{}", self.world, self.body)
+        }
+    }
+
+    // Frames fixed at compile time, for library code that must not mix them up. Each marker type
+    // stands for one set of axes.
+    pub trait WorldFrame: Copy + fmt::Debug + PartialEq {
+        const AXES: WorldAxes;
+    }
+
+    pub trait BodyFrame: Copy + fmt::Debug + PartialEq {
+        const AXES: BodyAxes;
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Ned;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Enu;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Nwu;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Frd;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Flu;
+
+    impl WorldFrame for Ned {
+        const AXES: WorldAxes = WorldAxes::Ned;
+    }
+
+    impl WorldFrame for Enu {
+        const AXES: WorldAxes = WorldAxes::Enu;
+    }
+
+    impl WorldFrame for Nwu {
+        const AXES: WorldAxes = WorldAxes::Nwu;
+    }
+
+    impl BodyFrame for Frd {
+        const AXES: BodyAxes = BodyAxes::Frd;
+    }
+
+    impl BodyFrame for Flu {
+        const AXES: BodyAxes = BodyAxes::Flu;
+    }
+
+    // A vector along the axes of frame `F`
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct FrameVector<F> {
+        pub vector: Vector3<f32>,
+        frame: PhantomData<F>,
+    }
+
+    impl<F> FrameVector<F> {
+        pub fn new(vector: Vector3<f32>) -> Self {
+            FrameVector {
+                vector,
+                frame: PhantomData,
+            }
+        }
+    }
+
+    impl<W: WorldFrame> FrameVector<W> {
+        pub fn to_world<W2: WorldFrame>(self) -> FrameVector<W2> {
+            let rotation = W2::AXES.from_ned() * W::AXES.from_ned().inverse();
+            FrameVector::new(rotation * self.vector)
+        }
+    }
+
+    impl<B: BodyFrame> FrameVector<B> {
+        pub fn to_body<B2: BodyFrame>(self) -> FrameVector<B2> {
+            let rotation = B2::AXES.from_frd() * B::AXES.from_frd().inverse();
+            FrameVector::new(rotation * self.vector)
+        }
+    }
+
+    // An attitude from body frame `B` to world frame `W`. Functions that take one only accept
+    // the frames they name, so a mismatch is a compile error rather than a sign flip.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Attitude<W: WorldFrame, B: BodyFrame> {
+        quaternion: UnitQuaternion<f32>,
+        frames: PhantomData<(W, B)>,
+    }
+
+    impl<W: WorldFrame, B: BodyFrame> Attitude<W, B> {
+        pub const FRAMES: Frames = Frames {
+            world: W::AXES,
+            body: B::AXES,
+        };
+
+        // The quaternion is taken to be in `W` and `B` already
+        pub fn new(quaternion: UnitQuaternion<f32>) -> Self {
+            Attitude {
+                quaternion,
+                frames: PhantomData,
+            }
+        }
+
+        // An attitude tagged with frames only known at run time, converted into `W` and `B`
+        pub fn from_frames(quaternion: UnitQuaternion<f32>, frames: Frames) -> Self {
+            Attitude::new(frames.convert(quaternion, Self::FRAMES))
+        }
+
+        pub fn quaternion(&self) -> UnitQuaternion<f32> {
+            self.quaternion
+        }
+
+        pub fn convert<W2: WorldFrame, B2: BodyFrame>(&self) -> Attitude<W2, B2> {
+            Attitude::from_frames(self.quaternion, Self::FRAMES)
+        }
+
+        pub fn body_to_world(&self, vector: FrameVector<B>) -> FrameVector<W> {
+            FrameVector::new(self.quaternion * vector.vector)
+        }
+
+        pub fn world_to_body(&self, vector: FrameVector<W>) -> FrameVector<B> {
+            FrameVector::new(self.quaternion.inverse() * vector.vector)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::coordinate::*;
+    use nalgebra::{Matrix3, UnitQuaternion, Vector3};
+    use std::f32::consts::FRAC_PI_2;
+
+    fn close(a: Vector3<f32>, b: Vector3<f32>) -> bool {
+        (a - b).norm() < 1e-5
+    }
+
+    #[test]
+    fn test_axes_rotations() {
+        // North, east and down along each set of world axes
+        let north = Vector3::new(1.0, 0.0, 0.0);
+        let east = Vector3::new(0.0, 1.0, 0.0);
+        let down = Vector3::new(0.0, 0.0, 1.0);
+        let enu = WorldAxes::Enu.from_ned();
+        assert!(close(enu * north, Vector3::new(0.0, 1.0, 0.0)));
+        assert!(close(enu * east, Vector3::new(1.0, 0.0, 0.0)));
+        assert!(close(enu * down, Vector3::new(0.0, 0.0, -1.0)));
+        let nwu = WorldAxes::Nwu.from_ned();
+        assert!(close(nwu * east, Vector3::new(0.0, -1.0, 0.0)));
+        assert!(close(nwu * down, Vector3::new(0.0, 0.0, -1.0)));
+
+        let right = Vector3::new(0.0, 1.0, 0.0);
+        assert!(close(
+            BodyAxes::Flu.from_frd() * right,
+            Vector3::new(0.0, -1.0, 0.0)
+        ));
+        assert_eq!(Frames::default().to_string(), "nwu/flu");
+    }
+
+    #[test]
+    fn test_frames_convert() {
+        // Heading 90° (east) in NED/FRD is a yaw of 0 in ENU/FLU, the ROS convention
+        let east = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), FRAC_PI_2);
+        let enu = Frames::NED_FRD.convert(east, Frames::ENU_FLU);
+        assert!(enu.angle_to(&UnitQuaternion::identity()) < 1e-5);
+
+        // Pitching nose up is a positive rotation about right in FRD, and a negative one about
+        // left in FLU
+        let nose_up = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.3);
+        let enu = Frames::NED_FRD.convert(nose_up, Frames::ENU_FLU);
+        let expected = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), FRAC_PI_2)
+            * UnitQuaternion::from_axis_angle(&Vector3::y_axis(), -0.3);
+        assert!(enu.angle_to(&expected) < 1e-5);
+        let back = Frames::ENU_FLU.convert(enu, Frames::NED_FRD);
+        assert!(back.angle_to(&nose_up) < 1e-5);
+
+        // Only the body axes move the covariance
+        let covariance = Matrix3::new(1.0, 0.5, 0.0, 0.5, 4.0, 0.0, 0.0, 0.0, 9.0);
+        let flu = Frames::NED_FRD.convert_covariance(covariance, Frames::ENU_FLU);
+        assert!((flu - Matrix3::new(1.0, -0.5, 0.0, -0.5, 4.0, 0.0, 0.0, 0.0, 9.0)).norm() < 1e-5);
+        let ned_flu = Frames {
+            world: WorldAxes::Ned,
+            body: BodyAxes::Flu,
+        };
+        assert_eq!(Frames::ENU_FLU.convert_covariance(flu, ned_flu), flu);
+    }
+
+    // Only an ENU/FLU attitude can be handed to this; anything else has to be converted first
+    fn heading_ros(attitude: Attitude<Enu, Flu>) -> f32 {
+        attitude.quaternion().euler_angles().2
+    }
+
+    #[test]
+    fn test_typed_attitudes() {
+        let heading = 0.4;
+        let ned =
+            Attitude::<Ned, Frd>::new(UnitQuaternion::from_axis_angle(&Vector3::z_axis(), heading));
+        assert!((heading_ros(ned.convert()) - (FRAC_PI_2 - heading)).abs() < 1e-5);
+        assert_eq!(Attitude::<Enu, Flu>::FRAMES, Frames::ENU_FLU);
+
+        // A forward vector points along the heading, whichever frames it is worked out in
+        let forward = FrameVector::<Frd>::new(Vector3::x());
+        let north_east = ned.body_to_world(forward);
+        let enu: Attitude<Enu, Flu> = ned.convert();
+        let east_north = enu.body_to_world(forward.to_body());
+        assert!(close(
+            east_north.vector,
+            north_east.to_world::<Enu>().vector
+        ));
+        assert!(close(enu.world_to_body(east_north).vector, Vector3::x()));
+
+        let runtime = Attitude::<Nwu, Flu>::from_frames(ned.quaternion(), Frames::NED_FRD);
+        assert!(
+            runtime
+                .convert::<Ned, Frd>()
+                .quaternion()
+                .angle_to(&ned.quaternion())
+                < 1e-5
+        );
+    }
+}
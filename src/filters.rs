@@ -0,0 +1,303 @@
+pub mod filter {
+
+    use std::collections::VecDeque;
+    use std::f64::consts::{PI, SQRT_2};
+
+    use crate::angles::angle::Unwrapper;
+
+    // Scales the median absolute deviation to a standard deviation for Gaussian noise
+    const MAD_SCALE: f64 = 1.4826;
+
+    // One stage of the smoothing chain between decode and display. Every stage sees continuous
+    // (unwrapped) angles, so a stream crossing ±π is filtered like any other.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum AngleFilter {
+        // Median of the latest `window` samples
+        Median {
+            window: usize,
+        },
+        // Replaces a sample more than `threshold` standard deviations (estimated from the median
+        // absolute deviation) from the median of the latest `window` samples with that median
+        Hampel {
+            window: usize,
+            threshold: f64,
+        },
+        // Moves a fraction `alpha` (0 < alpha ≤ 1) of the way towards each new sample
+        ExponentialMovingAverage {
+            alpha: f64,
+        },
+        // Second-order low-pass with a -3 dB point at `cutoff_frequency` (Hz), for samples arriving
+        // at `sample_rate` (Hz)
+        Butterworth {
+            cutoff_frequency: f64,
+            sample_rate: f64,
+        },
+    }
+
+    impl AngleFilter {
+        fn validate(&self) -> Result<(), &'static str> {
+            match *self {
+                AngleFilter::Median { window } | AngleFilter::Hampel { window, .. }
+                    if window == 0 =>
+                {
+                    Err("Filter window must hold at least one sample.")
+                }
+                AngleFilter::Hampel { threshold, .. }
+                    if !(threshold > 0.0 && threshold.is_finite()) =>
+                {
+                    Err("Hampel threshold must be positive.")
+                }
+                AngleFilter::ExponentialMovingAverage { alpha }
+                    if !(alpha > 0.0 && alpha <= 1.0) =>
+                {
+                    Err("Moving average weight must be in (0, 1].")
+                }
+                AngleFilter::Butterworth {
+                    cutoff_frequency,
+                    sample_rate,
+                } if !(cutoff_frequency > 0.0 && cutoff_frequency < sample_rate / 2.0) => {
+                    Err("Butterworth cutoff must be positive and below half the sample rate.")
+                }
+                _ => Ok(()),
+            }
+        }
+    }
+
+    fn median(values: &mut [f64]) -> f64 {
+        values.sort_by(f64::total_cmp);
+        let middle = values.len() / 2;
+        if values.len().is_multiple_of(2) {
+            (values[middle - 1] + values[middle]) / 2.0
+        } else {
+            values[middle]
+        }
+    }
+
+    // Filter state for one angle
+    #[derive(Debug, Clone)]
+    enum StageState {
+        Window(VecDeque<f64>),
+        Average(Option<f64>),
+        // Previous two inputs and outputs, newest first
+        Biquad(Option<([f64; 2], [f64; 2])>),
+    }
+
+    impl StageState {
+        fn new(filter: &AngleFilter) -> Self {
+            match filter {
+                AngleFilter::Median { .. } | AngleFilter::Hampel { .. } => {
+                    StageState::Window(VecDeque::new())
+                }
+                AngleFilter::ExponentialMovingAverage { .. } => StageState::Average(None),
+                AngleFilter::Butterworth { .. } => StageState::Biquad(None),
+            }
+        }
+
+        fn apply(&mut self, filter: &AngleFilter, value: f64) -> f64 {
+            match (filter, self) {
+                (AngleFilter::Median { window }, StageState::Window(samples)) => {
+                    push_window(samples, *window, value);
+                    median(&mut samples.iter().copied().collect::<Vec<f64>>())
+                }
+                (AngleFilter::Hampel { window, threshold }, StageState::Window(samples)) => {
+                    push_window(samples, *window, value);
+                    let center = median(&mut samples.iter().copied().collect::<Vec<f64>>());
+                    let mut deviations: Vec<f64> = samples
+                        .iter()
+                        .map(|sample| (sample - center).abs())
+                        .collect();
+                    let spread = MAD_SCALE * median(&mut deviations);
+                    if (value - center).abs() > threshold * spread {
+                        center
+                    } else {
+                        value
+                    }
+                }
+                (AngleFilter::ExponentialMovingAverage { alpha }, StageState::Average(average)) => {
+                    let next = match *average {
+                        Some(previous) => previous + alpha * (value - previous),
+                        None => value,
+                    };
+                    *average = Some(next);
+                    next
+                }
+                (
+                    AngleFilter::Butterworth {
+                        cutoff_frequency,
+                        sample_rate,
+                    },
+                    StageState::Biquad(history),
+                ) => {
+                    // Bilinear transform of the analogue prototype; starting from rest at the
+                    // first sample avoids a step response
+                    let k = (PI * cutoff_frequency / sample_rate).tan();
+                    let norm = 1.0 / (1.0 + SQRT_2 * k + k * k);
+                    let b0 = k * k * norm;
+                    let a1 = 2.0 * (k * k - 1.0) * norm;
+                    let a2 = (1.0 - SQRT_2 * k + k * k) * norm;
+                    let (inputs, outputs) = history.get_or_insert(([value; 2], [value; 2]));
+                    let output = b0 * (value + 2.0 * inputs[0] + inputs[1])
+                        - a1 * outputs[0]
+                        - a2 * outputs[1];
+                    *inputs = [value, inputs[0]];
+                    *outputs = [output, outputs[0]];
+                    output
+                }
+                _ => unreachable!("filter state is created from its filter"),
+            }
+        }
+    }
+
+    fn push_window(samples: &mut VecDeque<f64>, window: usize, value: f64) {
+        samples.push_back(value);
+        while samples.len() > window {
+            samples.pop_front();
+        }
+    }
+
+    // The configured stages in order, with one sensor's state
+    #[derive(Debug, Clone)]
+    pub struct FilterChain {
+        stages: Vec<(AngleFilter, [StageState; 3])>,
+        unwrapper: Unwrapper,
+    }
+
+    impl FilterChain {
+        pub fn new(filters: &[AngleFilter]) -> Result<Self, &'static str> {
+            let mut stages = Vec::with_capacity(filters.len());
+            for filter in filters {
+                filter.validate()?;
+                let state = StageState::new(filter);
+                stages.push((*filter, [state.clone(), state.clone(), state]));
+            }
+            Ok(FilterChain {
+                stages,
+                unwrapper: Unwrapper::new(),
+            })
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.stages.is_empty()
+        }
+
+        // Filters one sample. The result is the sample moved by however much the chain moved its
+        // continuous angles, so it stays on the sample's own branch of ±π.
+        pub fn apply(&mut self, angles: [f32; 3]) -> [f32; 3] {
+            if self.is_empty() {
+                return angles;
+            }
+            let continuous = self.unwrapper.push(angles).angles;
+            let mut filtered = continuous;
+            for (filter, states) in self.stages.iter_mut() {
+                for (value, state) in filtered.iter_mut().zip(states.iter_mut()) {
+                    *value = state.apply(filter, *value);
+                }
+            }
+            [0, 1, 2].map(|i| (angles[i] as f64 + filtered[i] - continuous[i]) as f32)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::filter::*;
+    use crate::angles::angle::{wrap_angle, AngleRange};
+    use std::f32::consts::PI;
+
+    fn yaws(chain: &mut FilterChain, values: &[f32]) -> Vec<f32> {
+        values
+            .iter()
+            .map(|yaw| chain.apply([0.0, 0.0, *yaw])[2])
+            .collect()
+    }
+
+    #[test]
+    fn test_filter_chain_validation() {
+        assert!(FilterChain::new(&[AngleFilter::Median { window: 0 }]).is_err());
+        assert!(FilterChain::new(&[AngleFilter::ExponentialMovingAverage { alpha: 1.5 }]).is_err());
+        let aliased = AngleFilter::Butterworth {
+            cutoff_frequency: 30.0,
+            sample_rate: 50.0,
+        };
+        assert!(FilterChain::new(&[aliased]).is_err());
+
+        let mut empty = FilterChain::new(&[]).unwrap();
+        assert_eq!(empty.apply([0.1, 0.2, 0.3]), [0.1, 0.2, 0.3]);
+    }
+
+    #[test]
+    fn test_median_and_hampel_remove_spikes() {
+        let values = [0.10, 0.11, 2.5, 0.13, 0.14];
+        let mut median = FilterChain::new(&[AngleFilter::Median { window: 3 }]).unwrap();
+        let filtered = yaws(&mut median, &values);
+        assert!(filtered.iter().all(|yaw| *yaw < 0.2));
+
+        let mut hampel = FilterChain::new(&[AngleFilter::Hampel {
+            window: 5,
+            threshold: 3.0,
+        }])
+        .unwrap();
+        let filtered = yaws(&mut hampel, &values);
+        assert!((filtered[2] - 0.11).abs() < 1e-6);
+        // Samples that are not outliers pass through untouched
+        assert_eq!(filtered[3], 0.13);
+    }
+
+    #[test]
+    fn test_filters_across_the_wrap() {
+        // Yaw oscillates about π, so the decoded angles jump between about +3.1 and -3.1
+        let values: Vec<f32> = (0..40)
+            .map(|i| wrap_angle(PI + 0.05 * (i as f32).sin(), AngleRange::SignedPi))
+            .collect();
+        let chains = [
+            AngleFilter::Median { window: 5 },
+            AngleFilter::Hampel {
+                window: 7,
+                threshold: 3.0,
+            },
+            AngleFilter::ExponentialMovingAverage { alpha: 0.2 },
+            AngleFilter::Butterworth {
+                cutoff_frequency: 2.0,
+                sample_rate: 50.0,
+            },
+        ];
+        for filter in chains {
+            let mut chain = FilterChain::new(&[filter]).unwrap();
+            for yaw in yaws(&mut chain, &values) {
+                // Naive averaging of ±3.1 would land near zero
+                let distance = wrap_angle(yaw - PI, AngleRange::SignedPi).abs();
+                assert!(distance < 0.06, "{:?} gave {}", filter, yaw);
+            }
+        }
+    }
+
+    #[test]
+    fn test_smoothing_filters_reduce_noise() {
+        use crate::generators::generator::Rng;
+
+        let mut rng = Rng::new(5);
+        let values: Vec<f32> = (0..500).map(|_| (rng.gaussian() * 0.1) as f32).collect();
+        let spread = |values: &[f32]| values[100..].iter().map(|v| v * v).sum::<f32>();
+        for filter in [
+            AngleFilter::ExponentialMovingAverage { alpha: 0.1 },
+            AngleFilter::Butterworth {
+                cutoff_frequency: 1.0,
+                sample_rate: 50.0,
+            },
+        ] {
+            let mut chain = FilterChain::new(&[filter]).unwrap();
+            let filtered = yaws(&mut chain, &values);
+            assert!(spread(&filtered) < spread(&values) / 5.0);
+        }
+
+        // A constant passes through a Butterworth stage unchanged
+        let mut chain = FilterChain::new(&[AngleFilter::Butterworth {
+            cutoff_frequency: 1.0,
+            sample_rate: 50.0,
+        }])
+        .unwrap();
+        assert!(yaws(&mut chain, &[0.7; 20])
+            .iter()
+            .all(|yaw| (yaw - 0.7).abs() < 1e-6));
+    }
+}
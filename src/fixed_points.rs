@@ -0,0 +1,684 @@
+pub mod fixed_point {
+
+    use std::cmp::Ordering;
+    use std::fmt;
+    use std::ops::{Add, Div, Mul, Neg, Sub};
+    use std::str::FromStr;
+
+    // Integer types that can back a fixed-point number. All arithmetic is done in i128,
+    // which is wide enough for every intermediate result of the 16/32/64-bit types.
+    pub trait FixedStorage: Copy + Ord + Default + fmt::Debug + Send + Sync + 'static {
+        const BITS: u32;
+        const SIGNED: bool;
+
+        fn to_i128(self) -> i128;
+        fn checked_from_i128(value: i128) -> Option<Self>;
+        fn wrapping_from_i128(value: i128) -> Self;
+
+        fn min_i128() -> i128 {
+            if Self::SIGNED {
+                -(1i128 << (Self::BITS - 1))
+            } else {
+                0
+            }
+        }
+
+        fn max_i128() -> i128 {
+            if Self::SIGNED {
+                (1i128 << (Self::BITS - 1)) - 1
+            } else {
+                (1i128 << Self::BITS) - 1
+            }
+        }
+
+        fn saturating_from_i128(value: i128) -> Self {
+            Self::checked_from_i128(value.clamp(Self::min_i128(), Self::max_i128()))
+                .expect("Clamped value must fit the storage type.")
+        }
+    }
+
+    macro_rules! impl_fixed_storage {
+        ($($int:ty => $signed:expr),* $(,)?) => {
+            $(
+                impl FixedStorage for $int {
+                    const BITS: u32 = <$int>::BITS;
+                    const SIGNED: bool = $signed;
+
+                    fn to_i128(self) -> i128 {
+                        self as i128
+                    }
+
+                    fn checked_from_i128(value: i128) -> Option<Self> {
+                        <$int>::try_from(value).ok()
+                    }
+
+                    fn wrapping_from_i128(value: i128) -> Self {
+                        value as $int
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_fixed_storage!(
+        u16 => false,
+        i16 => true,
+        u32 => false,
+        i32 => true,
+        u64 => false,
+        i64 => true,
+    );
+
+    // A Qm.n fixed-point number stored in the integer `I` with `FRAC` fractional bits.
+    // The stored integer is the value scaled by 2^FRAC.
+    #[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct Fixed<I: FixedStorage, const FRAC: u32> {
+        bits: I,
+    }
+
+    // Common Q formats, named after their integer and fractional bit counts
+    pub type U8F8 = Fixed<u16, 8>;
+    pub type I8F8 = Fixed<i16, 8>;
+    pub type U16F16 = Fixed<u32, 16>;
+    pub type I16F16 = Fixed<i32, 16>;
+    pub type U32F32 = Fixed<u64, 32>;
+    pub type I32F32 = Fixed<i64, 32>;
+
+    impl<I: FixedStorage, const FRAC: u32> Fixed<I, FRAC> {
+        // Evaluated on first use so that e.g. `Fixed<u16, 16>` fails to compile
+        const VALID_FRAC: () = assert!(
+            FRAC < I::BITS,
+            "FRAC must be smaller than the storage width"
+        );
+
+        pub const FRAC_BITS: u32 = FRAC;
+
+        pub fn from_bits(bits: I) -> Self {
+            let () = Self::VALID_FRAC;
+            Fixed { bits }
+        }
+
+        pub fn to_bits(self) -> I {
+            self.bits
+        }
+
+        pub fn zero() -> Self {
+            Self::from_bits(I::default())
+        }
+
+        pub fn min_value() -> Self {
+            Self::from_raw_saturating(I::min_i128())
+        }
+
+        pub fn max_value() -> Self {
+            Self::from_raw_saturating(I::max_i128())
+        }
+
+        // Smallest positive increment (one unit in the last place)
+        pub fn delta() -> Self {
+            Self::from_raw_saturating(1)
+        }
+
+        pub fn is_negative(self) -> bool {
+            self.raw() < 0
+        }
+
+        pub fn checked_from_int(value: i64) -> Option<Self> {
+            Self::from_raw_checked((value as i128) << FRAC)
+        }
+
+        pub fn checked_from_f64(value: f64) -> Option<Self> {
+            if !value.is_finite() {
+                return None;
+            }
+            let scaled = (value * Self::scale()).round();
+            if scaled < I::min_i128() as f64 || scaled > I::max_i128() as f64 {
+                return None;
+            }
+            Self::from_raw_checked(scaled as i128)
+        }
+
+        pub fn saturating_from_f64(value: f64) -> Self {
+            if value.is_nan() {
+                return Self::zero();
+            }
+            // `as` already saturates float to integer conversions, and i128 covers every storage type
+            Self::from_raw_saturating((value * Self::scale()).round() as i128)
+        }
+
+        pub fn wrapping_from_f64(value: f64) -> Self {
+            if !value.is_finite() {
+                return Self::zero();
+            }
+            let scaled = (value * Self::scale()).round();
+            let modulus = 2f64.powi(I::BITS as i32);
+            let wrapped = scaled.rem_euclid(modulus) as u128 as i128;
+            Self::from_raw_wrapping(wrapped)
+        }
+
+        pub fn checked_from_f32(value: f32) -> Option<Self> {
+            Self::checked_from_f64(value as f64)
+        }
+
+        // Exact for every storage type up to 32 bits; 64-bit values are rounded to 53 significant bits.
+        pub fn to_f64(self) -> f64 {
+            self.raw() as f64 / Self::scale()
+        }
+
+        pub fn to_f32(self) -> f32 {
+            self.to_f64() as f32
+        }
+
+        // Truncates towards zero, like an integer cast
+        pub fn to_int(self) -> i128 {
+            let raw = self.raw();
+            if raw < 0 {
+                -((-raw) >> FRAC)
+            } else {
+                raw >> FRAC
+            }
+        }
+
+        // Converts to another Q format, failing if the value does not fit or loses fractional bits
+        pub fn checked_cast<J: FixedStorage, const F2: u32>(self) -> Option<Fixed<J, F2>> {
+            let raw = self.raw();
+            let converted = if F2 >= FRAC {
+                raw.checked_mul(1i128 << (F2 - FRAC))?
+            } else {
+                let shift = FRAC - F2;
+                if raw & ((1i128 << shift) - 1) != 0 {
+                    return None;
+                }
+                raw >> shift
+            };
+            Fixed::<J, F2>::from_raw_checked(converted)
+        }
+
+        pub fn checked_add(self, rhs: Self) -> Option<Self> {
+            Self::from_raw_checked(self.raw() + rhs.raw())
+        }
+
+        pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+            Self::from_raw_checked(self.raw() - rhs.raw())
+        }
+
+        pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+            let product = self.raw().checked_mul(rhs.raw())?;
+            Self::from_raw_checked(product >> FRAC)
+        }
+
+        pub fn checked_div(self, rhs: Self) -> Option<Self> {
+            if rhs.raw() == 0 {
+                return None;
+            }
+            Self::from_raw_checked((self.raw() << FRAC) / rhs.raw())
+        }
+
+        pub fn checked_neg(self) -> Option<Self> {
+            Self::from_raw_checked(-self.raw())
+        }
+
+        pub fn wrapping_add(self, rhs: Self) -> Self {
+            Self::from_raw_wrapping(self.raw() + rhs.raw())
+        }
+
+        pub fn wrapping_sub(self, rhs: Self) -> Self {
+            Self::from_raw_wrapping(self.raw() - rhs.raw())
+        }
+
+        pub fn wrapping_mul(self, rhs: Self) -> Self {
+            // The bits we keep sit between FRAC and FRAC + BITS, which always fall within
+            // the low 128 bits of the product, so a wrapping i128 multiply is exact here.
+            Self::from_raw_wrapping(self.raw().wrapping_mul(rhs.raw()) >> FRAC)
+        }
+
+        pub fn wrapping_div(self, rhs: Self) -> Self {
+            assert!(
+                rhs.raw() != 0,
+                "Attempt to divide a fixed-point value by zero."
+            );
+            Self::from_raw_wrapping((self.raw() << FRAC) / rhs.raw())
+        }
+
+        pub fn wrapping_neg(self) -> Self {
+            Self::from_raw_wrapping(-self.raw())
+        }
+
+        pub fn saturating_add(self, rhs: Self) -> Self {
+            Self::from_raw_saturating(self.raw() + rhs.raw())
+        }
+
+        pub fn saturating_sub(self, rhs: Self) -> Self {
+            Self::from_raw_saturating(self.raw() - rhs.raw())
+        }
+
+        pub fn saturating_mul(self, rhs: Self) -> Self {
+            match self.raw().checked_mul(rhs.raw()) {
+                Some(product) => Self::from_raw_saturating(product >> FRAC),
+                None if self.is_negative() != rhs.is_negative() => Self::min_value(),
+                None => Self::max_value(),
+            }
+        }
+
+        pub fn saturating_div(self, rhs: Self) -> Self {
+            assert!(
+                rhs.raw() != 0,
+                "Attempt to divide a fixed-point value by zero."
+            );
+            Self::from_raw_saturating((self.raw() << FRAC) / rhs.raw())
+        }
+
+        fn raw(self) -> i128 {
+            self.bits.to_i128()
+        }
+
+        fn scale() -> f64 {
+            2f64.powi(FRAC as i32)
+        }
+
+        fn from_raw_checked(raw: i128) -> Option<Self> {
+            I::checked_from_i128(raw).map(Self::from_bits)
+        }
+
+        fn from_raw_wrapping(raw: i128) -> Self {
+            Self::from_bits(I::wrapping_from_i128(raw))
+        }
+
+        fn from_raw_saturating(raw: i128) -> Self {
+            Self::from_bits(I::saturating_from_i128(raw))
+        }
+    }
+
+    impl<I: FixedStorage, const FRAC: u32> Add for Fixed<I, FRAC> {
+        type Output = Self;
+
+        fn add(self, rhs: Self) -> Self {
+            self.checked_add(rhs)
+                .expect("Fixed-point addition overflowed.")
+        }
+    }
+
+    impl<I: FixedStorage, const FRAC: u32> Sub for Fixed<I, FRAC> {
+        type Output = Self;
+
+        fn sub(self, rhs: Self) -> Self {
+            self.checked_sub(rhs)
+                .expect("Fixed-point subtraction overflowed.")
+        }
+    }
+
+    impl<I: FixedStorage, const FRAC: u32> Mul for Fixed<I, FRAC> {
+        type Output = Self;
+
+        fn mul(self, rhs: Self) -> Self {
+            self.checked_mul(rhs)
+                .expect("Fixed-point multiplication overflowed.")
+        }
+    }
+
+    impl<I: FixedStorage, const FRAC: u32> Div for Fixed<I, FRAC> {
+        type Output = Self;
+
+        fn div(self, rhs: Self) -> Self {
+            self.checked_div(rhs)
+                .expect("Fixed-point division overflowed or divided by zero.")
+        }
+    }
+
+    impl<I: FixedStorage, const FRAC: u32> Neg for Fixed<I, FRAC> {
+        type Output = Self;
+
+        fn neg(self) -> Self {
+            self.checked_neg()
+                .expect("Fixed-point negation overflowed.")
+        }
+    }
+
+    // Widening conversions between storage types with the same number of fractional bits never lose information
+    macro_rules! impl_lossless_from {
+        ($($from:ty => $($to:ty),+);* $(;)?) => {
+            $($(
+                impl<const FRAC: u32> From<Fixed<$from, FRAC>> for Fixed<$to, FRAC> {
+                    fn from(value: Fixed<$from, FRAC>) -> Self {
+                        Fixed::from_bits(<$to>::from(value.to_bits()))
+                    }
+                }
+            )+)*
+        };
+    }
+
+    impl_lossless_from!(
+        u16 => u32, i32, u64, i64;
+        i16 => i32, i64;
+        u32 => u64, i64;
+        i32 => i64;
+    );
+
+    impl<I: FixedStorage, const FRAC: u32> fmt::Debug for Fixed<I, FRAC> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self)
+        }
+    }
+
+    impl<I: FixedStorage, const FRAC: u32> fmt::Display for Fixed<I, FRAC> {
+        // Prints the exact decimal expansion (every Qm.n value has at most n fractional digits),
+        // or rounds half away from zero when a precision is requested.
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let raw = self.raw();
+            let magnitude = raw.unsigned_abs();
+            let mask = (1u128 << FRAC) - 1;
+            let mut integer = magnitude >> FRAC;
+            let mut fraction = magnitude & mask;
+
+            let mut digits: Vec<u8> = Vec::new();
+            match f.precision() {
+                Some(precision) => {
+                    for _ in 0..precision {
+                        fraction *= 10;
+                        digits.push((fraction >> FRAC) as u8);
+                        fraction &= mask;
+                    }
+                    if fraction << 1 >= 1u128 << FRAC {
+                        // Propagate the rounding carry through the digits and into the integer part
+                        let mut carry = true;
+                        for digit in digits.iter_mut().rev() {
+                            if *digit == 9 {
+                                *digit = 0;
+                            } else {
+                                *digit += 1;
+                                carry = false;
+                                break;
+                            }
+                        }
+                        if carry {
+                            integer += 1;
+                        }
+                    }
+                }
+                None => {
+                    while fraction != 0 {
+                        fraction *= 10;
+                        digits.push((fraction >> FRAC) as u8);
+                        fraction &= mask;
+                    }
+                }
+            }
+
+            let mut text = integer.to_string();
+            if !digits.is_empty() {
+                text.push('.');
+                text.extend(digits.iter().map(|digit| (b'0' + digit) as char));
+            }
+            // Handles the sign, `+` flag and width/alignment the same way integers do
+            f.pad_integral(raw >= 0, "", &text)
+        }
+    }
+
+    impl<I: FixedStorage, const FRAC: u32> FromStr for Fixed<I, FRAC> {
+        type Err = &'static str;
+
+        // Parses decimal text such as "3.14159", "-0.5" or "+12", rounding to the nearest representable value
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let s = s.trim();
+            let (negative, unsigned) = match s.as_bytes().first() {
+                Some(b'-') => (true, &s[1..]),
+                Some(b'+') => (false, &s[1..]),
+                _ => (false, s),
+            };
+            let (integer_text, fraction_text) = match unsigned.split_once('.') {
+                Some((integer, fraction)) => (integer, fraction),
+                None => (unsigned, ""),
+            };
+            if integer_text.is_empty() && fraction_text.is_empty() {
+                return Err("Invalid fixed-point value; expected a decimal number.");
+            }
+            if !integer_text
+                .bytes()
+                .chain(fraction_text.bytes())
+                .all(|b| b.is_ascii_digit())
+            {
+                return Err("Invalid fixed-point value; expected a decimal number.");
+            }
+
+            let out_of_range = if negative {
+                "Underflow: value is out of range for the fixed-point type"
+            } else {
+                "Overflow: value is out of range for the fixed-point type"
+            };
+
+            // Bounding the integer part keeps the shift below within i128
+            let integer_limit = (I::max_i128() - I::min_i128()) >> FRAC;
+            let mut integer: i128 = 0;
+            for digit in integer_text.bytes() {
+                integer = integer * 10 + (digit - b'0') as i128;
+                if integer > integer_limit {
+                    return Err(out_of_range);
+                }
+            }
+
+            // Accumulate the fraction from the least significant digit upwards, with guard bits for rounding
+            const GUARD_BITS: u32 = 8;
+            let mut fraction: u128 = 0;
+            for digit in fraction_text.bytes().rev() {
+                fraction = (fraction + (((digit - b'0') as u128) << (FRAC + GUARD_BITS))) / 10;
+            }
+            let fraction = ((fraction + (1 << (GUARD_BITS - 1))) >> GUARD_BITS) as i128;
+
+            let magnitude = (integer << FRAC) + fraction;
+            let raw = if negative { -magnitude } else { magnitude };
+            Self::from_raw_checked(raw).ok_or(out_of_range)
+        }
+    }
+
+    impl<I: FixedStorage, const FRAC: u32> PartialEq<f64> for Fixed<I, FRAC> {
+        fn eq(&self, other: &f64) -> bool {
+            self.to_f64() == *other
+        }
+    }
+
+    impl<I: FixedStorage, const FRAC: u32> PartialOrd<f64> for Fixed<I, FRAC> {
+        fn partial_cmp(&self, other: &f64) -> Option<Ordering> {
+            self.to_f64().partial_cmp(other)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fixed_point::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_from_bits_round_trip() {
+        let value = U16F16::from_bits(205887);
+        assert_eq!(value.to_bits(), 205887);
+        assert!((value.to_f64() - PI).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_checked_from_f64() {
+        assert_eq!(U16F16::checked_from_f64(PI).unwrap().to_bits(), 205887);
+        assert_eq!(I16F16::checked_from_f64(-PI).unwrap().to_bits(), -205887);
+        assert_eq!(U16F16::checked_from_f64(0.0001).unwrap().to_bits(), 7);
+        assert!(U16F16::checked_from_f64(-PI).is_none());
+        assert!(U16F16::checked_from_f64(1e10).is_none());
+        assert!(U16F16::checked_from_f64(f64::NAN).is_none());
+    }
+
+    #[test]
+    fn test_saturating_and_wrapping_from_f64() {
+        assert_eq!(U16F16::saturating_from_f64(-1.0), U16F16::min_value());
+        assert_eq!(U16F16::saturating_from_f64(1e10), U16F16::max_value());
+        assert_eq!(I8F8::saturating_from_f64(-1e10), I8F8::min_value());
+        assert_eq!(U8F8::wrapping_from_f64(257.5).to_bits(), 0x0180);
+        assert_eq!(I8F8::wrapping_from_f64(128.0).to_bits(), i16::MIN);
+    }
+
+    #[test]
+    fn test_min_max_values() {
+        assert_eq!(U16F16::min_value().to_bits(), 0);
+        assert_eq!(U16F16::max_value().to_bits(), u32::MAX);
+        assert_eq!(I32F32::min_value().to_bits(), i64::MIN);
+        assert_eq!(U32F32::max_value().to_bits(), u64::MAX);
+        assert_eq!(I8F8::delta().to_f64(), 1.0 / 256.0);
+    }
+
+    #[test]
+    fn test_checked_arithmetic() {
+        let a = I16F16::checked_from_f64(1.5).unwrap();
+        let b = I16F16::checked_from_f64(-2.25).unwrap();
+        assert_eq!(a.checked_add(b).unwrap(), -0.75);
+        assert_eq!(a.checked_sub(b).unwrap(), 3.75);
+        assert_eq!(a.checked_mul(b).unwrap(), -3.375);
+        assert_eq!(b.checked_div(a).unwrap(), -1.5);
+        assert!(a.checked_div(I16F16::zero()).is_none());
+        assert!(I16F16::max_value().checked_add(I16F16::delta()).is_none());
+        assert!(U16F16::zero().checked_sub(U16F16::delta()).is_none());
+        assert!(I16F16::min_value().checked_neg().is_none());
+    }
+
+    #[test]
+    fn test_wrapping_arithmetic() {
+        assert_eq!(
+            I16F16::max_value().wrapping_add(I16F16::delta()),
+            I16F16::min_value()
+        );
+        assert_eq!(
+            U16F16::zero().wrapping_sub(U16F16::delta()),
+            U16F16::max_value()
+        );
+        let big = U8F8::checked_from_f64(200.0).unwrap();
+        let two = U8F8::checked_from_f64(2.0).unwrap();
+        assert_eq!(big.wrapping_mul(two), 144.0);
+        assert_eq!(I8F8::min_value().wrapping_neg(), I8F8::min_value());
+    }
+
+    #[test]
+    fn test_wrapping_mul_64_bit() {
+        // The full product does not fit in i128, but the kept bits must still be exact
+        let value = U32F32::max_value();
+        let expected = (u64::MAX as u128 * u64::MAX as u128) >> 32;
+        assert_eq!(value.wrapping_mul(value).to_bits(), expected as u64);
+    }
+
+    #[test]
+    fn test_saturating_arithmetic() {
+        let big = I8F8::checked_from_f64(100.0).unwrap();
+        let small = I8F8::checked_from_f64(-100.0).unwrap();
+        assert_eq!(big.saturating_add(big), I8F8::max_value());
+        assert_eq!(small.saturating_add(small), I8F8::min_value());
+        assert_eq!(big.saturating_mul(small), I8F8::min_value());
+        assert_eq!(
+            U32F32::max_value().saturating_mul(U32F32::max_value()),
+            U32F32::max_value()
+        );
+        assert_eq!(
+            U16F16::zero().saturating_sub(U16F16::delta()),
+            U16F16::zero()
+        );
+        assert_eq!(big.saturating_div(I8F8::delta()), I8F8::max_value());
+    }
+
+    #[test]
+    fn test_operators() {
+        let a = I32F32::checked_from_f64(0.5).unwrap();
+        let b = I32F32::checked_from_f64(0.25).unwrap();
+        assert_eq!(a + b, 0.75);
+        assert_eq!(a - b, 0.25);
+        assert_eq!(a * b, 0.125);
+        assert_eq!(a / b, 2.0);
+        assert_eq!(-a, -0.5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_operator_overflow_panics() {
+        let _ = U16F16::max_value() + U16F16::delta();
+    }
+
+    #[test]
+    fn test_lossless_conversions() {
+        let narrow = I8F8::checked_from_f64(-3.5).unwrap();
+        let wide: Fixed<i64, 8> = narrow.into();
+        assert_eq!(wide.to_f64(), -3.5);
+
+        let unsigned = U16F16::max_value();
+        let signed: Fixed<i64, 16> = unsigned.into();
+        assert_eq!(signed.to_bits(), u32::MAX as i64);
+    }
+
+    #[test]
+    fn test_checked_cast() {
+        let value = U16F16::checked_from_f64(2.75).unwrap();
+        let widened: Option<I32F32> = value.checked_cast();
+        assert_eq!(widened.unwrap(), 2.75);
+        let narrowed: Option<I8F8> = value.checked_cast();
+        assert_eq!(narrowed.unwrap(), 2.75);
+
+        // Fractional bits that do not survive the narrowing are rejected
+        let precise = U16F16::from_bits(1);
+        assert!(precise.checked_cast::<i16, 8>().is_none());
+        // As are values out of range of the target
+        assert!(U16F16::max_value().checked_cast::<i16, 8>().is_none());
+    }
+
+    #[test]
+    fn test_to_int() {
+        assert_eq!(I16F16::checked_from_f64(2.75).unwrap().to_int(), 2);
+        assert_eq!(I16F16::checked_from_f64(-2.75).unwrap().to_int(), -2);
+        assert_eq!(U16F16::checked_from_int(7).unwrap(), 7.0);
+        assert!(I8F8::checked_from_int(200).is_none());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(I8F8::checked_from_f64(-2.5).unwrap().to_string(), "-2.5");
+        assert_eq!(U16F16::zero().to_string(), "0");
+        assert_eq!(U16F16::from_bits(1).to_string(), "0.0000152587890625");
+        assert_eq!(format!("{:.4}", U16F16::from_bits(205887)), "3.1416");
+        assert_eq!(
+            format!("{:.2}", I8F8::checked_from_f64(9.998).unwrap()),
+            "10.00"
+        );
+        assert_eq!(
+            format!("{:+.1}", I8F8::checked_from_f64(1.25).unwrap()),
+            "+1.3"
+        );
+        assert_eq!(
+            format!("{:>6.1}", I8F8::checked_from_f64(1.5).unwrap()),
+            "   1.5"
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("3.14159".parse::<U16F16>().unwrap().to_bits(), 205887);
+        assert_eq!("-0.5".parse::<I8F8>().unwrap(), -0.5);
+        assert_eq!("+12".parse::<I16F16>().unwrap(), 12.0);
+        assert_eq!(".25".parse::<U8F8>().unwrap(), 0.25);
+        assert_eq!(" 7. ".parse::<U8F8>().unwrap(), 7.0);
+        assert!("".parse::<U8F8>().is_err());
+        assert!("1.2.3".parse::<U8F8>().is_err());
+        assert!("abc".parse::<U8F8>().is_err());
+        assert_eq!(
+            "-1".parse::<U8F8>().err().unwrap(),
+            "Underflow: value is out of range for the fixed-point type"
+        );
+        assert_eq!(
+            "256".parse::<U8F8>().err().unwrap(),
+            "Overflow: value is out of range for the fixed-point type"
+        );
+    }
+
+    #[test]
+    fn test_display_parse_round_trip() {
+        for bits in [i32::MIN, -205887, -1, 0, 1, 7, 205887, i32::MAX] {
+            let value = I16F16::from_bits(bits);
+            assert_eq!(value.to_string().parse::<I16F16>().unwrap(), value);
+        }
+        for bits in [0, 1, u64::MAX / 3, u64::MAX] {
+            let value = U32F32::from_bits(bits);
+            assert_eq!(value.to_string().parse::<U32F32>().unwrap(), value);
+        }
+    }
+}
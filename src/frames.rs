@@ -0,0 +1,458 @@
+pub mod frame {
+
+    use nalgebra::UnitQuaternion;
+
+    use crate::conversions::conversion::{
+        bits_to_u32, float_to_raw_with_policy, raw_to_float, ConversionPolicy, ConversionStats,
+    };
+    use crate::orientations::orientation::EulerConvention;
+
+    // Bits per angle field, and per frame (three angle fields plus the spare byte)
+    pub const FIELD_BITS: usize = 8;
+    pub const FRAME_BITS: usize = 4 * FIELD_BITS;
+
+    pub const CRC_MISMATCH: &str = "CRC mismatch: frame is corrupted.";
+
+    // What the three fields of a frame measure
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub enum FrameKind {
+        // Euler angles (rad) in the sensor's convention
+        #[default]
+        Attitude,
+        // Body-frame angular rate about x, y and z (rad/s)
+        AngularRate,
+        // Body-frame specific force; any unit, since only its direction is used
+        Acceleration,
+        // Body-frame magnetic field; any unit, since only its direction is used
+        MagneticField,
+    }
+
+    // What the fourth byte of a frame carries
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum SpareByte {
+        // Ignored on decode, zero on encode
+        #[default]
+        Unused,
+        // Rolling 8-bit frame counter
+        Sequence,
+        // CRC-8 over the three angle bytes
+        Crc8,
+    }
+
+    // How angles are laid out in a sensor frame: three 8-bit fields, most significant bit first,
+    // each holding the raw fixed-point value with `fractional_bits` fractional bits
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct FrameLayout {
+        pub fractional_bits: u32,
+        // Two's complement fields instead of unsigned ones
+        pub signed: bool,
+        pub spare: SpareByte,
+    }
+
+    impl FrameLayout {
+        // The layout the sensor has always used: unsigned raw Q16.16 fields, spare byte unused
+        pub const SENSOR: FrameLayout = FrameLayout {
+            fractional_bits: 16,
+            signed: false,
+            spare: SpareByte::Unused,
+        };
+
+        // Field values are scaled by 2^fractional_bits, which has to fit in a u64
+        pub fn validate(&self) -> Result<(), &'static str> {
+            if self.fractional_bits >= 64 {
+                return Err("Frame layouts take fewer than 64 fractional bits.");
+            }
+            Ok(())
+        }
+
+        pub fn resolution(&self) -> f32 {
+            1.0 / (1u64 << self.fractional_bits) as f32
+        }
+
+        // Largest error introduced by encoding an in-range value (fields are rounded to nearest)
+        pub fn quantization_bound(&self) -> f32 {
+            self.resolution() / 2.0
+        }
+
+        // Smallest and largest representable angle
+        pub fn range(&self) -> (f32, f32) {
+            let (min, max) = self.raw_range();
+            (
+                min as f32 * self.resolution(),
+                max as f32 * self.resolution(),
+            )
+        }
+
+        fn raw_range(&self) -> (i128, i128) {
+            if self.signed {
+                (-128, 127)
+            } else {
+                (0, 255)
+            }
+        }
+
+        fn field_to_float(&self, field: u8) -> f32 {
+            let raw = if self.signed {
+                field as i8 as i128
+            } else {
+                field as i128
+            };
+            raw_to_float(raw, self.fractional_bits) as f32
+        }
+
+        fn float_to_field(
+            &self,
+            value: f32,
+            policy: ConversionPolicy,
+            stats: &mut ConversionStats,
+        ) -> Result<u8, &'static str> {
+            let raw = float_to_raw_with_policy(
+                value as f64,
+                self.fractional_bits,
+                self.raw_range(),
+                policy,
+                stats,
+            )?;
+            // Keep the low byte, which is the two's complement encoding for signed fields
+            Ok(raw as u8)
+        }
+    }
+
+    impl Default for FrameLayout {
+        fn default() -> Self {
+            FrameLayout::SENSOR
+        }
+    }
+
+    // The contents of a decoded frame
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct DecodedFrame {
+        pub angles: [f32; 3],
+        // Frame counter, for layouts whose spare byte carries one
+        pub sequence: Option<u8>,
+    }
+
+    // CRC-8 with polynomial 0x07 and zero initial value (CRC-8/SMBUS)
+    pub fn crc8(bytes: &[u8]) -> u8 {
+        let mut crc: u8 = 0;
+        for byte in bytes {
+            crc ^= byte;
+            for _ in 0..8 {
+                crc = if crc & 0x80 != 0 {
+                    (crc << 1) ^ 0x07
+                } else {
+                    crc << 1
+                };
+            }
+        }
+        crc
+    }
+
+    pub fn bytes_to_bits(bytes: &[u8]) -> Vec<u32> {
+        bytes
+            .iter()
+            .flat_map(|byte| (0..8).rev().map(move |i| ((byte >> i) & 1) as u32))
+            .collect()
+    }
+
+    pub fn bits_to_bytes(bits: &[u32]) -> Result<Vec<u8>, &'static str> {
+        if !bits.len().is_multiple_of(FIELD_BITS) {
+            return Err("Bit count is not a whole number of bytes.");
+        }
+        bits.chunks(FIELD_BITS)
+            .map(|chunk| bits_to_u32(chunk).map(|byte| byte as u8))
+            .collect()
+    }
+
+    pub fn decode_frame(bits: &[u32], layout: &FrameLayout) -> Result<DecodedFrame, &'static str> {
+        let needed = match layout.spare {
+            SpareByte::Unused => 3 * FIELD_BITS,
+            SpareByte::Sequence | SpareByte::Crc8 => FRAME_BITS,
+        };
+        if bits.len() < needed {
+            return Err("Not enough bits to create u32 values for x, y, and z.");
+        }
+
+        let bytes = bits_to_bytes(&bits[0..needed])?;
+        let angles = [
+            layout.field_to_float(bytes[0]),
+            layout.field_to_float(bytes[1]),
+            layout.field_to_float(bytes[2]),
+        ];
+        let sequence = match layout.spare {
+            SpareByte::Unused => None,
+            SpareByte::Sequence => Some(bytes[3]),
+            SpareByte::Crc8 => {
+                if crc8(&bytes[0..3]) != bytes[3] {
+                    return Err(CRC_MISMATCH);
+                }
+                None
+            }
+        };
+        Ok(DecodedFrame { angles, sequence })
+    }
+
+    pub fn encode_frame(
+        angles: [f32; 3],
+        sequence: u8,
+        layout: &FrameLayout,
+        policy: ConversionPolicy,
+        stats: &mut ConversionStats,
+    ) -> Result<Vec<u32>, &'static str> {
+        let mut bytes = [0u8; 4];
+        for (byte, angle) in bytes.iter_mut().zip(angles) {
+            *byte = layout.float_to_field(angle, policy, stats)?;
+        }
+        bytes[3] = match layout.spare {
+            SpareByte::Unused => 0,
+            SpareByte::Sequence => sequence,
+            SpareByte::Crc8 => crc8(&bytes[0..3]),
+        };
+        Ok(bytes_to_bits(&bytes))
+    }
+
+    // Turns orientations into the frames a sensor with the given layout and convention would send
+    #[derive(Debug, Clone)]
+    pub struct Encoder {
+        pub layout: FrameLayout,
+        pub convention: EulerConvention,
+        pub policy: ConversionPolicy,
+        pub stats: ConversionStats,
+        sequence: u8,
+    }
+
+    impl Encoder {
+        pub fn new(
+            layout: FrameLayout,
+            convention: EulerConvention,
+            policy: ConversionPolicy,
+        ) -> Result<Self, &'static str> {
+            layout.validate()?;
+            Ok(Encoder {
+                layout,
+                convention,
+                policy,
+                stats: ConversionStats::default(),
+                sequence: 0,
+            })
+        }
+
+        // Angles are taken to be in the encoder's convention already
+        pub fn encode_angles(&mut self, angles: [f32; 3]) -> Result<Vec<u32>, &'static str> {
+            let frame = encode_frame(
+                angles,
+                self.sequence,
+                &self.layout,
+                self.policy,
+                &mut self.stats,
+            )?;
+            self.sequence = self.sequence.wrapping_add(1);
+            Ok(frame)
+        }
+
+        pub fn encode_quaternion(
+            &mut self,
+            quaternion: UnitQuaternion<f32>,
+        ) -> Result<Vec<u32>, &'static str> {
+            let angles = self.convention.from_quaternion(quaternion);
+            self.encode_angles(angles)
+        }
+    }
+
+    impl Default for Encoder {
+        fn default() -> Self {
+            Encoder::new(
+                FrameLayout::default(),
+                EulerConvention::default(),
+                ConversionPolicy::default(),
+            )
+            .expect("The default layout is valid")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::frame::*;
+    use crate::conversions::conversion::{
+        float_euler_angles_from_bits, ConversionPolicy, ConversionStats,
+    };
+    use crate::orientations::orientation::{EulerAngles, EulerConvention, EulerSequence};
+
+    #[test]
+    fn test_encode_frame_matches_sensor_frame() {
+        let frame: Vec<u32> = vec![
+            1, 0, 1, 1, 0, 0, 1, 0, // x: 178
+            0, 0, 0, 0, 1, 0, 1, 1, // y: 11
+            1, 1, 1, 1, 0, 0, 0, 1, // z: 241
+            0, 0, 0, 0, 0, 0, 0, 0, // Last 8 bits unused
+        ];
+        let (x, y, z) = float_euler_angles_from_bits(&frame).unwrap();
+
+        let mut stats = ConversionStats::default();
+        let encoded = encode_frame(
+            [x, y, z],
+            9,
+            &FrameLayout::SENSOR,
+            ConversionPolicy::default(),
+            &mut stats,
+        )
+        .unwrap();
+        assert_eq!(encoded, frame);
+        assert_eq!(
+            decode_frame(&encoded, &FrameLayout::SENSOR).unwrap().angles,
+            [x, y, z]
+        );
+    }
+
+    #[test]
+    fn test_round_trip_within_quantization_bound() {
+        let layout = FrameLayout {
+            fractional_bits: 5,
+            signed: true,
+            spare: SpareByte::Sequence,
+        };
+        let (min, max) = layout.range();
+        let mut encoder = Encoder::new(
+            layout,
+            EulerConvention::default(),
+            ConversionPolicy::default(),
+        )
+        .unwrap();
+
+        let mut value = min;
+        while value <= max {
+            let angles = [value, -value.clamp(-max, -min), 0.3 * value];
+            let frame = encoder.encode_angles(angles).unwrap();
+            assert_eq!(frame.len(), FRAME_BITS);
+            let decoded = decode_frame(&frame, &layout).unwrap();
+            for (decoded, angle) in decoded.angles.iter().zip(angles) {
+                assert!((decoded - angle).abs() <= layout.quantization_bound());
+            }
+            value += 0.01;
+        }
+    }
+
+    #[test]
+    fn test_spare_byte() {
+        let layout = FrameLayout {
+            fractional_bits: 6,
+            signed: true,
+            spare: SpareByte::Sequence,
+        };
+        let mut encoder = Encoder::new(
+            layout,
+            EulerConvention::default(),
+            ConversionPolicy::default(),
+        )
+        .unwrap();
+        for expected in [0u8, 1, 2] {
+            let frame = encoder.encode_angles([0.5, -0.5, 1.0]).unwrap();
+            assert_eq!(
+                decode_frame(&frame, &layout).unwrap().sequence,
+                Some(expected)
+            );
+        }
+
+        let layout = FrameLayout {
+            spare: SpareByte::Crc8,
+            ..layout
+        };
+        let mut stats = ConversionStats::default();
+        let mut frame = encode_frame(
+            [0.5, -0.5, 1.0],
+            0,
+            &layout,
+            ConversionPolicy::default(),
+            &mut stats,
+        )
+        .unwrap();
+        assert!(decode_frame(&frame, &layout).is_ok());
+
+        // Any single flipped bit is caught
+        frame[5] ^= 1;
+        assert_eq!(decode_frame(&frame, &layout), Err(CRC_MISMATCH));
+    }
+
+    #[test]
+    fn test_crc8() {
+        // CRC-8/SMBUS check value
+        assert_eq!(crc8(b"123456789"), 0xF4);
+        assert_eq!(crc8(&[]), 0);
+    }
+
+    #[test]
+    fn test_layout_validate() {
+        assert!(FrameLayout::SENSOR.validate().is_ok());
+        let layout = FrameLayout {
+            fractional_bits: 64,
+            ..FrameLayout::SENSOR
+        };
+        assert!(layout.validate().is_err());
+        assert!(Encoder::new(
+            layout,
+            EulerConvention::default(),
+            ConversionPolicy::default()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_encode_out_of_range() {
+        let layout = FrameLayout::SENSOR;
+        let mut stats = ConversionStats::default();
+        let result = encode_frame(
+            [-0.5, 0.0, 0.0],
+            0,
+            &layout,
+            ConversionPolicy::default(),
+            &mut stats,
+        );
+        assert_eq!(
+            result,
+            Err("Underflow: value is too small for the fixed-point range")
+        );
+
+        let frame = encode_frame(
+            [-0.5, 1.0, 0.0],
+            0,
+            &layout,
+            ConversionPolicy::saturating(),
+            &mut stats,
+        )
+        .unwrap();
+        let decoded = decode_frame(&frame, &layout).unwrap();
+        assert_eq!(decoded.angles[0], 0.0);
+        assert_eq!(decoded.angles[1], layout.range().1);
+        assert_eq!(stats.saturated, 2);
+    }
+
+    #[test]
+    fn test_encode_quaternion() {
+        let layout = FrameLayout {
+            fractional_bits: 6,
+            signed: true,
+            spare: SpareByte::Unused,
+        };
+        let convention = EulerConvention::intrinsic(EulerSequence::ZYX);
+        let mut encoder = Encoder::new(layout, convention, ConversionPolicy::default()).unwrap();
+        let angles = EulerAngles::with_convention([0.4, -0.2, 1.1], convention);
+
+        let frame = encoder.encode_quaternion(angles.to_quaternion()).unwrap();
+        let decoded = decode_frame(&frame, &layout).unwrap();
+        for i in 0..3 {
+            assert!(
+                (decoded.angles[i] - angles.angles[i]).abs() <= layout.quantization_bound() + 1e-5
+            );
+        }
+    }
+
+    #[test]
+    fn test_bits_and_bytes() {
+        let bytes = [0b1011_0010, 0x00, 0xFF];
+        let bits = bytes_to_bits(&bytes);
+        assert_eq!(&bits[0..8], &[1, 0, 1, 1, 0, 0, 1, 0]);
+        assert_eq!(bits_to_bytes(&bits).unwrap(), bytes.to_vec());
+        assert!(bits_to_bytes(&bits[0..7]).is_err());
+    }
+}
@@ -0,0 +1,368 @@
+pub mod fusion {
+
+    use nalgebra::{Matrix4, Quaternion, SymmetricEigen, UnitQuaternion};
+    use std::collections::BTreeMap;
+
+    use crate::samples::sample::{Sample, Timestamp};
+    use crate::sensors::sensor::SensorId;
+
+    // Weighted average of unit quaternions: the eigenvector of the largest eigenvalue of
+    // Σ wᵢ qᵢ qᵢᵀ (Markley et al.), which does not care about the sign of each qᵢ
+    pub fn average_quaternions(
+        quaternions: &[(UnitQuaternion<f32>, f32)],
+    ) -> Option<UnitQuaternion<f32>> {
+        let mut accumulator = Matrix4::<f64>::zeros();
+        let mut total_weight = 0.0;
+        for (quaternion, weight) in quaternions {
+            let coords = quaternion.coords.cast::<f64>();
+            accumulator += coords * coords.transpose() * *weight as f64;
+            total_weight += *weight as f64;
+        }
+        if total_weight <= 0.0 {
+            return None;
+        }
+
+        let eigen = SymmetricEigen::new(accumulator);
+        let largest = eigen.eigenvalues.imax();
+        let coords = eigen
+            .eigenvectors
+            .column(largest)
+            .into_owned()
+            .cast::<f32>();
+        Some(UnitQuaternion::new_normalize(Quaternion::from(coords)))
+    }
+
+    // Which sensors made it into a fused estimate
+    #[derive(Debug, Clone, PartialEq, Eq, Default)]
+    pub struct FusionInfo {
+        pub contributors: Vec<SensorId>,
+        // Disagreed with the others by more than the outlier threshold
+        pub rejected: Vec<SensorId>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct FusedEstimate {
+        pub quaternion: UnitQuaternion<f32>,
+        pub info: FusionInfo,
+    }
+
+    // Averages the measurements, repeatedly dropping the one furthest from the average while it is
+    // more than `outlier_threshold` (rad) away. Two sensors that disagree cannot outvote each
+    // other, so at least two are always kept.
+    pub fn fuse_redundant(
+        measurements: &[(SensorId, UnitQuaternion<f32>, f32)],
+        outlier_threshold: f32,
+    ) -> Option<FusedEstimate> {
+        let mut active: Vec<(SensorId, UnitQuaternion<f32>, f32)> = measurements.to_vec();
+        let mut rejected = Vec::new();
+        loop {
+            let weighted: Vec<(UnitQuaternion<f32>, f32)> =
+                active.iter().map(|(_, q, weight)| (*q, *weight)).collect();
+            let average = average_quaternions(&weighted)?;
+            let worst = active
+                .iter()
+                .enumerate()
+                .map(|(index, (_, q, _))| (index, q.angle_to(&average)))
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+            match worst {
+                Some((index, angle)) if angle > outlier_threshold && active.len() > 2 => {
+                    rejected.push(active.remove(index).0);
+                }
+                _ => {
+                    let mut contributors: Vec<SensorId> =
+                        active.iter().map(|(sensor, _, _)| *sensor).collect();
+                    contributors.sort();
+                    rejected.sort();
+                    return Some(FusedEstimate {
+                        quaternion: average,
+                        info: FusionInfo {
+                            contributors,
+                            rejected,
+                        },
+                    });
+                }
+            }
+        }
+    }
+
+    // Sensors that measure the same body, and how their samples are combined
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct RedundancyConfig {
+        // Each redundant sensor with its weight
+        pub sensors: Vec<(SensorId, f32)>,
+        // ID the fused samples are emitted under
+        pub fused_sensor: SensorId,
+        // Radians
+        pub outlier_threshold: f32,
+        // Fuse an epoch without its missing sensors once another sensor is this many samples ahead
+        pub max_lag: u64,
+    }
+
+    // One epoch's fused orientation, ready to be turned into a sample
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct FusedEpoch {
+        pub sequence: u64,
+        // Of the latest contributing sample
+        pub timestamp: Timestamp,
+        pub estimate: FusedEstimate,
+    }
+
+    // Groups redundant sensors' samples into epochs by sequence number and fuses each epoch.
+    // The sequence must be a shared epoch index, as set by the synchroniser or resampler,
+    // not a per-sensor counter
+    #[derive(Debug, Clone)]
+    pub struct RedundantFusion {
+        pub config: RedundancyConfig,
+        pending: BTreeMap<u64, Vec<(SensorId, UnitQuaternion<f32>, Timestamp)>>,
+        // Epochs up to this one have been fused
+        closed: Option<u64>,
+        late: BTreeMap<SensorId, usize>,
+    }
+
+    impl RedundantFusion {
+        pub fn new(config: RedundancyConfig) -> Self {
+            RedundantFusion {
+                config,
+                pending: BTreeMap::new(),
+                closed: None,
+                late: BTreeMap::new(),
+            }
+        }
+
+        // Samples dropped because their epoch had already been fused
+        pub fn late(&self) -> &BTreeMap<SensorId, usize> {
+            &self.late
+        }
+
+        fn weight(&self, sensor: SensorId) -> Option<f32> {
+            self.config
+                .sensors
+                .iter()
+                .find(|(id, _)| *id == sensor)
+                .map(|(_, weight)| *weight)
+        }
+
+        // Epochs released by this sample, in order; samples from other sensors are ignored. An
+        // epoch is released once it is complete or stale and every earlier epoch has been, so a
+        // sample for an epoch that has already been fused is dropped and counted as late.
+        pub fn push(&mut self, sample: &Sample) -> Vec<FusedEpoch> {
+            if self.weight(sample.sensor).is_none() {
+                return Vec::new();
+            }
+            if self.closed.is_some_and(|closed| sample.sequence <= closed) {
+                *self.late.entry(sample.sensor).or_default() += 1;
+                return Vec::new();
+            }
+            let epoch = self.pending.entry(sample.sequence).or_default();
+            let entry = (
+                sample.sensor,
+                sample.orientation.quaternion,
+                sample.timestamp,
+            );
+            // A sensor counts once per epoch; a repeated sample replaces its earlier one
+            match epoch
+                .iter_mut()
+                .find(|(sensor, _, _)| *sensor == sample.sensor)
+            {
+                Some(existing) => *existing = entry,
+                None => epoch.push(entry),
+            }
+
+            let stale_before = sample.sequence.saturating_sub(self.config.max_lag);
+            let mut fused = Vec::new();
+            while let Some((&sequence, epoch)) = self.pending.first_key_value() {
+                let complete = self
+                    .config
+                    .sensors
+                    .iter()
+                    .all(|(id, _)| epoch.iter().any(|(sensor, _, _)| sensor == id));
+                if !complete && sequence >= stale_before {
+                    break;
+                }
+                fused.extend(self.fuse(sequence));
+            }
+            fused
+        }
+
+        // Fuses whatever is still pending, for the end of a run
+        pub fn flush(&mut self) -> Vec<FusedEpoch> {
+            let sequences: Vec<u64> = self.pending.keys().copied().collect();
+            sequences
+                .into_iter()
+                .filter_map(|sequence| self.fuse(sequence))
+                .collect()
+        }
+
+        fn fuse(&mut self, sequence: u64) -> Option<FusedEpoch> {
+            let samples = self.pending.remove(&sequence)?;
+            self.closed = Some(sequence);
+            let measurements: Vec<(SensorId, UnitQuaternion<f32>, f32)> = samples
+                .iter()
+                .filter_map(|(sensor, q, _)| self.weight(*sensor).map(|w| (*sensor, *q, w)))
+                .collect();
+            let timestamp = samples
+                .iter()
+                .map(|(_, _, timestamp)| *timestamp)
+                .max_by_key(|timestamp| timestamp.received)?;
+            let estimate = fuse_redundant(&measurements, self.config.outlier_threshold)?;
+            Some(FusedEpoch {
+                sequence,
+                timestamp,
+                estimate,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fusion::*;
+    use crate::orientations::orientation::Orientation;
+    use crate::samples::sample::Sample;
+    use crate::sensors::sensor::SensorId;
+    use nalgebra::{UnitQuaternion, Vector3};
+
+    fn yaw(angle: f32) -> UnitQuaternion<f32> {
+        UnitQuaternion::from_axis_angle(&Vector3::z_axis(), angle)
+    }
+
+    #[test]
+    fn test_average_quaternions() {
+        let average = average_quaternions(&[(yaw(0.1), 1.0), (yaw(0.3), 1.0)]).unwrap();
+        assert!(average.angle_to(&yaw(0.2)) < 1e-4);
+
+        // Weights pull the average, and the sign of each quaternion does not matter
+        let flipped = UnitQuaternion::new_unchecked(-yaw(0.4).into_inner());
+        let average = average_quaternions(&[(yaw(0.0), 1.0), (flipped, 3.0)]).unwrap();
+        assert!(average.angle_to(&yaw(0.3)) < 5e-3);
+
+        assert!(average_quaternions(&[]).is_none());
+        assert!(average_quaternions(&[(yaw(0.1), 0.0)]).is_none());
+    }
+
+    #[test]
+    fn test_fuse_redundant_rejects_outlier() {
+        let measurements = [
+            (SensorId(1), yaw(0.10), 1.0),
+            (SensorId(2), yaw(0.12), 1.0),
+            (SensorId(3), yaw(1.50), 1.0),
+            (SensorId(4), yaw(0.11), 1.0),
+        ];
+        let estimate = fuse_redundant(&measurements, 0.1).unwrap();
+        assert_eq!(
+            estimate.info.contributors,
+            vec![SensorId(1), SensorId(2), SensorId(4)]
+        );
+        assert_eq!(estimate.info.rejected, vec![SensorId(3)]);
+        assert!(estimate.quaternion.angle_to(&yaw(0.11)) < 1e-3);
+
+        // Two sensors that disagree are both kept
+        let estimate = fuse_redundant(&measurements[1..3], 0.1).unwrap();
+        assert_eq!(estimate.info.contributors.len(), 2);
+        assert!(estimate.info.rejected.is_empty());
+    }
+
+    #[test]
+    fn test_redundant_fusion_epochs() {
+        let mut fusion = RedundantFusion::new(RedundancyConfig {
+            sensors: vec![(SensorId(1), 1.0), (SensorId(2), 1.0)],
+            fused_sensor: SensorId(100),
+            outlier_threshold: 0.5,
+            max_lag: 2,
+        });
+        let sample = |sensor, sequence, angle| {
+            let mut sample = Sample::new(sequence, Orientation::from_quaternion(yaw(angle)));
+            sample.sensor = SensorId(sensor);
+            sample
+        };
+
+        assert!(fusion.push(&sample(1, 0, 0.1)).is_empty());
+        // Other sensors do not take part
+        assert!(fusion.push(&sample(7, 0, 2.0)).is_empty());
+        let fused = fusion.push(&sample(2, 0, 0.3));
+        assert_eq!(fused.len(), 1);
+        assert_eq!(fused[0].sequence, 0);
+        assert!(fused[0].estimate.quaternion.angle_to(&yaw(0.2)) < 1e-4);
+
+        // Sensor 2 misses epoch 1; it is fused without it once sensor 1 is far enough ahead
+        for sequence in 1..=3 {
+            assert!(fusion.push(&sample(1, sequence, 0.1)).is_empty());
+        }
+        let fused = fusion.push(&sample(1, 4, 0.1));
+        assert_eq!(fused.len(), 1);
+        assert_eq!(fused[0].sequence, 1);
+        assert_eq!(fused[0].estimate.info.contributors, vec![SensorId(1)]);
+
+        let flushed = fusion.flush();
+        assert_eq!(
+            flushed
+                .iter()
+                .map(|epoch| epoch.sequence)
+                .collect::<Vec<u64>>(),
+            vec![2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn test_redundant_fusion_releases_epochs_once_in_order() {
+        let mut fusion = RedundantFusion::new(RedundancyConfig {
+            sensors: vec![(SensorId(1), 1.0), (SensorId(2), 1.0)],
+            fused_sensor: SensorId(100),
+            outlier_threshold: 0.5,
+            max_lag: 2,
+        });
+        let sample = |sensor, sequence| {
+            let mut sample = Sample::new(sequence, Orientation::from_quaternion(yaw(0.1)));
+            sample.sensor = SensorId(sensor);
+            sample
+        };
+        let sequences = |fused: Vec<FusedEpoch>| -> Vec<u64> {
+            fused.iter().map(|epoch| epoch.sequence).collect()
+        };
+
+        // Epoch 1 completes while epoch 0 still waits for sensor 2 within the lag, so it is held
+        assert!(fusion.push(&sample(1, 0)).is_empty());
+        assert!(fusion.push(&sample(1, 1)).is_empty());
+        assert!(fusion.push(&sample(2, 1)).is_empty());
+        // Sensor 2's sample for epoch 0 completes it, and releases both in order
+        assert_eq!(sequences(fusion.push(&sample(2, 0))), vec![0, 1]);
+
+        // Epoch 2 goes stale without sensor 2, whose sample for it then arrives too late
+        assert!(fusion.push(&sample(1, 2)).is_empty());
+        assert!(fusion.push(&sample(1, 3)).is_empty());
+        assert!(fusion.push(&sample(1, 4)).is_empty());
+        assert_eq!(sequences(fusion.push(&sample(1, 5))), vec![2]);
+        assert!(fusion.push(&sample(2, 2)).is_empty());
+        assert_eq!(fusion.late().get(&SensorId(2)), Some(&1));
+        assert_eq!(sequences(fusion.flush()), vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn test_redundant_fusion_counts_each_sensor_once() {
+        let mut fusion = RedundantFusion::new(RedundancyConfig {
+            sensors: vec![(SensorId(1), 1.0), (SensorId(2), 1.0), (SensorId(3), 1.0)],
+            fused_sensor: SensorId(100),
+            outlier_threshold: 0.5,
+            max_lag: 8,
+        });
+        let sample = |sensor, angle| {
+            let mut sample = Sample::new(0, Orientation::from_quaternion(yaw(angle)));
+            sample.sensor = SensorId(sensor);
+            sample
+        };
+
+        // Three samples, but only two sensors: the epoch stays open
+        assert!(fusion.push(&sample(1, 0.9)).is_empty());
+        assert!(fusion.push(&sample(1, 0.1)).is_empty());
+        assert!(fusion.push(&sample(2, 0.1)).is_empty());
+        let fused = fusion.push(&sample(3, 0.1));
+        assert_eq!(fused.len(), 1);
+        assert_eq!(
+            fused[0].estimate.info.contributors,
+            vec![SensorId(1), SensorId(2), SensorId(3)]
+        );
+        // The repeated sample replaced the first one
+        assert!(fused[0].estimate.info.rejected.is_empty());
+        assert!(fused[0].estimate.quaternion.angle_to(&yaw(0.1)) < 1e-4);
+    }
+}
@@ -0,0 +1,475 @@
+pub mod generator {
+
+    use nalgebra::{Unit, UnitQuaternion, Vector3};
+    use std::f64::consts::TAU;
+    use std::time::Duration;
+
+    use crate::frames::frame::{Encoder, FrameKind};
+    use crate::inputs::input::{Source, TimedFrame};
+    use crate::noises::noise::ErrorModel;
+    use crate::orientations::orientation::EulerAngles;
+    use crate::sensors::sensor::SensorId;
+
+    // Small deterministic PRNG (SplitMix64), so that runs can be reproduced from a seed
+    #[derive(Debug, Clone)]
+    pub struct Rng {
+        state: u64,
+    }
+
+    impl Rng {
+        pub fn new(seed: u64) -> Self {
+            Rng { state: seed }
+        }
+
+        pub fn next_u64(&mut self) -> u64 {
+            self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = self.state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        }
+
+        // Uniform in [0, 1)
+        pub fn next_f64(&mut self) -> f64 {
+            (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+        }
+
+        pub fn uniform(&mut self, low: f64, high: f64) -> f64 {
+            low + (high - low) * self.next_f64()
+        }
+
+        // Standard normal, by the Box-Muller transform
+        pub fn gaussian(&mut self) -> f64 {
+            // 1 - u is in (0, 1], so the logarithm is finite
+            let u1 = 1.0 - self.next_f64();
+            let u2 = self.next_f64();
+            (-2.0 * u1.ln()).sqrt() * (TAU * u2).cos()
+        }
+    }
+
+    // A scripted orientation at a given time, in seconds from the start of the run
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Waypoint {
+        pub time: f64,
+        pub orientation: UnitQuaternion<f32>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum MotionProfile {
+        // Holds a single orientation
+        Static(UnitQuaternion<f32>),
+        // Rotates about a body axis at a fixed rate (rad/s), starting from `start`
+        ConstantRate {
+            start: UnitQuaternion<f32>,
+            axis: Unit<Vector3<f32>>,
+            rate: f32,
+        },
+        // Each of `center`'s angles, in its own convention, oscillates about its value
+        SinusoidalSway {
+            center: EulerAngles,
+            // Radians
+            amplitudes: [f32; 3],
+            // Hz
+            frequencies: [f32; 3],
+            // Radians
+            phases: [f32; 3],
+        },
+        // Rotates by a random body-frame rotation vector every sample; the standard deviation of
+        // each component grows with the square root of time (rad/√s)
+        RandomWalk {
+            start: UnitQuaternion<f32>,
+            sigma: f32,
+        },
+        // Slerps between waypoints ordered by time, holding the first before it and the last after
+        Waypoints(Vec<Waypoint>),
+    }
+
+    // One generated orientation
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct GeneratedSample {
+        pub index: u64,
+        // Seconds since the first sample
+        pub time: f64,
+        pub quaternion: UnitQuaternion<f32>,
+    }
+
+    // Endless stream of orientations following a motion profile, sampled at a fixed rate
+    #[derive(Debug, Clone)]
+    pub struct Generator {
+        pub profile: MotionProfile,
+        // Hz
+        pub sample_rate: f64,
+        pub rng: Rng,
+        index: u64,
+        walk: Option<UnitQuaternion<f32>>,
+    }
+
+    impl Generator {
+        pub fn new(
+            profile: MotionProfile,
+            sample_rate: f64,
+            seed: u64,
+        ) -> Result<Self, &'static str> {
+            if !(sample_rate > 0.0 && sample_rate.is_finite()) {
+                return Err("Sample rate must be positive.");
+            }
+            if let MotionProfile::Waypoints(waypoints) = &profile {
+                let ordered = waypoints
+                    .windows(2)
+                    .all(|pair| pair[0].time <= pair[1].time);
+                if !ordered || waypoints.iter().any(|waypoint| !waypoint.time.is_finite()) {
+                    return Err("Waypoint times must be finite and in ascending order.");
+                }
+            }
+            Ok(Generator {
+                profile,
+                sample_rate,
+                rng: Rng::new(seed),
+                index: 0,
+                walk: None,
+            })
+        }
+
+        // The orientation of the next sample, at `time`. A random walk takes one step from the
+        // previous sample, so it must be called once per sample and in order
+        fn next_orientation(&mut self, time: f64) -> UnitQuaternion<f32> {
+            match &self.profile {
+                MotionProfile::Static(orientation) => *orientation,
+                MotionProfile::ConstantRate { start, axis, rate } => {
+                    start * UnitQuaternion::from_axis_angle(axis, (*rate as f64 * time) as f32)
+                }
+                MotionProfile::SinusoidalSway {
+                    center,
+                    amplitudes,
+                    frequencies,
+                    phases,
+                } => {
+                    let mut angles = center.angles;
+                    for i in 0..3 {
+                        let argument = TAU * frequencies[i] as f64 * time + phases[i] as f64;
+                        angles[i] += amplitudes[i] * argument.sin() as f32;
+                    }
+                    EulerAngles::with_convention(angles, center.convention).to_quaternion()
+                }
+                MotionProfile::RandomWalk { start, sigma } => {
+                    let current = match self.walk {
+                        None => *start,
+                        Some(previous) => {
+                            let scale = *sigma as f64 * (1.0 / self.sample_rate).sqrt();
+                            let step = Vector3::new(
+                                (self.rng.gaussian() * scale) as f32,
+                                (self.rng.gaussian() * scale) as f32,
+                                (self.rng.gaussian() * scale) as f32,
+                            );
+                            previous * UnitQuaternion::from_scaled_axis(step)
+                        }
+                    };
+                    self.walk = Some(current);
+                    current
+                }
+                MotionProfile::Waypoints(waypoints) => interpolate_waypoints(waypoints, time),
+            }
+        }
+    }
+
+    impl Iterator for Generator {
+        type Item = GeneratedSample;
+
+        fn next(&mut self) -> Option<GeneratedSample> {
+            let index = self.index;
+            let time = index as f64 / self.sample_rate;
+            let quaternion = self.next_orientation(time);
+            self.index += 1;
+            Some(GeneratedSample {
+                index,
+                time,
+                quaternion,
+            })
+        }
+    }
+
+    pub fn interpolate_waypoints(waypoints: &[Waypoint], time: f64) -> UnitQuaternion<f32> {
+        let (first, last) = match (waypoints.first(), waypoints.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return UnitQuaternion::identity(),
+        };
+        if time <= first.time {
+            return first.orientation;
+        }
+        if time >= last.time {
+            return last.orientation;
+        }
+        // The first waypoint strictly after `time`; the one before it is at or before `time`
+        let next = waypoints
+            .iter()
+            .position(|waypoint| waypoint.time > time)
+            .unwrap_or(waypoints.len() - 1);
+        let (from, to) = (&waypoints[next - 1], &waypoints[next]);
+        let fraction = ((time - from.time) / (to.time - from.time)) as f32;
+        from.orientation.slerp(&to.orientation, fraction)
+    }
+
+    // Pipeline source that encodes generated orientations into frames
+    pub struct GeneratorSource {
+        pub generator: Generator,
+        pub encoder: Encoder,
+        // Stop after this many generated samples, dropped ones included; `None` runs forever
+        pub limit: Option<u64>,
+        // Sensor errors applied before encoding (and, for dropouts and bit flips, after)
+        pub errors: Option<ErrorModel>,
+        // Samples skipped because the encoder's layout and policy could not represent them
+        pub unencodable: u64,
+    }
+
+    impl GeneratorSource {
+        pub fn new(generator: Generator, encoder: Encoder, limit: Option<u64>) -> Self {
+            GeneratorSource {
+                generator,
+                encoder,
+                limit,
+                errors: None,
+                unencodable: 0,
+            }
+        }
+
+        pub fn with_errors(mut self, errors: ErrorModel) -> Self {
+            self.errors = Some(errors);
+            self
+        }
+    }
+
+    impl Source for GeneratorSource {
+        fn next_frame(&mut self) -> Option<Vec<u32>> {
+            self.next_timed_frame().map(|frame| frame.bits)
+        }
+
+        // Frames are stamped with the generator's sample time, as a sensor clock would
+        fn next_timed_frame(&mut self) -> Option<TimedFrame> {
+            loop {
+                if let Some(limit) = self.limit.as_mut() {
+                    if *limit == 0 {
+                        return None;
+                    }
+                    *limit -= 1;
+                }
+                let sample = self.generator.next()?;
+                let sensor_time = Some(Duration::from_secs_f64(sample.time));
+                let errors = match self.errors.as_mut() {
+                    None => {
+                        let Ok(bits) = self.encoder.encode_quaternion(sample.quaternion) else {
+                            self.unencodable += 1;
+                            continue;
+                        };
+                        return Some(TimedFrame {
+                            bits,
+                            sensor_time,
+                            sensor: SensorId::default(),
+                            kind: FrameKind::Attitude,
+                        });
+                    }
+                    Some(errors) => errors,
+                };
+
+                let dt = 1.0 / self.generator.sample_rate;
+                let angles = errors.measure(sample.quaternion, self.encoder.convention, dt);
+                // Encode even dropped frames, so that sequence numbers show the gap
+                let encoded = self.encoder.encode_angles(angles);
+                if errors.drops_frame() {
+                    continue;
+                }
+                let Ok(mut bits) = encoded else {
+                    self.unencodable += 1;
+                    continue;
+                };
+                errors.flip_bits(&mut bits);
+                return Some(TimedFrame {
+                    bits,
+                    sensor_time,
+                    sensor: SensorId::default(),
+                    kind: FrameKind::Attitude,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::generator::*;
+    use crate::inputs::input::Source;
+    use nalgebra::{UnitQuaternion, Vector3};
+
+    fn angle_between(a: UnitQuaternion<f32>, b: UnitQuaternion<f32>) -> f32 {
+        a.angle_to(&b)
+    }
+
+    #[test]
+    fn test_rng_is_deterministic() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        let mut c = Rng::new(43);
+        let first: Vec<u64> = (0..4).map(|_| a.next_u64()).collect();
+        let second: Vec<u64> = (0..4).map(|_| b.next_u64()).collect();
+        let third: Vec<u64> = (0..4).map(|_| c.next_u64()).collect();
+        assert_eq!(first, second);
+        assert_ne!(first, third);
+
+        let mut rng = Rng::new(7);
+        let values: Vec<f64> = (0..10_000).map(|_| rng.gaussian()).collect();
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+        assert!(mean.abs() < 0.05);
+        assert!((variance - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_static_and_constant_rate() {
+        let start = UnitQuaternion::from_euler_angles(0.1, 0.2, 0.3);
+        let mut generator = Generator::new(MotionProfile::Static(start), 50.0, 0).unwrap();
+        assert!(Generator::new(MotionProfile::Static(start), 0.0, 0).is_err());
+        assert!(Generator::new(MotionProfile::Static(start), f64::NAN, 0).is_err());
+        assert!(generator
+            .by_ref()
+            .take(5)
+            .all(|sample| sample.quaternion == start));
+
+        let profile = MotionProfile::ConstantRate {
+            start: UnitQuaternion::identity(),
+            axis: Vector3::z_axis(),
+            rate: 0.5,
+        };
+        let samples: Vec<GeneratedSample> =
+            Generator::new(profile, 10.0, 0).unwrap().take(11).collect();
+        assert_eq!(samples[10].index, 10);
+        assert!((samples[10].time - 1.0).abs() < 1e-12);
+        let (_, _, yaw) = samples[10].quaternion.euler_angles();
+        assert!((yaw - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_sinusoidal_sway() {
+        let profile = MotionProfile::SinusoidalSway {
+            center: Default::default(),
+            amplitudes: [0.2, 0.0, 0.0],
+            frequencies: [1.0, 0.0, 0.0],
+            phases: [0.0; 3],
+        };
+        let mut generator = Generator::new(profile, 4.0, 0).unwrap();
+        // A quarter period in, roll is at its peak
+        let samples: Vec<GeneratedSample> = generator.by_ref().take(2).collect();
+        let (roll, pitch, yaw) = samples[1].quaternion.euler_angles();
+        assert!((roll - 0.2).abs() < 1e-5);
+        assert!(pitch.abs() < 1e-6 && yaw.abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_random_walk_is_seeded() {
+        let profile = MotionProfile::RandomWalk {
+            start: UnitQuaternion::identity(),
+            sigma: 0.1,
+        };
+        let a: Vec<GeneratedSample> = Generator::new(profile.clone(), 100.0, 5)
+            .unwrap()
+            .take(100)
+            .collect();
+        let b: Vec<GeneratedSample> = Generator::new(profile.clone(), 100.0, 5)
+            .unwrap()
+            .take(100)
+            .collect();
+        let c: Vec<GeneratedSample> = Generator::new(profile, 100.0, 6)
+            .unwrap()
+            .take(100)
+            .collect();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(a[0].quaternion, UnitQuaternion::identity());
+
+        // Steps are small: three components of 0.1 * sqrt(0.01) standard deviation
+        for pair in a.windows(2) {
+            assert!(angle_between(pair[0].quaternion, pair[1].quaternion) < 0.1);
+        }
+    }
+
+    #[test]
+    fn test_waypoints() {
+        let end = UnitQuaternion::from_euler_angles(0.0, 0.0, 1.0);
+        let waypoints = vec![
+            Waypoint {
+                time: 1.0,
+                orientation: UnitQuaternion::identity(),
+            },
+            Waypoint {
+                time: 3.0,
+                orientation: end,
+            },
+        ];
+        assert_eq!(
+            interpolate_waypoints(&waypoints, 0.0),
+            UnitQuaternion::identity()
+        );
+        assert_eq!(interpolate_waypoints(&waypoints, 5.0), end);
+        let (_, _, yaw) = interpolate_waypoints(&waypoints, 2.0).euler_angles();
+        assert!((yaw - 0.5).abs() < 1e-5);
+        assert_eq!(interpolate_waypoints(&[], 1.0), UnitQuaternion::identity());
+
+        let reversed: Vec<Waypoint> = waypoints.iter().rev().copied().collect();
+        assert!(Generator::new(MotionProfile::Waypoints(reversed), 10.0, 0).is_err());
+        assert!(Generator::new(MotionProfile::Waypoints(waypoints), 10.0, 0).is_ok());
+    }
+
+    #[test]
+    fn test_generator_source_limit() {
+        use crate::conversions::conversion::ConversionPolicy;
+        use crate::frames::frame::{decode_frame, Encoder, FrameLayout};
+        use crate::orientations::orientation::EulerConvention;
+
+        let layout = FrameLayout {
+            fractional_bits: 5,
+            signed: true,
+            ..Default::default()
+        };
+        let profile = MotionProfile::ConstantRate {
+            start: UnitQuaternion::identity(),
+            axis: Vector3::x_axis(),
+            rate: 1.0,
+        };
+        let encoder = Encoder::new(
+            layout,
+            EulerConvention::default(),
+            ConversionPolicy::default(),
+        )
+        .unwrap();
+        let mut source =
+            GeneratorSource::new(Generator::new(profile, 2.0, 0).unwrap(), encoder, Some(3));
+
+        let frames: Vec<Vec<u32>> = std::iter::from_fn(|| source.next_frame()).collect();
+        assert_eq!(frames.len(), 3);
+        let roll = decode_frame(&frames[2], &layout).unwrap().angles[0];
+        assert!((roll - 1.0).abs() <= layout.quantization_bound() + 1e-5);
+    }
+
+    #[test]
+    fn test_generator_source_skips_unencodable_samples() {
+        use crate::conversions::conversion::ConversionPolicy;
+        use crate::frames::frame::{Encoder, FrameLayout};
+        use crate::orientations::orientation::EulerConvention;
+
+        // The default layout is unsigned, so a negative yaw does not fit in it
+        let yaw = UnitQuaternion::from_euler_angles(0.0, 0.0, -0.1);
+        let generator = Generator::new(MotionProfile::Static(yaw), 100.0, 1).unwrap();
+        let mut source = GeneratorSource::new(generator.clone(), Encoder::default(), Some(2));
+        assert_eq!(source.next_timed_frame(), None);
+        assert_eq!(source.unencodable, 2);
+
+        // A saturating encoder clamps it instead
+        let encoder = Encoder::new(
+            FrameLayout::default(),
+            EulerConvention::default(),
+            ConversionPolicy::saturating(),
+        )
+        .unwrap();
+        let mut source = GeneratorSource::new(generator, encoder, Some(2));
+        assert!(source.next_timed_frame().is_some());
+        assert_eq!(source.unencodable, 0);
+    }
+}
@@ -0,0 +1,494 @@
+pub mod health {
+
+    use std::collections::{HashMap, VecDeque};
+    use std::f32::consts::PI;
+    use std::fmt;
+    use std::time::Duration;
+
+    use crate::angles::angle::{wrap_angle, AngleRange};
+    use crate::attitudes::attitude::seconds_between;
+    use crate::frames::frame::FrameKind;
+    use crate::samples::sample::Timestamp;
+    use crate::sensors::sensor::SensorId;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum HealthState {
+        #[default]
+        Ok,
+        // Faults have been seen recently, but not for long
+        Degraded,
+        // Faults have been seen in `fail_after` evaluations in a row
+        Failed,
+    }
+
+    impl fmt::Display for HealthState {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                HealthState::Ok => f.write_str("ok"),
+                HealthState::Degraded => f.write_str("degraded"),
+                HealthState::Failed => f.write_str("failed"),
+            }
+        }
+    }
+
+    // Something wrong with a sensor's input stream
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Fault {
+        // The same x/y/z values for `stuck_frames` frames of one kind in a row
+        Stuck,
+        // A value beyond the range configured for its kind, or not a number
+        OutOfRange,
+        // An attitude angle moved faster than any body could turn
+        RateOfChange,
+        // No frame at all for the dropout time
+        Dropout,
+        // Successive values scatter more than the noise limit
+        Noise,
+    }
+
+    impl fmt::Display for Fault {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Fault::Stuck => f.write_str("stuck"),
+                Fault::OutOfRange => f.write_str("out_of_range"),
+                Fault::RateOfChange => f.write_str("rate_of_change"),
+                Fault::Dropout => f.write_str("dropout"),
+                Fault::Noise => f.write_str("noise"),
+            }
+        }
+    }
+
+    // One limit for each kind of frame, in the units that kind is sent in
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct KindLimits {
+        // Radians
+        pub attitude: f32,
+        // Radians per second
+        pub angular_rate: f32,
+        // Metres per second squared
+        pub acceleration: f32,
+        pub magnetic_field: f32,
+    }
+
+    impl KindLimits {
+        pub fn get(&self, kind: FrameKind) -> f32 {
+            match kind {
+                FrameKind::Attitude => self.attitude,
+                FrameKind::AngularRate => self.angular_rate,
+                FrameKind::Acceleration => self.acceleration,
+                FrameKind::MagneticField => self.magnetic_field,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct NoiseConfig {
+        // Number of latest sample-to-sample differences the spread is taken over
+        pub window: usize,
+        // Largest believable standard deviation of those differences, per kind; white noise of
+        // standard deviation σ gives √2·σ
+        pub limits: KindLimits,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct HealthConfig {
+        // A real sensor's least significant bits always flicker, so this many identical frames
+        // mean it has stopped updating
+        pub stuck_frames: Option<usize>,
+        // Largest believable magnitude of any value
+        pub range: KindLimits,
+        // Fastest believable change of an attitude angle (rad/s)
+        pub max_rate_of_change: Option<f32>,
+        // Longest believable time without a frame, on the host receive clock
+        pub dropout: Option<Duration>,
+        pub noise: Option<NoiseConfig>,
+        // Faulty evaluations in a row before a sensor is failed; every frame is one evaluation,
+        // and so is every dropout time without a frame
+        pub fail_after: usize,
+        // Clean evaluations in a row before a degraded or failed sensor is ok again
+        pub recover_after: usize,
+    }
+
+    impl Default for HealthConfig {
+        fn default() -> Self {
+            HealthConfig {
+                stuck_frames: Some(50),
+                range: KindLimits {
+                    attitude: 2.0 * PI,
+                    angular_rate: 35.0,
+                    acceleration: 160.0,
+                    magnetic_field: 1000.0,
+                },
+                max_rate_of_change: Some(35.0),
+                dropout: Some(Duration::from_millis(100)),
+                noise: None,
+                fail_after: 10,
+                recover_after: 20,
+            }
+        }
+    }
+
+    impl HealthConfig {
+        pub fn validate(&self) -> Result<(), &'static str> {
+            if self.fail_after == 0 || self.recover_after == 0 {
+                return Err("Health transitions need at least one evaluation.");
+            }
+            if self.stuck_frames.is_some_and(|frames| frames < 2) {
+                return Err("A stuck sensor must repeat at least two frames.");
+            }
+            if self.dropout.is_some_and(|dropout| dropout.is_zero()) {
+                return Err("Dropout time must be positive.");
+            }
+            if self.noise.is_some_and(|noise| noise.window < 2) {
+                return Err("Noise window must hold at least two differences.");
+            }
+            Ok(())
+        }
+    }
+
+    // A change of a sensor's health state
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct HealthEvent {
+        pub sensor: SensorId,
+        // Of the frame during which the change was noticed; for a silent sensor, the latest frame
+        // from any sensor, received when the silence was noticed
+        pub timestamp: Timestamp,
+        pub from: HealthState,
+        pub to: HealthState,
+        // Seen in the evaluation that caused the change; empty on recovery
+        pub faults: Vec<Fault>,
+    }
+
+    // One kind of frame's recent values
+    #[derive(Debug, Clone, Default)]
+    struct KindHistory {
+        previous: Option<([f32; 3], Duration)>,
+        // Frames in a row with the same values as the one before
+        repeats: usize,
+        differences: VecDeque<[f32; 3]>,
+    }
+
+    // Judges one sensor's input stream frame by frame
+    #[derive(Debug, Clone)]
+    pub struct HealthMonitor {
+        pub config: HealthConfig,
+        pub sensor: SensorId,
+        state: HealthState,
+        kinds: HashMap<FrameKind, KindHistory>,
+        faulty: usize,
+        clean: usize,
+        last_received: Option<Duration>,
+        // Dropout times already counted since the last frame
+        silences: u64,
+    }
+
+    impl HealthMonitor {
+        pub fn new(sensor: SensorId, config: HealthConfig) -> Result<Self, &'static str> {
+            config.validate()?;
+            Ok(HealthMonitor {
+                config,
+                sensor,
+                state: HealthState::Ok,
+                kinds: HashMap::new(),
+                faulty: 0,
+                clean: 0,
+                last_received: None,
+                silences: 0,
+            })
+        }
+
+        pub fn state(&self) -> HealthState {
+            self.state
+        }
+
+        // Counts every whole dropout time since this sensor's last frame that has not been
+        // counted yet, judged at `now`
+        pub fn check_silence(&mut self, now: Timestamp) -> Vec<HealthEvent> {
+            let (Some(dropout), Some(last)) = (self.config.dropout, self.last_received) else {
+                return Vec::new();
+            };
+            let silences =
+                (now.received.saturating_sub(last).as_nanos() / dropout.as_nanos()) as u64;
+            let mut events = Vec::new();
+            while self.silences < silences {
+                self.silences += 1;
+                events.extend(self.evaluate(vec![Fault::Dropout], now));
+            }
+            events
+        }
+
+        // Judges one decoded frame, after counting any silence before it
+        pub fn check_frame(
+            &mut self,
+            kind: FrameKind,
+            values: [f32; 3],
+            timestamp: Timestamp,
+        ) -> Vec<HealthEvent> {
+            let mut events = self.check_silence(timestamp);
+            self.last_received = Some(timestamp.received);
+            self.silences = 0;
+
+            let config = self.config;
+            let history = self.kinds.entry(kind).or_default();
+            let mut faults = Vec::new();
+            let limit = config.range.get(kind);
+            if values
+                .iter()
+                .any(|value| value.is_nan() || value.abs() > limit)
+            {
+                faults.push(Fault::OutOfRange);
+            }
+            if let Some((previous, previous_time)) = history.previous {
+                let repeated = values
+                    .iter()
+                    .zip(previous)
+                    .all(|(value, previous)| value.to_bits() == previous.to_bits());
+                history.repeats = if repeated { history.repeats + 1 } else { 0 };
+                if config
+                    .stuck_frames
+                    .is_some_and(|frames| history.repeats + 1 >= frames)
+                {
+                    faults.push(Fault::Stuck);
+                }
+
+                // Angles are compared the short way round, so crossing ±π is not a jump
+                let difference = [0, 1, 2].map(|i| match kind {
+                    FrameKind::Attitude => {
+                        wrap_angle(values[i] - previous[i], AngleRange::SignedPi)
+                    }
+                    _ => values[i] - previous[i],
+                });
+                let dt = seconds_between(Some(previous_time), timestamp.source);
+                if let (FrameKind::Attitude, Some(max_rate)) = (kind, config.max_rate_of_change) {
+                    if dt > 0.0 && difference.iter().any(|d| d.abs() / dt > max_rate) {
+                        faults.push(Fault::RateOfChange);
+                    }
+                }
+                if let Some(noise) = config.noise {
+                    history.differences.push_back(difference);
+                    while history.differences.len() > noise.window {
+                        history.differences.pop_front();
+                    }
+                    if history.differences.len() == noise.window
+                        && spread(&history.differences) > noise.limits.get(kind)
+                    {
+                        faults.push(Fault::Noise);
+                    }
+                }
+            }
+            history.previous = Some((values, timestamp.source));
+            events.extend(self.evaluate(faults, timestamp));
+            events
+        }
+
+        fn evaluate(&mut self, faults: Vec<Fault>, timestamp: Timestamp) -> Option<HealthEvent> {
+            let next = if faults.is_empty() {
+                self.faulty = 0;
+                self.clean += 1;
+                if self.clean >= self.config.recover_after {
+                    HealthState::Ok
+                } else {
+                    self.state
+                }
+            } else {
+                self.clean = 0;
+                self.faulty += 1;
+                if self.faulty >= self.config.fail_after || self.state == HealthState::Failed {
+                    HealthState::Failed
+                } else {
+                    HealthState::Degraded
+                }
+            };
+            if next == self.state {
+                return None;
+            }
+            let from = std::mem::replace(&mut self.state, next);
+            Some(HealthEvent {
+                sensor: self.sensor,
+                timestamp,
+                from,
+                to: next,
+                faults,
+            })
+        }
+    }
+
+    // The largest standard deviation of any of the three values
+    fn spread(differences: &VecDeque<[f32; 3]>) -> f32 {
+        let count = differences.len() as f32;
+        (0..3)
+            .map(|i| {
+                let mean = differences.iter().map(|d| d[i]).sum::<f32>() / count;
+                let variance = differences
+                    .iter()
+                    .map(|d| (d[i] - mean) * (d[i] - mean))
+                    .sum::<f32>()
+                    / count;
+                variance.sqrt()
+            })
+            .fold(0.0, f32::max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::health::*;
+    use crate::frames::frame::FrameKind;
+    use crate::samples::sample::Timestamp;
+    use crate::sensors::sensor::SensorId;
+    use std::time::Duration;
+
+    // At 100 Hz, received as soon as taken
+    fn at(step: u64) -> Timestamp {
+        Timestamp {
+            source: Duration::from_millis(10 * step),
+            received: Duration::from_millis(10 * step),
+            ..Default::default()
+        }
+    }
+
+    fn monitor(config: HealthConfig) -> HealthMonitor {
+        HealthMonitor::new(SensorId(1), config).unwrap()
+    }
+
+    // A slowly turning sensor with a little flicker, so that it is neither stuck nor noisy
+    fn turning(step: u64) -> [f32; 3] {
+        let flicker = if step.is_multiple_of(2) {
+            0.001
+        } else {
+            -0.001
+        };
+        [0.1 + flicker, -0.2, 0.01 * step as f32]
+    }
+
+    #[test]
+    fn test_health_config_validation() {
+        let config = HealthConfig {
+            fail_after: 0,
+            ..Default::default()
+        };
+        assert!(HealthMonitor::new(SensorId(1), config).is_err());
+        let config = HealthConfig {
+            stuck_frames: Some(1),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+        assert!(HealthConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_stuck_sensor_degrades_fails_and_recovers() {
+        let mut monitor = monitor(HealthConfig {
+            stuck_frames: Some(5),
+            fail_after: 3,
+            recover_after: 2,
+            ..Default::default()
+        });
+        let mut events = Vec::new();
+        for step in 0..10 {
+            events.extend(monitor.check_frame(FrameKind::Attitude, turning(step), at(step)));
+        }
+        assert!(events.is_empty());
+        // Frozen from step 10; the fifth identical frame is the first stuck one
+        for step in 10..20 {
+            events.extend(monitor.check_frame(FrameKind::Attitude, turning(10), at(step)));
+        }
+        let transitions: Vec<(HealthState, HealthState, u64)> = events
+            .iter()
+            .map(|event| {
+                (
+                    event.from,
+                    event.to,
+                    event.timestamp.source.as_millis() as u64,
+                )
+            })
+            .collect();
+        assert_eq!(
+            transitions,
+            vec![
+                (HealthState::Ok, HealthState::Degraded, 140),
+                (HealthState::Degraded, HealthState::Failed, 160),
+            ]
+        );
+        assert_eq!(events[0].faults, vec![Fault::Stuck]);
+
+        events.clear();
+        for step in 20..23 {
+            events.extend(monitor.check_frame(FrameKind::Attitude, turning(step), at(step)));
+        }
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            (events[0].to, events[0].timestamp),
+            (HealthState::Ok, at(21))
+        );
+        assert!(events[0].faults.is_empty());
+    }
+
+    #[test]
+    fn test_range_rate_and_noise_faults() {
+        let check = |config: HealthConfig, spike: [f32; 3]| {
+            let mut monitor = monitor(config);
+            for step in 0..20 {
+                monitor.check_frame(FrameKind::Attitude, turning(step), at(step));
+            }
+            monitor.check_frame(FrameKind::Attitude, spike, at(20))
+        };
+        let config = HealthConfig::default();
+        let events = check(config, [0.1, -0.2, 9.0]);
+        assert_eq!(
+            events[0].faults,
+            vec![Fault::OutOfRange, Fault::RateOfChange]
+        );
+        // 1 rad in 10 ms is far too fast for a body to turn
+        let events = check(config, [0.1, -0.2, 1.2]);
+        assert_eq!(events[0].faults, vec![Fault::RateOfChange]);
+        // Crossing ±π is not a jump
+        let mut wrapping = monitor(config);
+        wrapping.check_frame(FrameKind::Attitude, [0.0, 0.0, 3.1], at(0));
+        assert!(wrapping
+            .check_frame(FrameKind::Attitude, [0.0, 0.0, -3.1], at(1))
+            .is_empty());
+
+        // Scatter well above the turning sensor's flicker
+        let noisy = HealthConfig {
+            noise: Some(NoiseConfig {
+                window: 10,
+                limits: KindLimits {
+                    attitude: 0.01,
+                    ..config.range
+                },
+            }),
+            ..config
+        };
+        assert!(check(noisy, turning(20)).is_empty());
+        let mut monitor = monitor(noisy);
+        let mut events = Vec::new();
+        for step in 0..20 {
+            let mut values = turning(step);
+            values[0] += if step % 3 == 0 { 0.05 } else { -0.02 };
+            events.extend(monitor.check_frame(FrameKind::Attitude, values, at(step)));
+        }
+        assert_eq!(events[0].faults, vec![Fault::Noise]);
+    }
+
+    #[test]
+    fn test_dropout_counts_each_silent_period() {
+        let mut monitor = monitor(HealthConfig {
+            dropout: Some(Duration::from_millis(100)),
+            fail_after: 3,
+            ..Default::default()
+        });
+        assert!(monitor.check_silence(at(50)).is_empty());
+        monitor.check_frame(FrameKind::Attitude, turning(0), at(0));
+        assert!(monitor.check_silence(at(9)).is_empty());
+        // A tenth of a second of silence degrades the sensor, and three fail it
+        let events = monitor.check_silence(at(10));
+        assert_eq!(events[0].to, HealthState::Degraded);
+        assert_eq!(events[0].faults, vec![Fault::Dropout]);
+        assert!(monitor.check_silence(at(19)).is_empty());
+        let events = monitor.check_silence(at(30));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].to, HealthState::Failed);
+        assert_eq!(monitor.state(), HealthState::Failed);
+    }
+}
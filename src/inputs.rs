@@ -1,6 +1,40 @@
 pub mod input {
     use crossbeam_queue::SegQueue;
     use std::io::{self, Write};
+    use std::time::Duration;
+
+    use crate::frames::frame::FrameKind;
+    use crate::sensors::sensor::SensorId;
+
+    // A frame together with the time the sensor stamped it with, if it did
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct TimedFrame {
+        pub bits: Vec<u32>,
+        pub sensor_time: Option<Duration>,
+        pub sensor: SensorId,
+        pub kind: FrameKind,
+    }
+
+    // Where the pipeline's first thread gets its frames from; `None` ends the run
+    pub trait Source: Send {
+        fn next_frame(&mut self) -> Option<Vec<u32>>;
+
+        // Sources that know when a frame was taken override this
+        fn next_timed_frame(&mut self) -> Option<TimedFrame> {
+            self.next_frame().map(|bits| TimedFrame {
+                bits,
+                sensor_time: None,
+                sensor: SensorId::default(),
+                kind: FrameKind::Attitude,
+            })
+        }
+    }
+
+    impl Source for SegQueue<Vec<u32>> {
+        fn next_frame(&mut self) -> Option<Vec<u32>> {
+            self.pop()
+        }
+    }
 
     pub fn read_queue_from_user(mode: &str, test_input: &str) -> SegQueue<Vec<u32>> {
         let q: SegQueue<Vec<u32>> = SegQueue::new();
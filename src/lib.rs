@@ -0,0 +1,23 @@
+pub mod angles;
+pub mod attitudes;
+pub mod calibrations;
+pub mod calibrators;
+pub mod conversions;
+pub mod coordinates;
+pub mod filters;
+pub mod fixed_points;
+pub mod frames;
+pub mod fusions;
+pub mod generators;
+pub mod healths;
+pub mod inputs;
+pub mod noises;
+pub mod orientations;
+pub mod outputs;
+pub mod replays;
+pub mod resamplings;
+pub mod samples;
+pub mod sensors;
+pub mod simulations;
+pub mod synchronisations;
+pub mod velocities;
@@ -1,13 +1,99 @@
-use crate::inputs::input::read_queue_from_user;
-use crate::simulations::simulation::run_simulation;
-mod conversions;
-mod inputs;
-mod simulations;
+use ravn::calibrators::calibrator::{calibrate_capture, CalibrateConfig};
+use ravn::frames::frame::SpareByte;
+use ravn::inputs::input::read_queue_from_user;
+use ravn::sensors::sensor::SensorId;
+use ravn::simulations::simulation::run_simulation;
 
 use crossbeam_queue::SegQueue;
 use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process;
+
+const CALIBRATE_USAGE: &str = "Usage: ravn calibrate <capture> <directory> [sensor] \
+[--fractional-bits <n>] [--signed] [--spare unused|sequence|crc8] [--convention <ZYX|zyx|...>]";
+
+// What `ravn calibrate` was asked to do
+#[derive(Debug)]
+struct CalibrateArgs {
+    capture: PathBuf,
+    directory: PathBuf,
+    sensor: SensorId,
+    config: CalibrateConfig,
+}
+
+// Positional arguments first, then options describing how the captured frames are packed;
+// anything not given keeps `CalibrateConfig::default()`
+fn parse_calibrate_args(args: &[String]) -> Result<CalibrateArgs, &'static str> {
+    let mut positional: Vec<&String> = Vec::new();
+    let mut config = CalibrateConfig::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or("Option is missing its value.");
+        match arg.as_str() {
+            "--fractional-bits" => {
+                config.layout.fractional_bits = value()?
+                    .parse()
+                    .map_err(|_| "Fractional bits must be a number.")?;
+            }
+            "--signed" => config.layout.signed = true,
+            "--spare" => {
+                config.layout.spare = match value()?.as_str() {
+                    "unused" => SpareByte::Unused,
+                    "sequence" => SpareByte::Sequence,
+                    "crc8" => SpareByte::Crc8,
+                    _ => return Err("Spare byte must be unused, sequence or crc8."),
+                };
+            }
+            "--convention" => config.convention = value()?.parse()?,
+            option if option.starts_with("--") => return Err("Unknown option."),
+            _ => positional.push(arg),
+        }
+    }
+    config.layout.validate()?;
+    let (capture, directory, sensor) = match positional[..] {
+        [capture, directory] => (capture, directory, None),
+        [capture, directory, sensor] => (capture, directory, Some(sensor)),
+        _ => return Err("Expected a capture file and a directory."),
+    };
+    let sensor = match sensor.map(|sensor| sensor.parse()) {
+        None => SensorId::default(),
+        Some(Ok(sensor)) => SensorId(sensor),
+        Some(Err(_)) => return Err("Sensor must be a number."),
+    };
+    Ok(CalibrateArgs {
+        capture: PathBuf::from(capture),
+        directory: PathBuf::from(directory),
+        sensor,
+        config,
+    })
+}
+
+// `ravn calibrate <capture> <directory> [sensor] [options]` fits a stationary capture and writes
+// the sensor's calibration file into the directory
+fn calibrate(args: &[String]) {
+    let args = match parse_calibrate_args(args) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{}\n{}", error, CALIBRATE_USAGE);
+            process::exit(2);
+        }
+    };
+    match calibrate_capture(&args.capture, &args.directory, args.sensor, &args.config) {
+        Ok(fit) => println!("{}", fit),
+        Err(error) => {
+            eprintln!("Calibration failed: {}", error);
+            process::exit(1);
+        }
+    }
+}
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("calibrate") {
+        calibrate(&args[1..]);
+        return;
+    }
+
     let mut input = String::new();
     print!("Enter 'y' to use the default queue or 'n' to enter a queue: ");
     io::stdout().flush().unwrap();
@@ -26,6 +112,117 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ravn::calibrations::calibration::{calibration_path, Calibration};
+    use ravn::conversions::conversion::ConversionPolicy;
+    use ravn::frames::frame::{Encoder, FrameLayout};
+    use ravn::orientations::orientation::{EulerAngles, EulerConvention};
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_calibrate_args() {
+        let args = parse_calibrate_args(&strings(&["capture.txt", "out"])).unwrap();
+        assert_eq!(args.sensor, SensorId::default());
+        assert_eq!(args.config, CalibrateConfig::default());
+
+        let args = parse_calibrate_args(&strings(&[
+            "capture.txt",
+            "--fractional-bits",
+            "5",
+            "out",
+            "--signed",
+            "--spare",
+            "crc8",
+            "--convention",
+            "ZYX",
+            "3",
+        ]))
+        .unwrap();
+        assert_eq!(args.capture, PathBuf::from("capture.txt"));
+        assert_eq!(args.directory, PathBuf::from("out"));
+        assert_eq!(args.sensor, SensorId(3));
+        assert_eq!(
+            args.config.layout,
+            FrameLayout {
+                fractional_bits: 5,
+                signed: true,
+                spare: SpareByte::Crc8,
+            }
+        );
+        assert_eq!(args.config.convention, EulerConvention::AEROSPACE);
+
+        for bad in [
+            &["capture.txt"][..],
+            &["capture.txt", "out", "sensor"],
+            &["capture.txt", "out", "--fractional-bits"],
+            &["capture.txt", "out", "--fractional-bits", "64"],
+            &["capture.txt", "out", "--spare", "parity"],
+            &["capture.txt", "out", "--convention", "Zyx"],
+            &["capture.txt", "out", "--unsigned"],
+        ] {
+            assert!(parse_calibrate_args(&strings(bad)).is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn test_calibrate_capture_with_cli_options() {
+        // A sensor that needs no calibration, sending signed Q2.5 fields in the aerospace
+        // convention; the negative angles cannot be read with the default unsigned layout
+        let layout = FrameLayout {
+            fractional_bits: 5,
+            signed: true,
+            spare: SpareByte::Unused,
+        };
+        let convention = EulerConvention::AEROSPACE;
+        let mut encoder = Encoder::new(layout, convention, ConversionPolicy::default()).unwrap();
+        let mut text = String::new();
+        for (roll, pitch, yaw) in [
+            (0.0, 0.0, 0.0),
+            (0.8, 0.0, 0.0),
+            (-0.8, 0.3, 0.0),
+            (0.0, 0.7, 0.5),
+            (0.0, -0.7, -0.5),
+            (0.4, 0.4, 1.2),
+            (-0.5, -0.2, -1.2),
+        ] {
+            text.push_str(&format!("orientation {} {} {}\n", roll, pitch, yaw));
+            let orientation = EulerAngles::new(roll, pitch, yaw).to_quaternion();
+            let bits = encoder.encode_quaternion(orientation).unwrap();
+            let bits: Vec<String> = bits.iter().map(u32::to_string).collect();
+            text.push_str(&bits.join(","));
+            text.push('\n');
+        }
+
+        let directory = std::env::temp_dir().join(format!("ravn-cli-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let capture = directory.join("capture.txt");
+        std::fs::write(&capture, text).unwrap();
+        let args = parse_calibrate_args(&strings(&[
+            capture.to_str().unwrap(),
+            directory.to_str().unwrap(),
+            "4",
+            "--fractional-bits",
+            "5",
+            "--signed",
+            "--convention",
+            "ZYX",
+        ]))
+        .unwrap();
+        let fit =
+            calibrate_capture(&args.capture, &args.directory, args.sensor, &args.config).unwrap();
+        let saved = Calibration::load(&calibration_path(&directory, SensorId(4))).unwrap();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(saved.bias, fit.calibration.bias);
+        // Within the field resolution of 1/32 rad of no calibration at all
+        assert!(fit.rms < 0.03, "{}", fit);
+        for i in 0..3 {
+            assert!(saved.bias[i].abs() < 0.03, "{}", fit);
+            assert!((saved.scale[i] - 1.0).abs() < 0.05, "{}", fit);
+        }
+    }
 
     #[test]
     fn test_run_simulation() {
@@ -0,0 +1,242 @@
+pub mod noise {
+
+    use nalgebra::{UnitQuaternion, Vector3};
+
+    use crate::generators::generator::Rng;
+    use crate::orientations::orientation::EulerConvention;
+
+    // Error sources of a simulated sensor, applied to clean orientations before encoding.
+    // Per-axis values are in the order of the encoder's Euler angles; the default has no errors.
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub struct ErrorConfig {
+        // Standard deviation of the noise added to every sample (rad)
+        pub white_noise: [f32; 3],
+        // Constant offset (rad)
+        pub bias: [f32; 3],
+        // Drift of the bias (rad/√s)
+        pub bias_random_walk: [f32; 3],
+        // Relative gain error; 0.01 reads 1% high
+        pub scale_factor: [f32; 3],
+        // Small rotation (as a rotation vector, rad) between the true and the sensor axes
+        pub misalignment: [f32; 3],
+        // Step the reported angles are rounded to (rad); zero disables it
+        pub quantization: f32,
+        // Chance per sample of getting stuck, and for how many samples the last output repeats
+        pub stuck_probability: f64,
+        pub stuck_duration: u64,
+        // Chance per frame of never arriving
+        pub dropout_probability: f64,
+        // Chance per bit of arriving flipped
+        pub bit_flip_probability: f64,
+    }
+
+    // How often the discrete faults fired
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct ErrorStats {
+        pub stuck: usize,
+        pub dropped: usize,
+        pub flipped_bits: usize,
+    }
+
+    // Stateful, seeded application of an `ErrorConfig`
+    #[derive(Debug, Clone)]
+    pub struct ErrorModel {
+        pub config: ErrorConfig,
+        pub stats: ErrorStats,
+        rng: Rng,
+        bias_walk: [f64; 3],
+        last: Option<[f32; 3]>,
+        stuck_remaining: u64,
+    }
+
+    impl ErrorModel {
+        pub fn new(config: ErrorConfig, seed: u64) -> Self {
+            ErrorModel {
+                config,
+                stats: ErrorStats::default(),
+                rng: Rng::new(seed),
+                bias_walk: [0.0; 3],
+                last: None,
+                stuck_remaining: 0,
+            }
+        }
+
+        // The Euler angles (in `convention`) a faulty sensor reports for the true orientation;
+        // `dt` is the time since the previous sample in seconds
+        pub fn measure(
+            &mut self,
+            quaternion: UnitQuaternion<f32>,
+            convention: EulerConvention,
+            dt: f64,
+        ) -> [f32; 3] {
+            let config = self.config;
+
+            // Draw every random number up front so that the sequence does not depend on faults
+            for i in 0..3 {
+                self.bias_walk[i] +=
+                    self.rng.gaussian() * config.bias_random_walk[i] as f64 * dt.sqrt();
+            }
+            let noise: [f64; 3] = [
+                self.rng.gaussian(),
+                self.rng.gaussian(),
+                self.rng.gaussian(),
+            ];
+            let gets_stuck = self.rng.next_f64() < config.stuck_probability;
+
+            if self.stuck_remaining > 0 {
+                self.stuck_remaining -= 1;
+                self.stats.stuck += 1;
+                if let Some(last) = self.last {
+                    return last;
+                }
+            }
+
+            let [mx, my, mz] = config.misalignment;
+            let misaligned =
+                quaternion * UnitQuaternion::from_scaled_axis(Vector3::new(mx, my, mz));
+            let mut angles = convention.from_quaternion(misaligned);
+            for i in 0..3 {
+                let mut angle = angles[i] as f64 * (1.0 + config.scale_factor[i] as f64);
+                angle += config.bias[i] as f64 + self.bias_walk[i];
+                angle += noise[i] * config.white_noise[i] as f64;
+                if config.quantization > 0.0 {
+                    let step = config.quantization as f64;
+                    angle = (angle / step).round() * step;
+                }
+                angles[i] = angle as f32;
+            }
+
+            if gets_stuck && config.stuck_duration > 0 {
+                // This sample is the value that will be repeated
+                self.stuck_remaining = config.stuck_duration;
+            }
+            self.last = Some(angles);
+            angles
+        }
+
+        pub fn drops_frame(&mut self) -> bool {
+            let dropped = self.rng.next_f64() < self.config.dropout_probability;
+            if dropped {
+                self.stats.dropped += 1;
+            }
+            dropped
+        }
+
+        pub fn flip_bits(&mut self, frame: &mut [u32]) {
+            if self.config.bit_flip_probability <= 0.0 {
+                return;
+            }
+            for bit in frame.iter_mut() {
+                if self.rng.next_f64() < self.config.bit_flip_probability {
+                    *bit ^= 1;
+                    self.stats.flipped_bits += 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::noise::*;
+    use crate::orientations::orientation::{EulerAngles, EulerConvention};
+    use nalgebra::UnitQuaternion;
+
+    fn truth() -> UnitQuaternion<f32> {
+        EulerAngles::new(0.1, 0.2, 0.3).to_quaternion()
+    }
+
+    #[test]
+    fn test_no_errors_by_default() {
+        let mut model = ErrorModel::new(ErrorConfig::default(), 1);
+        let angles = model.measure(truth(), EulerConvention::default(), 0.01);
+        for (angle, expected) in angles.iter().zip([0.1, 0.2, 0.3]) {
+            assert!((angle - expected).abs() < 1e-5);
+        }
+        assert!(!model.drops_frame());
+        let mut frame = vec![1, 0, 1, 0];
+        model.flip_bits(&mut frame);
+        assert_eq!(frame, vec![1, 0, 1, 0]);
+    }
+
+    #[test]
+    fn test_bias_scale_and_quantization() {
+        let config = ErrorConfig {
+            bias: [0.05, 0.0, 0.0],
+            scale_factor: [0.0, 0.5, 0.0],
+            quantization: 0.25,
+            ..Default::default()
+        };
+        let mut model = ErrorModel::new(config, 1);
+        let angles = model.measure(truth(), EulerConvention::default(), 0.01);
+        // Roll reads 0.15 and pitch 0.3; like yaw, both round to the 0.25 step
+        assert_eq!(angles, [0.25, 0.25, 0.25]);
+    }
+
+    #[test]
+    fn test_white_noise_and_bias_walk_are_seeded() {
+        let config = ErrorConfig {
+            white_noise: [0.01; 3],
+            bias_random_walk: [0.02; 3],
+            ..Default::default()
+        };
+        let run = |seed| {
+            let mut model = ErrorModel::new(config, seed);
+            (0..50)
+                .map(|_| model.measure(truth(), EulerConvention::default(), 0.01))
+                .collect::<Vec<[f32; 3]>>()
+        };
+        assert_eq!(run(3), run(3));
+        assert_ne!(run(3), run(4));
+
+        let samples = run(3);
+        let mean = samples.iter().map(|a| a[0] as f64).sum::<f64>() / samples.len() as f64;
+        assert!((mean - 0.1).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_misalignment() {
+        let config = ErrorConfig {
+            misalignment: [0.0, 0.0, 0.1],
+            ..Default::default()
+        };
+        let mut model = ErrorModel::new(config, 1);
+        let angles = model.measure(UnitQuaternion::identity(), EulerConvention::default(), 0.01);
+        assert!(angles[0].abs() < 1e-6);
+        assert!((angles[2] - 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_stuck_at() {
+        let config = ErrorConfig {
+            stuck_probability: 1.0,
+            stuck_duration: 2,
+            ..Default::default()
+        };
+        let mut model = ErrorModel::new(config, 1);
+        let convention = EulerConvention::default();
+        let first = model.measure(truth(), convention, 0.01);
+        let moved = EulerAngles::new(0.5, 0.5, 0.5).to_quaternion();
+        assert_eq!(model.measure(moved, convention, 0.01), first);
+        assert_eq!(model.measure(moved, convention, 0.01), first);
+        assert_eq!(model.stats.stuck, 2);
+    }
+
+    #[test]
+    fn test_dropouts_and_bit_flips() {
+        let config = ErrorConfig {
+            dropout_probability: 0.5,
+            bit_flip_probability: 1.0,
+            ..Default::default()
+        };
+        let mut model = ErrorModel::new(config, 9);
+        let dropped = (0..1000).filter(|_| model.drops_frame()).count();
+        assert!((400..600).contains(&dropped));
+        assert_eq!(model.stats.dropped, dropped);
+
+        let mut frame = vec![1, 0, 1, 0];
+        model.flip_bits(&mut frame);
+        assert_eq!(frame, vec![0, 1, 0, 1]);
+        assert_eq!(model.stats.flipped_bits, 4);
+    }
+}
@@ -0,0 +1,872 @@
+pub mod orientation {
+
+    use nalgebra::{Matrix3, Quaternion, Rotation3, Unit, UnitQuaternion, Vector3};
+    use std::fmt;
+    use std::str::FromStr;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum Axis {
+        X,
+        Y,
+        Z,
+    }
+
+    impl Axis {
+        pub fn index(self) -> usize {
+            match self {
+                Axis::X => 0,
+                Axis::Y => 1,
+                Axis::Z => 2,
+            }
+        }
+
+        pub fn unit_vector(self) -> Unit<Vector3<f32>> {
+            match self {
+                Axis::X => Vector3::x_axis(),
+                Axis::Y => Vector3::y_axis(),
+                Axis::Z => Vector3::z_axis(),
+            }
+        }
+    }
+
+    // The 12 axis sequences: six Tait-Bryan (all axes distinct) and six proper Euler (first axis repeated)
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum EulerSequence {
+        XYZ,
+        XZY,
+        YXZ,
+        YZX,
+        ZXY,
+        ZYX,
+        XYX,
+        XZX,
+        YXY,
+        YZY,
+        ZXZ,
+        ZYZ,
+    }
+
+    impl EulerSequence {
+        pub const ALL: [EulerSequence; 12] = [
+            EulerSequence::XYZ,
+            EulerSequence::XZY,
+            EulerSequence::YXZ,
+            EulerSequence::YZX,
+            EulerSequence::ZXY,
+            EulerSequence::ZYX,
+            EulerSequence::XYX,
+            EulerSequence::XZX,
+            EulerSequence::YXY,
+            EulerSequence::YZY,
+            EulerSequence::ZXZ,
+            EulerSequence::ZYZ,
+        ];
+
+        pub fn axes(self) -> [Axis; 3] {
+            use Axis::*;
+            match self {
+                EulerSequence::XYZ => [X, Y, Z],
+                EulerSequence::XZY => [X, Z, Y],
+                EulerSequence::YXZ => [Y, X, Z],
+                EulerSequence::YZX => [Y, Z, X],
+                EulerSequence::ZXY => [Z, X, Y],
+                EulerSequence::ZYX => [Z, Y, X],
+                EulerSequence::XYX => [X, Y, X],
+                EulerSequence::XZX => [X, Z, X],
+                EulerSequence::YXY => [Y, X, Y],
+                EulerSequence::YZY => [Y, Z, Y],
+                EulerSequence::ZXZ => [Z, X, Z],
+                EulerSequence::ZYZ => [Z, Y, Z],
+            }
+        }
+
+        pub fn is_proper_euler(self) -> bool {
+            let [first, _, third] = self.axes();
+            first == third
+        }
+    }
+
+    // Intrinsic rotations are about the axes of the rotating body, each one already moved by the
+    // previous rotations. Extrinsic rotations are about the fixed reference axes.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum RotationFrame {
+        Intrinsic,
+        Extrinsic,
+    }
+
+    // How a triplet of Euler angles maps to a rotation. `angles[i]` is always the rotation about
+    // `sequence.axes()[i]`, and the rotations are applied in that order.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct EulerConvention {
+        pub sequence: EulerSequence,
+        pub frame: RotationFrame,
+    }
+
+    impl EulerConvention {
+        // The convention Ravn has always used: roll about x, then pitch about y, then yaw about z,
+        // all about the fixed axes (the same rotation as intrinsic z-y-x with the angles reversed)
+        pub const ROLL_PITCH_YAW: EulerConvention = EulerConvention {
+            sequence: EulerSequence::XYZ,
+            frame: RotationFrame::Extrinsic,
+        };
+
+        // Aerospace heading, elevation, bank
+        pub const AEROSPACE: EulerConvention = EulerConvention {
+            sequence: EulerSequence::ZYX,
+            frame: RotationFrame::Intrinsic,
+        };
+
+        pub fn new(sequence: EulerSequence, frame: RotationFrame) -> Self {
+            EulerConvention { sequence, frame }
+        }
+
+        pub fn intrinsic(sequence: EulerSequence) -> Self {
+            EulerConvention::new(sequence, RotationFrame::Intrinsic)
+        }
+
+        pub fn extrinsic(sequence: EulerSequence) -> Self {
+            EulerConvention::new(sequence, RotationFrame::Extrinsic)
+        }
+
+        // Names for each angle: roll/pitch/yaw by rotation axis for Tait-Bryan sequences,
+        // and alpha/beta/gamma by position for proper Euler sequences
+        pub fn labels(self) -> [&'static str; 3] {
+            if self.sequence.is_proper_euler() {
+                return ["alpha", "beta", "gamma"];
+            }
+            self.sequence.axes().map(|axis| match axis {
+                Axis::X => "roll",
+                Axis::Y => "pitch",
+                Axis::Z => "yaw",
+            })
+        }
+
+        pub fn to_quaternion(self, angles: [f32; 3]) -> UnitQuaternion<f32> {
+            let axes = self.sequence.axes();
+            let rotations: Vec<UnitQuaternion<f32>> = axes
+                .iter()
+                .zip(angles.iter())
+                .map(|(axis, angle)| UnitQuaternion::from_axis_angle(&axis.unit_vector(), *angle))
+                .collect();
+            let quaternion = match self.frame {
+                // Body-axis rotations compose left to right, fixed-axis rotations right to left
+                RotationFrame::Intrinsic => rotations[0] * rotations[1] * rotations[2],
+                RotationFrame::Extrinsic => rotations[2] * rotations[1] * rotations[0],
+            };
+            canonical_quaternion(quaternion)
+        }
+
+        // Extracts the angles with the general quaternion method of Bernardes and Viollet (2022),
+        // which handles all 12 sequences in both frames without going through a rotation matrix.
+        // Angles are in [-π, π]; the middle angle is in [-π/2, π/2] for Tait-Bryan sequences and
+        // [0, π] for proper Euler sequences.
+        pub fn from_quaternion(self, quaternion: UnitQuaternion<f32>) -> [f32; 3] {
+            self.extract(quaternion, &SingularityConfig::exact(), None)
+                .angles
+        }
+
+        // Like `from_quaternion`, but flags samples within `singularity.threshold` of gimbal lock
+        // and resolves them with `singularity.strategy`. `previous_third` is the third angle of
+        // the previous sample, used by `SingularityStrategy::HoldThird`.
+        pub fn extract(
+            self,
+            quaternion: UnitQuaternion<f32>,
+            singularity: &SingularityConfig,
+            previous_third: Option<f32>,
+        ) -> EulerAngles {
+            let q = quaternion.into_inner();
+            // Scalar-last, so that the vector part is indexed by axis
+            let quat = [q.i as f64, q.j as f64, q.k as f64, q.w as f64];
+
+            let extrinsic = self.frame == RotationFrame::Extrinsic;
+            let mut axes = self.sequence.axes();
+            if !extrinsic {
+                // An intrinsic sequence is the reversed extrinsic one
+                axes.reverse();
+            }
+            let i = axes[0].index();
+            let j = axes[1].index();
+            let symmetric = self.sequence.is_proper_euler();
+            let k = if symmetric {
+                3 - i - j
+            } else {
+                axes[2].index()
+            };
+            // +1 for an even permutation of (x, y, z), -1 for an odd one
+            let sign =
+                ((i as i64 - j as i64) * (j as i64 - k as i64) * (k as i64 - i as i64) / 2) as f64;
+
+            let (a, b, c, d) = if symmetric {
+                (quat[3], quat[i], quat[j], quat[k] * sign)
+            } else {
+                (
+                    quat[3] - quat[j],
+                    quat[i] + quat[k] * sign,
+                    quat[j] + quat[3],
+                    quat[k] * sign - quat[i],
+                )
+            };
+
+            let mut angles = [0.0f64; 3];
+            angles[1] = 2.0 * c.hypot(d).atan2(a.hypot(b));
+            let half_sum = b.atan2(a);
+            let half_diff = d.atan2(c);
+
+            // Before the Tait-Bryan shift the middle angle is in [0, π] and singular at both ends
+            let distance = angles[1].min(std::f64::consts::PI - angles[1]);
+            let near_singularity = singularity.flags(distance);
+
+            if near_singularity {
+                // Only the sum (middle angle near 0) or difference (near π) of the outer angles is
+                // well defined. The strategy fixes the third angle and the first absorbs the rest.
+                let third = match singularity.strategy {
+                    SingularityStrategy::ZeroThird => 0.0,
+                    SingularityStrategy::HoldThird => previous_third.unwrap_or(0.0) as f64,
+                };
+                // Undo the sign flip and reversal applied below to find the raw slot of the third angle
+                let tait_bryan_sign = if symmetric { 1.0 } else { sign };
+                let at_zero = angles[1] < std::f64::consts::FRAC_PI_2;
+                if extrinsic {
+                    angles[2] = third * tait_bryan_sign;
+                    angles[0] = if at_zero {
+                        2.0 * half_sum - angles[2]
+                    } else {
+                        angles[2] - 2.0 * half_diff
+                    };
+                } else {
+                    angles[0] = third;
+                    angles[2] = if at_zero {
+                        2.0 * half_sum - angles[0]
+                    } else {
+                        angles[0] + 2.0 * half_diff
+                    };
+                }
+            } else {
+                angles[0] = half_sum - half_diff;
+                angles[2] = half_sum + half_diff;
+            }
+
+            if !symmetric {
+                angles[2] *= sign;
+                angles[1] -= std::f64::consts::FRAC_PI_2;
+            }
+            if !extrinsic {
+                angles.swap(0, 2);
+            }
+
+            EulerAngles {
+                angles: angles.map(|angle| wrap_to_pi(angle) as f32),
+                convention: self,
+                near_singularity,
+            }
+        }
+
+        // Distance in radians of the middle angle from the nearest gimbal-lock configuration
+        pub fn singularity_distance(self, middle_angle: f32) -> f32 {
+            if self.sequence.is_proper_euler() {
+                middle_angle
+                    .abs()
+                    .min(std::f32::consts::PI - middle_angle.abs())
+            } else {
+                std::f32::consts::FRAC_PI_2 - middle_angle.abs()
+            }
+        }
+    }
+
+    // How the outer angles are chosen close to gimbal lock, where only their sum or difference
+    // is defined. Either way the middle angle and the combined outer rotation are preserved.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum SingularityStrategy {
+        // Set the third angle to zero and put the whole outer rotation into the first
+        #[default]
+        ZeroThird,
+        // Keep the third angle from the previous sample, so it does not jump while passing the singularity
+        HoldThird,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct SingularityConfig {
+        // Samples whose middle angle is within this many radians of gimbal lock are flagged
+        pub threshold: f32,
+        pub strategy: SingularityStrategy,
+    }
+
+    impl SingularityConfig {
+        pub const DEFAULT_THRESHOLD: f32 = 1e-3;
+
+        // Whether a middle angle this many radians from gimbal lock is flagged; gimbal lock
+        // itself is recognised within rounding error whatever the threshold
+        pub fn flags(&self, distance: f64) -> bool {
+            const EPSILON: f64 = 1e-7;
+            distance <= (self.threshold as f64).max(EPSILON)
+        }
+
+        // Only exact singularities are treated specially
+        pub fn exact() -> Self {
+            SingularityConfig {
+                threshold: 0.0,
+                strategy: SingularityStrategy::ZeroThird,
+            }
+        }
+    }
+
+    impl Default for SingularityConfig {
+        fn default() -> Self {
+            SingularityConfig {
+                threshold: SingularityConfig::DEFAULT_THRESHOLD,
+                strategy: SingularityStrategy::default(),
+            }
+        }
+    }
+
+    impl Default for EulerConvention {
+        fn default() -> Self {
+            EulerConvention::ROLL_PITCH_YAW
+        }
+    }
+
+    // Upper case for intrinsic ("ZYX") and lower case for extrinsic ("xyz")
+    impl fmt::Display for EulerConvention {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let name = format!("{:?}", self.sequence);
+            match self.frame {
+                RotationFrame::Intrinsic => write!(f, "{}", name),
+                RotationFrame::Extrinsic => write!(f, "{}", name.to_lowercase()),
+            }
+        }
+    }
+
+    impl FromStr for EulerConvention {
+        type Err = &'static str;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let s = s.trim();
+            let frame = if s.chars().all(|c| c.is_ascii_uppercase()) {
+                RotationFrame::Intrinsic
+            } else if s.chars().all(|c| c.is_ascii_lowercase()) {
+                RotationFrame::Extrinsic
+            } else {
+                return Err("Invalid Euler convention; use upper case for intrinsic (ZYX) or lower case for extrinsic (zyx).");
+            };
+            let upper = s.to_ascii_uppercase();
+            let sequence = EulerSequence::ALL
+                .into_iter()
+                .find(|sequence| format!("{:?}", sequence) == upper)
+                .ok_or("Invalid Euler convention; expected one of the 12 axis sequences such as ZYX or ZXZ.")?;
+            Ok(EulerConvention::new(sequence, frame))
+        }
+    }
+
+    // Euler angles in radians, tagged with the convention needed to interpret them.
+    // `near_singularity` marks angles extracted close to gimbal lock, where the first and
+    // third angles are not individually trustworthy.
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub struct EulerAngles {
+        pub angles: [f32; 3],
+        pub convention: EulerConvention,
+        pub near_singularity: bool,
+    }
+
+    impl EulerAngles {
+        // Roll, pitch and yaw in Ravn's default convention
+        pub fn new(roll: f32, pitch: f32, yaw: f32) -> Self {
+            EulerAngles::with_convention([roll, pitch, yaw], EulerConvention::ROLL_PITCH_YAW)
+        }
+
+        // Only angles at gimbal lock itself are flagged; flagging them within a configured
+        // threshold is up to `EulerConvention::extract`
+        pub fn with_convention(angles: [f32; 3], convention: EulerConvention) -> Self {
+            let near_singularity =
+                SingularityConfig::exact().flags(convention.singularity_distance(angles[1]) as f64);
+            EulerAngles {
+                angles,
+                convention,
+                near_singularity,
+            }
+        }
+
+        pub fn from_quaternion(
+            quaternion: UnitQuaternion<f32>,
+            convention: EulerConvention,
+        ) -> Self {
+            EulerAngles::with_convention(convention.from_quaternion(quaternion), convention)
+        }
+
+        pub fn to_quaternion(&self) -> UnitQuaternion<f32> {
+            self.convention.to_quaternion(self.angles)
+        }
+
+        // The same rotation expressed in another convention
+        pub fn convert(&self, convention: EulerConvention) -> Self {
+            EulerAngles::from_quaternion(self.to_quaternion(), convention)
+        }
+
+        pub fn labels(&self) -> [&'static str; 3] {
+            self.convention.labels()
+        }
+    }
+
+    impl From<(f32, f32, f32)> for EulerAngles {
+        fn from((roll, pitch, yaw): (f32, f32, f32)) -> Self {
+            EulerAngles::new(roll, pitch, yaw)
+        }
+    }
+
+    // Wraps an angle into [-π, π]
+    pub fn wrap_to_pi(angle: f64) -> f64 {
+        use std::f64::consts::PI;
+        if (-PI..=PI).contains(&angle) {
+            angle
+        } else {
+            (angle + PI).rem_euclid(2.0 * PI) - PI
+        }
+    }
+
+    // An orientation carried both as the Euler angles we decoded and as a unit quaternion.
+    // Quaternions have no gimbal-lock ambiguity, so they are the canonical form for consumers.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Orientation {
+        pub euler: EulerAngles,
+        pub quaternion: UnitQuaternion<f32>,
+    }
+
+    impl Orientation {
+        pub fn new(euler: EulerAngles, quaternion: UnitQuaternion<f32>) -> Self {
+            Orientation {
+                euler,
+                quaternion: canonical_quaternion(quaternion),
+            }
+        }
+
+        pub fn from_euler_angles(euler: EulerAngles) -> Self {
+            Orientation::new(euler, quaternion_from_euler_angles(euler))
+        }
+
+        pub fn from_quaternion(quaternion: UnitQuaternion<f32>) -> Self {
+            Orientation::from_quaternion_with_convention(quaternion, EulerConvention::default())
+        }
+
+        pub fn from_quaternion_with_convention(
+            quaternion: UnitQuaternion<f32>,
+            convention: EulerConvention,
+        ) -> Self {
+            Orientation::new(
+                EulerAngles::from_quaternion(quaternion, convention),
+                quaternion,
+            )
+        }
+
+        pub fn identity() -> Self {
+            Orientation::from_quaternion(UnitQuaternion::identity())
+        }
+
+        pub fn rotation_matrix(&self) -> RotationMatrix {
+            RotationMatrix::from_quaternion(self.quaternion)
+        }
+
+        pub fn axis_angle(&self) -> AxisAngle {
+            AxisAngle::from_quaternion(self.quaternion)
+        }
+
+        pub fn rotation_vector(&self) -> RotationVector {
+            RotationVector::from_quaternion(self.quaternion)
+        }
+    }
+
+    impl Default for Orientation {
+        fn default() -> Self {
+            Orientation::identity()
+        }
+    }
+
+    // Direction cosine matrix that maps body-frame vectors into the reference frame (v_ref = R * v_body).
+    // Its columns are the body axes expressed in the reference frame.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct RotationMatrix(pub Matrix3<f32>);
+
+    impl RotationMatrix {
+        pub fn from_quaternion(quaternion: UnitQuaternion<f32>) -> Self {
+            RotationMatrix(*quaternion.to_rotation_matrix().matrix())
+        }
+
+        pub fn to_quaternion(&self) -> UnitQuaternion<f32> {
+            canonical_quaternion(UnitQuaternion::from_rotation_matrix(
+                &Rotation3::from_matrix_unchecked(self.0),
+            ))
+        }
+
+        // Row-major, matching how the matrix is written down on paper
+        pub fn rows(&self) -> [[f32; 3]; 3] {
+            let m = &self.0;
+            [
+                [m[(0, 0)], m[(0, 1)], m[(0, 2)]],
+                [m[(1, 0)], m[(1, 1)], m[(1, 2)]],
+                [m[(2, 0)], m[(2, 1)], m[(2, 2)]],
+            ]
+        }
+    }
+
+    // A right-handed rotation of `angle` radians about the unit `axis`.
+    // The angle is always in [0, π]; the identity is reported as a zero rotation about x.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct AxisAngle {
+        pub axis: Unit<Vector3<f32>>,
+        pub angle: f32,
+    }
+
+    impl AxisAngle {
+        pub fn from_quaternion(quaternion: UnitQuaternion<f32>) -> Self {
+            match canonical_quaternion(quaternion).axis_angle() {
+                Some((axis, angle)) => AxisAngle { axis, angle },
+                None => AxisAngle {
+                    axis: Vector3::x_axis(),
+                    angle: 0.0,
+                },
+            }
+        }
+
+        pub fn to_quaternion(&self) -> UnitQuaternion<f32> {
+            canonical_quaternion(UnitQuaternion::from_axis_angle(&self.axis, self.angle))
+        }
+    }
+
+    // The axis scaled by the angle (the logarithm map of the rotation), with norm in [0, π]
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct RotationVector(pub Vector3<f32>);
+
+    impl RotationVector {
+        pub fn from_quaternion(quaternion: UnitQuaternion<f32>) -> Self {
+            RotationVector(canonical_quaternion(quaternion).scaled_axis())
+        }
+
+        pub fn to_quaternion(&self) -> UnitQuaternion<f32> {
+            canonical_quaternion(UnitQuaternion::from_scaled_axis(self.0))
+        }
+    }
+
+    pub fn quaternion_from_euler_angles(euler: EulerAngles) -> UnitQuaternion<f32> {
+        euler.to_quaternion()
+    }
+
+    // q and -q describe the same rotation; we always emit the one with a non-negative scalar part
+    pub fn canonical_quaternion(quaternion: UnitQuaternion<f32>) -> UnitQuaternion<f32> {
+        if quaternion.w < 0.0 {
+            let q = quaternion.into_inner();
+            UnitQuaternion::new_unchecked(Quaternion::new(-q.w, -q.i, -q.j, -q.k))
+        } else {
+            quaternion
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::orientation::*;
+    use nalgebra::{Matrix3, Quaternion, UnitQuaternion, Vector3};
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    #[test]
+    fn test_identity_orientation() {
+        let orientation = Orientation::identity();
+        assert_eq!(orientation.euler, EulerAngles::default());
+        assert_eq!(orientation.quaternion, UnitQuaternion::identity());
+    }
+
+    #[test]
+    fn test_quaternion_from_euler_angles_single_axis() {
+        // A quarter turn about z is (cos 45°, 0, 0, sin 45°)
+        let quaternion = quaternion_from_euler_angles(EulerAngles::new(0.0, 0.0, FRAC_PI_2));
+        let expected = std::f32::consts::FRAC_1_SQRT_2;
+        assert!((quaternion.w - expected).abs() < 1e-6);
+        assert!(quaternion.i.abs() < 1e-6);
+        assert!(quaternion.j.abs() < 1e-6);
+        assert!((quaternion.k - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_orientation_round_trip() {
+        let euler = EulerAngles::new(0.3, -0.4, 1.2);
+        let orientation = Orientation::from_euler_angles(euler);
+        let recovered = Orientation::from_quaternion(orientation.quaternion);
+        for i in 0..3 {
+            assert!((recovered.euler.angles[i] - euler.angles[i]).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_canonical_quaternion() {
+        let negative = UnitQuaternion::new_unchecked(Quaternion::new(-0.5, 0.5, -0.5, 0.5));
+        let canonical = canonical_quaternion(negative);
+        assert_eq!(
+            canonical.into_inner(),
+            Quaternion::new(0.5, -0.5, 0.5, -0.5)
+        );
+        // Both forms rotate vectors identically
+        let v = Vector3::new(1.0, 2.0, 3.0);
+        assert!((negative * v - canonical * v).norm() < 1e-6);
+    }
+
+    #[test]
+    fn test_rotation_matrix() {
+        // A quarter turn about z takes the body x axis onto the reference y axis
+        let orientation = Orientation::from_euler_angles(EulerAngles::new(0.0, 0.0, FRAC_PI_2));
+        let matrix = orientation.rotation_matrix();
+        let expected = Matrix3::new(0.0, -1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0);
+        assert!((matrix.0 - expected).norm() < 1e-6);
+        assert!((matrix.rows()[0][1] + 1.0).abs() < 1e-6);
+        assert!(matrix.to_quaternion().angle_to(&orientation.quaternion) < 1e-6);
+    }
+
+    #[test]
+    fn test_axis_angle() {
+        let orientation = Orientation::from_euler_angles(EulerAngles::new(0.0, -FRAC_PI_2, 0.0));
+        let axis_angle = orientation.axis_angle();
+        assert!((axis_angle.angle - FRAC_PI_2).abs() < 1e-6);
+        assert!((axis_angle.axis.into_inner() + Vector3::y()).norm() < 1e-6);
+        assert!(axis_angle.to_quaternion().angle_to(&orientation.quaternion) < 1e-6);
+    }
+
+    #[test]
+    fn test_axis_angle_identity() {
+        let axis_angle = Orientation::identity().axis_angle();
+        assert_eq!(axis_angle.angle, 0.0);
+        assert_eq!(axis_angle.axis, Vector3::x_axis());
+    }
+
+    #[test]
+    fn test_rotation_vector() {
+        let orientation = Orientation::from_euler_angles(EulerAngles::new(0.5, 0.0, 0.0));
+        let rotation_vector = orientation.rotation_vector();
+        assert!((rotation_vector.0 - Vector3::new(0.5, 0.0, 0.0)).norm() < 1e-6);
+        assert!(
+            rotation_vector
+                .to_quaternion()
+                .angle_to(&orientation.quaternion)
+                < 1e-6
+        );
+        assert_eq!(
+            Orientation::identity().rotation_vector().0,
+            Vector3::zeros()
+        );
+    }
+
+    #[test]
+    fn test_rotation_vector_norm_is_at_most_pi() {
+        // 270° about x is the same as 90° the other way
+        let quaternion = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), 1.5 * PI);
+        let rotation_vector = RotationVector::from_quaternion(quaternion);
+        assert!((rotation_vector.0 - Vector3::new(-FRAC_PI_2, 0.0, 0.0)).norm() < 1e-5);
+    }
+
+    fn all_conventions() -> Vec<EulerConvention> {
+        EulerSequence::ALL
+            .into_iter()
+            .flat_map(|sequence| {
+                [
+                    EulerConvention::intrinsic(sequence),
+                    EulerConvention::extrinsic(sequence),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_default_convention_matches_nalgebra() {
+        // The default must keep producing what Rotation3::from_euler_angles always did
+        let (roll, pitch, yaw) = (0.3, -0.4, 1.2);
+        let expected = UnitQuaternion::from_euler_angles(roll, pitch, yaw);
+        let quaternion = EulerConvention::default().to_quaternion([roll, pitch, yaw]);
+        assert!(quaternion.angle_to(&expected) < 1e-6);
+
+        let angles = EulerConvention::default().from_quaternion(expected);
+        let (r, p, y) = expected.euler_angles();
+        assert!((angles[0] - r).abs() < 1e-5);
+        assert!((angles[1] - p).abs() < 1e-5);
+        assert!((angles[2] - y).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_intrinsic_is_reversed_extrinsic() {
+        let angles = [0.1, 0.2, 0.3];
+        let intrinsic = EulerConvention::intrinsic(EulerSequence::ZYX).to_quaternion(angles);
+        let extrinsic =
+            EulerConvention::extrinsic(EulerSequence::XYZ).to_quaternion([0.3, 0.2, 0.1]);
+        assert!(intrinsic.angle_to(&extrinsic) < 1e-6);
+    }
+
+    #[test]
+    fn test_all_conventions_round_trip() {
+        let samples: [[f32; 3]; 4] = [
+            [0.1, 0.2, 0.3],
+            [-2.5, 1.0, 3.0],
+            [3.0, -1.2, -0.7],
+            [0.7, 1.4, -2.9],
+        ];
+        for convention in all_conventions() {
+            for angles in samples {
+                let mut angles = angles;
+                if convention.sequence.is_proper_euler() {
+                    // The middle angle of a proper Euler sequence is in [0, π]
+                    angles[1] = angles[1].abs();
+                }
+                let quaternion = convention.to_quaternion(angles);
+                let recovered = convention.from_quaternion(quaternion);
+                for i in 0..3 {
+                    assert!(
+                        (recovered[i] - angles[i]).abs() < 1e-4,
+                        "{} {:?} -> {:?}",
+                        convention,
+                        angles,
+                        recovered
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_all_conventions_gimbal_lock_preserves_rotation() {
+        for convention in all_conventions() {
+            let middles = if convention.sequence.is_proper_euler() {
+                [0.0, PI]
+            } else {
+                [FRAC_PI_2, -FRAC_PI_2]
+            };
+            for middle in middles {
+                let quaternion = convention.to_quaternion([0.4, middle, -0.9]);
+                let recovered = convention.to_quaternion(convention.from_quaternion(quaternion));
+                assert!(
+                    recovered.angle_to(&quaternion) < 1e-3,
+                    "{} at {}",
+                    convention,
+                    middle
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_convert_between_conventions() {
+        let vendor = EulerAngles::with_convention([0.5, -0.2, 0.1], EulerConvention::AEROSPACE);
+        let zxz = vendor.convert(EulerConvention::intrinsic(EulerSequence::ZXZ));
+        let back = zxz.convert(EulerConvention::AEROSPACE);
+        assert!(vendor.to_quaternion().angle_to(&zxz.to_quaternion()) < 1e-6);
+        for i in 0..3 {
+            assert!((back.angles[i] - vendor.angles[i]).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_convention_parse_and_display() {
+        assert_eq!(
+            "ZYX".parse::<EulerConvention>().unwrap(),
+            EulerConvention::AEROSPACE
+        );
+        assert_eq!(
+            "xyz".parse::<EulerConvention>().unwrap(),
+            EulerConvention::ROLL_PITCH_YAW
+        );
+        assert_eq!(
+            EulerConvention::intrinsic(EulerSequence::ZXZ).to_string(),
+            "ZXZ"
+        );
+        assert_eq!(
+            EulerConvention::extrinsic(EulerSequence::ZXZ).to_string(),
+            "zxz"
+        );
+        assert!("Zyx".parse::<EulerConvention>().is_err());
+        assert!("XXY".parse::<EulerConvention>().is_err());
+        for convention in all_conventions() {
+            assert_eq!(
+                convention.to_string().parse::<EulerConvention>().unwrap(),
+                convention
+            );
+        }
+    }
+
+    #[test]
+    fn test_convention_labels() {
+        assert_eq!(
+            EulerConvention::ROLL_PITCH_YAW.labels(),
+            ["roll", "pitch", "yaw"]
+        );
+        assert_eq!(
+            EulerConvention::AEROSPACE.labels(),
+            ["yaw", "pitch", "roll"]
+        );
+        assert_eq!(
+            EulerConvention::intrinsic(EulerSequence::ZXZ).labels(),
+            ["alpha", "beta", "gamma"]
+        );
+    }
+
+    #[test]
+    fn test_singularity_flagged_within_threshold() {
+        let convention = EulerConvention::AEROSPACE;
+        let config = SingularityConfig {
+            threshold: 0.01,
+            strategy: SingularityStrategy::ZeroThird,
+        };
+
+        let near = convention.to_quaternion([0.3, FRAC_PI_2 - 0.005, 0.2]);
+        let extracted = convention.extract(near, &config, None);
+        assert!(extracted.near_singularity);
+        assert_eq!(extracted.angles[2], 0.0);
+        assert!((extracted.angles[1] - (FRAC_PI_2 - 0.005)).abs() < 1e-4);
+        // The combined rotation is reproduced to within the threshold
+        assert!(extracted.to_quaternion().angle_to(&near) < 0.01);
+
+        let far = convention.to_quaternion([0.3, FRAC_PI_2 - 0.1, 0.2]);
+        let extracted = convention.extract(far, &config, None);
+        assert!(!extracted.near_singularity);
+        assert!((extracted.angles[2] - 0.2).abs() < 1e-4);
+
+        // Angles taken as they are only flag gimbal lock itself, not the configured threshold
+        let angles = [0.3, FRAC_PI_2 - 0.005, 0.2];
+        assert!(!EulerAngles::with_convention(angles, convention).near_singularity);
+        assert!(EulerAngles::with_convention([0.3, FRAC_PI_2, 0.2], convention).near_singularity);
+    }
+
+    #[test]
+    fn test_singularity_hold_third() {
+        let config = SingularityConfig {
+            threshold: 0.01,
+            strategy: SingularityStrategy::HoldThird,
+        };
+        for convention in all_conventions() {
+            let middle = if convention.sequence.is_proper_euler() {
+                PI
+            } else {
+                -FRAC_PI_2
+            };
+            let quaternion = convention.to_quaternion([0.4, middle, -0.9]);
+            let extracted = convention.extract(quaternion, &config, Some(-0.9));
+            assert!(extracted.near_singularity, "{}", convention);
+            assert!((extracted.angles[2] + 0.9).abs() < 1e-4, "{}", convention);
+            assert!(
+                extracted.to_quaternion().angle_to(&quaternion) < 1e-3,
+                "{}",
+                convention
+            );
+        }
+    }
+
+    #[test]
+    fn test_singularity_distance() {
+        let aerospace = EulerConvention::AEROSPACE;
+        assert!((aerospace.singularity_distance(FRAC_PI_2) - 0.0).abs() < 1e-6);
+        assert!((aerospace.singularity_distance(-1.0) - (FRAC_PI_2 - 1.0)).abs() < 1e-6);
+        let zxz = EulerConvention::intrinsic(EulerSequence::ZXZ);
+        assert!((zxz.singularity_distance(0.2) - 0.2).abs() < 1e-6);
+        assert!((zxz.singularity_distance(PI - 0.3) - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_wrap_to_pi() {
+        use std::f64::consts::PI;
+        assert_eq!(wrap_to_pi(1.0), 1.0);
+        assert_eq!(wrap_to_pi(PI), PI);
+        assert!((wrap_to_pi(3.0 * PI + 0.5) - (-PI + 0.5)).abs() < 1e-12);
+        assert!((wrap_to_pi(-5.0 * PI - 0.5) - (PI - 0.5)).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,1267 @@
+pub mod output {
+
+    use nalgebra::Matrix3;
+    use std::fmt;
+    use std::io::{self, Write};
+
+    use crate::healths::health::HealthEvent;
+    use crate::resamplings::resampling::Resampled;
+    use crate::samples::sample::Sample;
+    use crate::sensors::sensor::SensorId;
+
+    // The forms in which a sink can emit each sample's orientation
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Representation {
+        Euler,
+        // Unwrapped Euler angles and wrap counts; empty unless the unwrapping stage is enabled
+        ContinuousEuler,
+        Quaternion,
+        RotationMatrix,
+        AxisAngle,
+        RotationVector,
+        // Source timestamp, its time base, and host receive time
+        Timestamp,
+        // ID of the sensor the sample came from
+        Sensor,
+        // Sensors that contributed to, or were rejected from, a fused sample; empty otherwise
+        Fusion,
+        // Attitude error covariance (rad²); empty unless the estimator tracks one
+        Covariance,
+        // Body-frame angular velocity and Euler angle rates (rad/s); empty unless rate estimation
+        // is enabled and the stream has moved on from its first sample
+        AngularVelocity,
+        // Whether the sample was measured or interpolated, and from which measured samples; empty
+        // unless the resampling stage is enabled
+        Resampling,
+        // Epoch, clock offset (s) and drift against the reference clock, and the sensors the epoch
+        // was closed without; empty unless the synchronisation stage is enabled
+        Synchronisation,
+        // World and body axes the orientation is expressed in
+        Frames,
+    }
+
+    // Destination for reconstructed samples; runs on the display thread
+    pub trait Sink: Send {
+        fn emit(&mut self, sample: &Sample) -> io::Result<()>;
+
+        // A change of a sensor's health; sinks that only hold samples ignore it
+        fn health(&mut self, _event: &HealthEvent) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn finish(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    // A sink together with the sensors whose samples it receives
+    pub struct Route {
+        // `None` routes every sensor to the sink
+        pub sensors: Option<Vec<SensorId>>,
+        pub sink: Box<dyn Sink>,
+    }
+
+    impl Route {
+        pub fn all(sink: Box<dyn Sink>) -> Self {
+            Route {
+                sensors: None,
+                sink,
+            }
+        }
+
+        pub fn only(sensors: Vec<SensorId>, sink: Box<dyn Sink>) -> Self {
+            Route {
+                sensors: Some(sensors),
+                sink,
+            }
+        }
+
+        pub fn accepts(&self, sensor: SensorId) -> bool {
+            match &self.sensors {
+                None => true,
+                Some(sensors) => sensors.contains(&sensor),
+            }
+        }
+    }
+
+    // Human-readable lines, one per representation
+    pub fn format_text(sample: &Sample, representations: &[Representation]) -> Vec<String> {
+        representations
+            .iter()
+            .filter_map(|representation| format_representation(sample, *representation))
+            .collect()
+    }
+
+    // One human-readable line for a change of a sensor's health
+    pub fn format_health_event(event: &HealthEvent) -> String {
+        let line = format!(
+            "Sensor {} went from {} to {} at {:#?} s ({})",
+            event.sensor,
+            event.from,
+            event.to,
+            event.timestamp.source.as_secs_f64(),
+            event.timestamp.base
+        );
+        if event.faults.is_empty() {
+            format!("{}.", line)
+        } else {
+            let faults: Vec<String> = event.faults.iter().map(|f| f.to_string()).collect();
+            format!("{}: {}.", line, faults.join(", "))
+        }
+    }
+
+    // `None` for a representation the sample has nothing for, e.g. covariance from a filter
+    // that does not track one
+    fn format_representation(sample: &Sample, representation: Representation) -> Option<String> {
+        let line = match representation {
+            Representation::Euler => {
+                let euler = sample.orientation.euler;
+                let [first, second, third] = euler.labels();
+                let line = format!(
+                    "Observed Euler angles of {:#?} ({}), {:#?} ({}), and {:#?} ({}).",
+                    euler.angles[0], first, euler.angles[1], second, euler.angles[2], third
+                );
+                if euler.near_singularity {
+                    format!(
+                        "{} Near gimbal lock, so {} and {} are not individually reliable.",
+                        line, first, third
+                    )
+                } else {
+                    line
+                }
+            }
+            Representation::Quaternion => {
+                let q = sample.orientation.quaternion;
+                format!(
+                    "Observed quaternion of {:#?} (w), {:#?} (x), {:#?} (y), and {:#?} (z).",
+                    q.w, q.i, q.j, q.k
+                )
+            }
+            Representation::RotationMatrix => {
+                let rows = sample.orientation.rotation_matrix().rows();
+                format!("Observed rotation matrix of {:?}.", rows)
+            }
+            Representation::AxisAngle => {
+                let axis_angle = sample.orientation.axis_angle();
+                let axis = axis_angle.axis;
+                format!(
+                    "Observed rotation of {:#?} (angle) about [{:#?}, {:#?}, {:#?}] (axis).",
+                    axis_angle.angle, axis.x, axis.y, axis.z
+                )
+            }
+            Representation::RotationVector => {
+                let v = sample.orientation.rotation_vector().0;
+                format!(
+                    "Observed rotation vector of [{:#?}, {:#?}, {:#?}].",
+                    v.x, v.y, v.z
+                )
+            }
+            Representation::Timestamp => {
+                let timestamp = sample.timestamp;
+                format!(
+                    "Stamped at {:.6} s ({} time) and received at {:.6} s since the Unix epoch.",
+                    timestamp.source.as_secs_f64(),
+                    timestamp.base,
+                    timestamp.received.as_secs_f64()
+                )
+            }
+            Representation::Sensor => format!("From sensor {}.", sample.sensor),
+            Representation::Frames => format!(
+                "Expressed along {} world axes and {} body axes.",
+                sample.frames.world, sample.frames.body
+            ),
+            Representation::ContinuousEuler => {
+                let unwrapped = sample.unwrapped?;
+                let [first, second, third] = sample.orientation.euler.labels();
+                format!(
+                    "Continuous Euler angles of {:#?} ({}), {:#?} ({}), and {:#?} ({}) after {:?} wraps.",
+                    unwrapped.angles[0],
+                    first,
+                    unwrapped.angles[1],
+                    second,
+                    unwrapped.angles[2],
+                    third,
+                    unwrapped.wraps
+                )
+            }
+            Representation::Fusion => {
+                let fusion = sample.fusion.as_ref()?;
+                format!(
+                    "Fused from sensors {}; rejected {}.",
+                    id_list(&fusion.contributors, ", "),
+                    if fusion.rejected.is_empty() {
+                        "none".to_string()
+                    } else {
+                        id_list(&fusion.rejected, ", ")
+                    }
+                )
+            }
+            Representation::Covariance => {
+                let rows = sample.covariance?;
+                format!(
+                    "Attitude error covariance of {:?} (rad²).",
+                    covariance_rows(&rows)
+                )
+            }
+            Representation::AngularVelocity => {
+                let velocity = sample.angular_velocity?;
+                let body = velocity.body;
+                let line = format!(
+                    "Angular velocity of [{:#?}, {:#?}, {:#?}] rad/s about the body axes.",
+                    body.x, body.y, body.z
+                );
+                match velocity.euler_rates {
+                    Some(rates) => {
+                        let [first, second, third] = sample.orientation.euler.labels();
+                        format!(
+                            "{} Euler rates of {:#?} ({}), {:#?} ({}), and {:#?} ({}) rad/s.",
+                            line, rates[0], first, rates[1], second, rates[2], third
+                        )
+                    }
+                    None => format!("{} Euler rates are undefined at gimbal lock.", line),
+                }
+            }
+            Representation::Resampling => match sample.resampled? {
+                Resampled::Measured { sequence } => {
+                    format!("Resampled from measured sample {}.", sequence)
+                }
+                Resampled::Interpolated { from, to, fraction } => format!(
+                    "Interpolated {:#?} of the way from sample {} to sample {}.",
+                    fraction, from, to
+                ),
+            },
+            Representation::Synchronisation => {
+                let info = sample.synchronisation.as_ref()?;
+                format!(
+                    "Epoch {} on a clock offset by {} s with a drift of {}; missing sensors {}.",
+                    info.epoch,
+                    info.clock.offset,
+                    info.clock.drift,
+                    if info.missing.is_empty() {
+                        "none".to_string()
+                    } else {
+                        id_list(&info.missing, ", ")
+                    }
+                )
+            }
+        };
+        Some(line)
+    }
+
+    // Prints to standard output, which is what the simulation has always done
+    pub struct ConsoleSink {
+        representations: Vec<Representation>,
+    }
+
+    impl ConsoleSink {
+        pub fn new(representations: Vec<Representation>) -> Self {
+            ConsoleSink { representations }
+        }
+    }
+
+    impl Default for ConsoleSink {
+        fn default() -> Self {
+            ConsoleSink::new(vec![Representation::Euler])
+        }
+    }
+
+    impl Sink for ConsoleSink {
+        fn emit(&mut self, sample: &Sample) -> io::Result<()> {
+            for line in format_text(sample, &self.representations) {
+                println!("{}", line);
+            }
+            Ok(())
+        }
+
+        fn health(&mut self, event: &HealthEvent) -> io::Result<()> {
+            println!("{}", format_health_event(event));
+            Ok(())
+        }
+    }
+
+    // The same text as the console sink, written to any writer
+    pub struct TextSink<W: Write + Send> {
+        writer: W,
+        representations: Vec<Representation>,
+    }
+
+    impl<W: Write + Send> TextSink<W> {
+        pub fn new(writer: W, representations: Vec<Representation>) -> Self {
+            TextSink {
+                writer,
+                representations,
+            }
+        }
+
+        pub fn into_inner(self) -> W {
+            self.writer
+        }
+    }
+
+    impl<W: Write + Send> Sink for TextSink<W> {
+        fn emit(&mut self, sample: &Sample) -> io::Result<()> {
+            for line in format_text(sample, &self.representations) {
+                writeln!(self.writer, "{}", line)?;
+            }
+            Ok(())
+        }
+
+        fn health(&mut self, event: &HealthEvent) -> io::Result<()> {
+            writeln!(self.writer, "{}", format_health_event(event))
+        }
+
+        fn finish(&mut self) -> io::Result<()> {
+            self.writer.flush()
+        }
+    }
+
+    // Comma-separated values with a header row naming the selected columns. Health events do not
+    // fit the columns, so they are written as `#` comment rows between the samples.
+    pub struct CsvSink<W: Write + Send> {
+        writer: W,
+        representations: Vec<Representation>,
+        header_written: bool,
+    }
+
+    impl<W: Write + Send> CsvSink<W> {
+        pub fn new(writer: W, representations: Vec<Representation>) -> Self {
+            CsvSink {
+                writer,
+                representations,
+                header_written: false,
+            }
+        }
+
+        pub fn into_inner(self) -> W {
+            self.writer
+        }
+
+        fn header(&self, sample: &Sample) -> Vec<&'static str> {
+            let mut columns = vec!["sequence"];
+            for representation in &self.representations {
+                match representation {
+                    Representation::Euler => {
+                        columns.extend(sample.orientation.euler.labels());
+                        columns.push("near_singularity");
+                    }
+                    Representation::ContinuousEuler => {
+                        columns.extend(sample.orientation.euler.labels().map(continuous_column));
+                        columns.extend(sample.orientation.euler.labels().map(wraps_column));
+                    }
+                    Representation::Quaternion => columns.extend(["qw", "qx", "qy", "qz"]),
+                    Representation::RotationMatrix => columns.extend([
+                        "r11", "r12", "r13", "r21", "r22", "r23", "r31", "r32", "r33",
+                    ]),
+                    Representation::AxisAngle => {
+                        columns.extend(["axis_x", "axis_y", "axis_z", "angle"])
+                    }
+                    Representation::RotationVector => columns.extend(["rv_x", "rv_y", "rv_z"]),
+                    Representation::Timestamp => {
+                        columns.extend(["source_time", "time_base", "received_time"])
+                    }
+                    Representation::Sensor => columns.push("sensor"),
+                    Representation::Fusion => columns.extend(["contributors", "rejected"]),
+                    // The matrix is symmetric, so only its upper triangle is written
+                    Representation::Covariance => {
+                        columns.extend(["cov_xx", "cov_xy", "cov_xz", "cov_yy", "cov_yz", "cov_zz"])
+                    }
+                    Representation::AngularVelocity => {
+                        columns.extend(["omega_x", "omega_y", "omega_z"]);
+                        columns.extend(sample.orientation.euler.labels().map(rate_column));
+                    }
+                    // A measured sample is written as running from itself to itself
+                    Representation::Resampling => {
+                        columns.extend(["origin", "from", "to", "fraction"])
+                    }
+                    Representation::Synchronisation => {
+                        columns.extend(["epoch", "clock_offset", "clock_drift", "missing"])
+                    }
+                    Representation::Frames => columns.extend(["world_frame", "body_frame"]),
+                }
+            }
+            columns
+        }
+    }
+
+    impl<W: Write + Send> Sink for CsvSink<W> {
+        fn emit(&mut self, sample: &Sample) -> io::Result<()> {
+            if !self.header_written {
+                writeln!(self.writer, "{}", self.header(sample).join(","))?;
+                self.header_written = true;
+            }
+
+            let mut fields = vec![sample.sequence.to_string()];
+            for representation in &self.representations {
+                match representation {
+                    Representation::Euler => {
+                        let euler = sample.orientation.euler;
+                        fields.extend(euler.angles.map(|v| v.to_string()));
+                        fields.push(euler.near_singularity.to_string());
+                    }
+                    Representation::ContinuousEuler => match sample.unwrapped {
+                        Some(unwrapped) => {
+                            fields.extend(unwrapped.angles.map(|v| v.to_string()));
+                            fields.extend(unwrapped.wraps.map(|v| v.to_string()));
+                        }
+                        None => fields.extend(std::iter::repeat_n(String::new(), 6)),
+                    },
+                    Representation::Quaternion => {
+                        let q = sample.orientation.quaternion;
+                        fields.extend([q.w, q.i, q.j, q.k].map(|v| v.to_string()));
+                    }
+                    Representation::RotationMatrix => {
+                        let rows = sample.orientation.rotation_matrix().rows();
+                        fields.extend(rows.iter().flatten().map(|v| v.to_string()));
+                    }
+                    Representation::AxisAngle => {
+                        let axis_angle = sample.orientation.axis_angle();
+                        let axis = axis_angle.axis;
+                        fields.extend(
+                            [axis.x, axis.y, axis.z, axis_angle.angle].map(|v| v.to_string()),
+                        );
+                    }
+                    Representation::RotationVector => {
+                        let v = sample.orientation.rotation_vector().0;
+                        fields.extend([v.x, v.y, v.z].map(|v| v.to_string()));
+                    }
+                    Representation::Timestamp => {
+                        let timestamp = sample.timestamp;
+                        fields.extend([
+                            timestamp.source.as_secs_f64().to_string(),
+                            timestamp.base.to_string(),
+                            timestamp.received.as_secs_f64().to_string(),
+                        ]);
+                    }
+                    Representation::Sensor => fields.push(sample.sensor.to_string()),
+                    // IDs are space-separated so that each list stays in one column
+                    Representation::Fusion => match &sample.fusion {
+                        Some(fusion) => {
+                            fields.push(id_list(&fusion.contributors, " "));
+                            fields.push(id_list(&fusion.rejected, " "));
+                        }
+                        None => fields.extend([String::new(), String::new()]),
+                    },
+                    Representation::Covariance => match sample.covariance {
+                        Some(covariance) => fields.extend(
+                            [(0, 0), (0, 1), (0, 2), (1, 1), (1, 2), (2, 2)]
+                                .map(|index| covariance[index].to_string()),
+                        ),
+                        None => fields.extend(std::iter::repeat_n(String::new(), 6)),
+                    },
+                    Representation::AngularVelocity => {
+                        let velocity = sample.angular_velocity;
+                        match velocity {
+                            Some(velocity) => {
+                                let body = velocity.body;
+                                fields.extend([body.x, body.y, body.z].map(|v| v.to_string()))
+                            }
+                            None => fields.extend(std::iter::repeat_n(String::new(), 3)),
+                        }
+                        match velocity.and_then(|velocity| velocity.euler_rates) {
+                            Some(rates) => fields.extend(rates.map(|v| v.to_string())),
+                            None => fields.extend(std::iter::repeat_n(String::new(), 3)),
+                        }
+                    }
+                    Representation::Resampling => match sample.resampled {
+                        Some(Resampled::Measured { sequence }) => fields.extend([
+                            "measured".to_string(),
+                            sequence.to_string(),
+                            sequence.to_string(),
+                            "0".to_string(),
+                        ]),
+                        Some(Resampled::Interpolated { from, to, fraction }) => fields.extend([
+                            "interpolated".to_string(),
+                            from.to_string(),
+                            to.to_string(),
+                            fraction.to_string(),
+                        ]),
+                        None => fields.extend(std::iter::repeat_n(String::new(), 4)),
+                    },
+                    Representation::Synchronisation => match &sample.synchronisation {
+                        Some(info) => fields.extend([
+                            info.epoch.to_string(),
+                            info.clock.offset.to_string(),
+                            info.clock.drift.to_string(),
+                            id_list(&info.missing, " "),
+                        ]),
+                        None => fields.extend(std::iter::repeat_n(String::new(), 4)),
+                    },
+                    Representation::Frames => fields.extend([
+                        sample.frames.world.to_string(),
+                        sample.frames.body.to_string(),
+                    ]),
+                }
+            }
+            writeln!(self.writer, "{}", fields.join(","))
+        }
+
+        fn health(&mut self, event: &HealthEvent) -> io::Result<()> {
+            writeln!(self.writer, "# {}", format_health_event(event))
+        }
+
+        fn finish(&mut self) -> io::Result<()> {
+            self.writer.flush()
+        }
+    }
+
+    // One JSON object per line; health events are objects with `"event":"health"`
+    pub struct JsonSink<W: Write + Send> {
+        writer: W,
+        representations: Vec<Representation>,
+    }
+
+    impl<W: Write + Send> JsonSink<W> {
+        pub fn new(writer: W, representations: Vec<Representation>) -> Self {
+            JsonSink {
+                writer,
+                representations,
+            }
+        }
+
+        pub fn into_inner(self) -> W {
+            self.writer
+        }
+    }
+
+    impl<W: Write + Send> Sink for JsonSink<W> {
+        fn emit(&mut self, sample: &Sample) -> io::Result<()> {
+            let mut fields = vec![format!("\"sequence\":{}", sample.sequence)];
+            for representation in &self.representations {
+                match representation {
+                    Representation::Euler => {
+                        let euler = sample.orientation.euler;
+                        let [first, second, third] = euler.labels();
+                        fields.push(format!(
+                            "\"euler\":{{\"convention\":\"{}\",\"{}\":{},\"{}\":{},\"{}\":{},\"near_singularity\":{}}}",
+                            euler.convention,
+                            first,
+                            json_number(euler.angles[0]),
+                            second,
+                            json_number(euler.angles[1]),
+                            third,
+                            json_number(euler.angles[2]),
+                            euler.near_singularity
+                        ));
+                    }
+                    Representation::ContinuousEuler => {
+                        if let Some(unwrapped) = sample.unwrapped {
+                            let angles: Vec<String> = unwrapped
+                                .angles
+                                .iter()
+                                .map(|v| json_number(*v as f32))
+                                .collect();
+                            let wraps: Vec<String> =
+                                unwrapped.wraps.iter().map(|v| v.to_string()).collect();
+                            fields.push(format!(
+                                "\"continuous_euler\":{{\"angles\":[{}],\"wraps\":[{}]}}",
+                                angles.join(","),
+                                wraps.join(",")
+                            ));
+                        }
+                    }
+                    Representation::Quaternion => {
+                        let q = sample.orientation.quaternion;
+                        fields.push(format!(
+                            "\"quaternion\":{{\"w\":{},\"x\":{},\"y\":{},\"z\":{}}}",
+                            json_number(q.w),
+                            json_number(q.i),
+                            json_number(q.j),
+                            json_number(q.k)
+                        ));
+                    }
+                    Representation::RotationMatrix => {
+                        let rows = sample.orientation.rotation_matrix().rows();
+                        let rows: Vec<String> = rows.iter().map(|row| json_array(row)).collect();
+                        fields.push(format!("\"rotation_matrix\":[{}]", rows.join(",")));
+                    }
+                    Representation::AxisAngle => {
+                        let axis_angle = sample.orientation.axis_angle();
+                        let axis = axis_angle.axis;
+                        fields.push(format!(
+                            "\"axis_angle\":{{\"axis\":{},\"angle\":{}}}",
+                            json_array(&[axis.x, axis.y, axis.z]),
+                            json_number(axis_angle.angle)
+                        ));
+                    }
+                    Representation::RotationVector => {
+                        let v = sample.orientation.rotation_vector().0;
+                        fields.push(format!(
+                            "\"rotation_vector\":{}",
+                            json_array(&[v.x, v.y, v.z])
+                        ));
+                    }
+                    Representation::Timestamp => {
+                        // Seconds as f64, since f32 cannot resolve wall-clock time
+                        let timestamp = sample.timestamp;
+                        fields.push(format!(
+                            "\"timestamp\":{{\"source\":{},\"base\":\"{}\",\"received\":{}}}",
+                            timestamp.source.as_secs_f64(),
+                            timestamp.base,
+                            timestamp.received.as_secs_f64()
+                        ));
+                    }
+                    Representation::Sensor => fields.push(format!("\"sensor\":{}", sample.sensor)),
+                    Representation::Fusion => {
+                        if let Some(fusion) = &sample.fusion {
+                            fields.push(format!(
+                                "\"fusion\":{{\"contributors\":[{}],\"rejected\":[{}]}}",
+                                id_list(&fusion.contributors, ","),
+                                id_list(&fusion.rejected, ",")
+                            ));
+                        }
+                    }
+                    Representation::Covariance => {
+                        if let Some(covariance) = sample.covariance {
+                            let rows: Vec<String> = covariance_rows(&covariance)
+                                .iter()
+                                .map(|row| json_array(row))
+                                .collect();
+                            fields.push(format!("\"covariance\":[{}]", rows.join(",")));
+                        }
+                    }
+                    Representation::AngularVelocity => {
+                        if let Some(velocity) = sample.angular_velocity {
+                            let body = velocity.body;
+                            let euler_rates = match velocity.euler_rates {
+                                Some(rates) => {
+                                    let [first, second, third] = sample.orientation.euler.labels();
+                                    format!(
+                                        "{{\"{}\":{},\"{}\":{},\"{}\":{}}}",
+                                        first,
+                                        json_number(rates[0]),
+                                        second,
+                                        json_number(rates[1]),
+                                        third,
+                                        json_number(rates[2])
+                                    )
+                                }
+                                None => "null".to_string(),
+                            };
+                            fields.push(format!(
+                                "\"angular_velocity\":{{\"body\":{},\"euler_rates\":{}}}",
+                                json_array(&[body.x, body.y, body.z]),
+                                euler_rates
+                            ));
+                        }
+                    }
+                    Representation::Resampling => match sample.resampled {
+                        Some(Resampled::Measured { sequence }) => fields.push(format!(
+                            "\"resampled\":{{\"origin\":\"measured\",\"sequence\":{}}}",
+                            sequence
+                        )),
+                        Some(Resampled::Interpolated { from, to, fraction }) => {
+                            fields.push(format!(
+                                "\"resampled\":{{\"origin\":\"interpolated\",\"from\":{},\"to\":{},\"fraction\":{}}}",
+                                from,
+                                to,
+                                json_number(fraction)
+                            ))
+                        }
+                        None => {}
+                    },
+                    // The clock fit keeps offset and drift finite
+                    Representation::Synchronisation => {
+                        if let Some(info) = &sample.synchronisation {
+                            fields.push(format!(
+                                "\"synchronisation\":{{\"epoch\":{},\"clock_offset\":{},\"clock_drift\":{},\"missing\":[{}]}}",
+                                info.epoch,
+                                json_number(info.clock.offset),
+                                json_number(info.clock.drift),
+                                id_list(&info.missing, ",")
+                            ));
+                        }
+                    }
+                    Representation::Frames => fields.push(format!(
+                        "\"frames\":{{\"world\":\"{}\",\"body\":\"{}\"}}",
+                        sample.frames.world, sample.frames.body
+                    )),
+                }
+            }
+            writeln!(self.writer, "{{{}}}", fields.join(","))
+        }
+
+        fn health(&mut self, event: &HealthEvent) -> io::Result<()> {
+            let faults: Vec<String> = event.faults.iter().map(|f| format!("\"{}\"", f)).collect();
+            writeln!(
+                self.writer,
+                "{{\"event\":\"health\",\"sensor\":{},\"time\":{},\"from\":\"{}\",\"to\":\"{}\",\"faults\":[{}]}}",
+                event.sensor,
+                event.timestamp.source.as_secs_f64(),
+                event.from,
+                event.to,
+                faults.join(",")
+            )
+        }
+
+        fn finish(&mut self) -> io::Result<()> {
+            self.writer.flush()
+        }
+    }
+
+    fn id_list(sensors: &[SensorId], separator: &str) -> String {
+        let ids: Vec<String> = sensors.iter().map(|sensor| sensor.to_string()).collect();
+        ids.join(separator)
+    }
+
+    fn covariance_rows(covariance: &Matrix3<f32>) -> [[f32; 3]; 3] {
+        [0, 1, 2].map(|row| [0, 1, 2].map(|column| covariance[(row, column)]))
+    }
+
+    fn rate_column(label: &'static str) -> &'static str {
+        match label {
+            "roll" => "roll_rate",
+            "pitch" => "pitch_rate",
+            "yaw" => "yaw_rate",
+            "alpha" => "alpha_rate",
+            "beta" => "beta_rate",
+            _ => "gamma_rate",
+        }
+    }
+
+    fn continuous_column(label: &'static str) -> &'static str {
+        match label {
+            "roll" => "roll_continuous",
+            "pitch" => "pitch_continuous",
+            "yaw" => "yaw_continuous",
+            "alpha" => "alpha_continuous",
+            "beta" => "beta_continuous",
+            _ => "gamma_continuous",
+        }
+    }
+
+    fn wraps_column(label: &'static str) -> &'static str {
+        match label {
+            "roll" => "roll_wraps",
+            "pitch" => "pitch_wraps",
+            "yaw" => "yaw_wraps",
+            "alpha" => "alpha_wraps",
+            "beta" => "beta_wraps",
+            _ => "gamma_wraps",
+        }
+    }
+
+    // JSON has no NaN or infinity, so those become null. Generic so that f64 fields keep their
+    // precision
+    pub fn json_number<T: Copy + fmt::Display + Into<f64>>(value: T) -> String {
+        if value.into().is_finite() {
+            value.to_string()
+        } else {
+            "null".to_string()
+        }
+    }
+
+    pub fn json_array(values: &[f32]) -> String {
+        let values: Vec<String> = values.iter().map(|v| json_number(*v)).collect();
+        format!("[{}]", values.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::output::*;
+    use crate::angles::angle::UnwrappedAngles;
+    use crate::orientations::orientation::{
+        EulerAngles, EulerConvention, EulerSequence, Orientation,
+    };
+    use crate::samples::sample::{Sample, TimeBase, Timestamp};
+    use crate::sensors::sensor::SensorId;
+    use std::time::Duration;
+
+    fn sample() -> Sample {
+        Sample::new(
+            7,
+            Orientation::from_euler_angles(EulerAngles::new(0.0, 0.0, 0.0)),
+        )
+    }
+
+    #[test]
+    fn test_format_text_euler() {
+        let lines = format_text(&sample(), &[Representation::Euler]);
+        assert_eq!(
+            lines,
+            vec!["Observed Euler angles of 0.0 (roll), 0.0 (pitch), and 0.0 (yaw)."]
+        );
+    }
+
+    #[test]
+    fn test_format_text_quaternion() {
+        let lines = format_text(&sample(), &[Representation::Quaternion]);
+        assert_eq!(
+            lines,
+            vec!["Observed quaternion of 1.0 (w), 0.0 (x), 0.0 (y), and 0.0 (z)."]
+        );
+    }
+
+    #[test]
+    fn test_text_sink() {
+        let mut sink = TextSink::new(
+            Vec::new(),
+            vec![Representation::Euler, Representation::Quaternion],
+        );
+        sink.emit(&sample()).unwrap();
+        sink.finish().unwrap();
+        let text = String::from_utf8(sink.into_inner()).unwrap();
+        assert_eq!(text.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_csv_sink() {
+        let mut sink = CsvSink::new(
+            Vec::new(),
+            vec![Representation::Euler, Representation::Quaternion],
+        );
+        sink.emit(&sample()).unwrap();
+        sink.emit(&sample()).unwrap();
+        let text = String::from_utf8(sink.into_inner()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines[0],
+            "sequence,roll,pitch,yaw,near_singularity,qw,qx,qy,qz"
+        );
+        assert_eq!(lines[1], "7,0,0,0,false,1,0,0,0");
+        assert_eq!(lines.len(), 3);
+    }
+
+    #[test]
+    fn test_json_sink() {
+        let mut sink = JsonSink::new(Vec::new(), vec![Representation::Quaternion]);
+        sink.emit(&sample()).unwrap();
+        let text = String::from_utf8(sink.into_inner()).unwrap();
+        assert_eq!(
+            text,
+            "{\"sequence\":7,\"quaternion\":{\"w\":1,\"x\":0,\"y\":0,\"z\":0}}\n"
+        );
+    }
+
+    #[test]
+    fn test_json_number_non_finite() {
+        assert_eq!(json_number(f32::NAN), "null");
+        assert_eq!(json_number(f32::INFINITY), "null");
+        assert_eq!(json_number(-0.5), "-0.5");
+    }
+
+    #[test]
+    fn test_csv_sink_rotation_forms() {
+        let mut sink = CsvSink::new(
+            Vec::new(),
+            vec![
+                Representation::RotationMatrix,
+                Representation::AxisAngle,
+                Representation::RotationVector,
+            ],
+        );
+        sink.emit(&sample()).unwrap();
+        let text = String::from_utf8(sink.into_inner()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines[0],
+            "sequence,r11,r12,r13,r21,r22,r23,r31,r32,r33,axis_x,axis_y,axis_z,angle,rv_x,rv_y,rv_z"
+        );
+        assert_eq!(lines[1], "7,1,0,0,0,1,0,0,0,1,1,0,0,0,0,0,0");
+    }
+
+    #[test]
+    fn test_json_sink_rotation_forms() {
+        let mut sink = JsonSink::new(
+            Vec::new(),
+            vec![
+                Representation::RotationMatrix,
+                Representation::AxisAngle,
+                Representation::RotationVector,
+            ],
+        );
+        sink.emit(&sample()).unwrap();
+        let text = String::from_utf8(sink.into_inner()).unwrap();
+        assert_eq!(
+            text,
+            "{\"sequence\":7,\"rotation_matrix\":[[1,0,0],[0,1,0],[0,0,1]],\
+             \"axis_angle\":{\"axis\":[1,0,0],\"angle\":0},\"rotation_vector\":[0,0,0]}\n"
+        );
+    }
+
+    #[test]
+    fn test_format_text_rotation_forms() {
+        let lines = format_text(
+            &sample(),
+            &[
+                Representation::RotationMatrix,
+                Representation::AxisAngle,
+                Representation::RotationVector,
+            ],
+        );
+        assert_eq!(
+            lines,
+            vec![
+                "Observed rotation matrix of [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]].",
+                "Observed rotation of 0.0 (angle) about [1.0, 0.0, 0.0] (axis).",
+                "Observed rotation vector of [0.0, 0.0, 0.0].",
+            ]
+        );
+    }
+
+    // A change to the test sample, and what each kind of sink makes of the changed sample: its
+    // text lines, and the CSV and JSON a fresh sink writes for it
+    type SinkCase = (
+        fn(&mut Sample),
+        &'static [&'static str],
+        &'static str,
+        &'static str,
+    );
+
+    fn check_sinks(representation: Representation, cases: &[SinkCase]) {
+        for (change, lines, csv, json) in cases {
+            let mut sample = sample();
+            change(&mut sample);
+            assert_eq!(
+                format_text(&sample, &[representation]),
+                *lines,
+                "{:?}",
+                representation
+            );
+            let mut sink = CsvSink::new(Vec::new(), vec![representation]);
+            sink.emit(&sample).unwrap();
+            assert_eq!(String::from_utf8(sink.into_inner()).unwrap(), *csv);
+            let mut sink = JsonSink::new(Vec::new(), vec![representation]);
+            sink.emit(&sample).unwrap();
+            assert_eq!(String::from_utf8(sink.into_inner()).unwrap(), *json);
+        }
+    }
+
+    #[test]
+    fn test_sinks_label_euler_angles_by_convention() {
+        check_sinks(
+            Representation::Euler,
+            &[
+                // The identity is exactly a gimbal-lock configuration of a proper Euler sequence
+                (
+                    |sample| {
+                        sample.orientation = Orientation::from_quaternion_with_convention(
+                            EulerAngles::new(0.0, 0.0, 0.0).to_quaternion(),
+                            EulerConvention::intrinsic(EulerSequence::ZXZ),
+                        )
+                    },
+                    &["Observed Euler angles of 0.0 (alpha), 0.0 (beta), and 0.0 (gamma). \
+                       Near gimbal lock, so alpha and gamma are not individually reliable."],
+                    "sequence,alpha,beta,gamma,near_singularity\n7,0,0,0,true\n",
+                    "{\"sequence\":7,\"euler\":{\"convention\":\"ZXZ\",\"alpha\":0,\"beta\":0,\"gamma\":0,\
+                     \"near_singularity\":true}}\n",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_sinks_continuous_euler() {
+        check_sinks(
+            Representation::ContinuousEuler,
+            &[
+                (
+                    |sample| {
+                        sample.unwrapped = Some(UnwrappedAngles {
+                            angles: [0.0, 0.0, 7.5],
+                            wraps: [0, 0, 1],
+                        })
+                    },
+                    &["Continuous Euler angles of 0.0 (roll), 0.0 (pitch), and 7.5 (yaw) after [0, 0, 1] wraps."],
+                    "sequence,roll_continuous,pitch_continuous,yaw_continuous,roll_wraps,pitch_wraps,yaw_wraps\n\
+                     7,0,0,7.5,0,0,1\n",
+                    "{\"sequence\":7,\"continuous_euler\":{\"angles\":[0,0,7.5],\"wraps\":[0,0,1]}}\n",
+                ),
+                // Without the unwrapping stage there is nothing to print, but CSV keeps its columns
+                // aligned
+                (
+                    |_| {},
+                    &[],
+                    "sequence,roll_continuous,pitch_continuous,yaw_continuous,roll_wraps,pitch_wraps,yaw_wraps\n\
+                     7,,,,,,\n",
+                    "{\"sequence\":7}\n",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_sinks_timestamp() {
+        check_sinks(
+            Representation::Timestamp,
+            &[
+                (
+                    |sample| {
+                        sample.timestamp = Timestamp {
+                            source: Duration::from_millis(1500),
+                            base: TimeBase::Sensor,
+                            received: Duration::from_secs(1_700_000_000),
+                        }
+                    },
+                    &["Stamped at 1.500000 s (sensor time) and received at 1700000000.000000 s since the Unix epoch."],
+                    "sequence,source_time,time_base,received_time\n7,1.5,sensor,1700000000\n",
+                    "{\"sequence\":7,\"timestamp\":{\"source\":1.5,\"base\":\"sensor\",\"received\":1700000000}}\n",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_sinks_sensor_and_routes() {
+        check_sinks(
+            Representation::Sensor,
+            &[(
+                |sample| sample.sensor = SensorId(3),
+                &["From sensor 3."],
+                "sequence,sensor\n7,3\n",
+                "{\"sequence\":7,\"sensor\":3}\n",
+            )],
+        );
+
+        let route = Route::only(
+            vec![SensorId(1), SensorId(3)],
+            Box::new(ConsoleSink::default()),
+        );
+        assert!(route.accepts(SensorId(3)));
+        assert!(!route.accepts(SensorId(2)));
+        assert!(Route::all(Box::new(ConsoleSink::default())).accepts(SensorId(2)));
+    }
+
+    #[test]
+    fn test_sinks_fusion() {
+        use crate::fusions::fusion::FusionInfo;
+
+        check_sinks(
+            Representation::Fusion,
+            &[
+                (
+                    |sample| {
+                        sample.fusion = Some(FusionInfo {
+                            contributors: vec![SensorId(1), SensorId(2)],
+                            rejected: vec![SensorId(3)],
+                        })
+                    },
+                    &["Fused from sensors 1, 2; rejected 3."],
+                    "sequence,contributors,rejected\n7,1 2,3\n",
+                    "{\"sequence\":7,\"fusion\":{\"contributors\":[1,2],\"rejected\":[3]}}\n",
+                ),
+                (
+                    |_| {},
+                    &[],
+                    "sequence,contributors,rejected\n7,,\n",
+                    "{\"sequence\":7}\n",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_sinks_covariance() {
+        use nalgebra::Matrix3;
+
+        check_sinks(
+            Representation::Covariance,
+            &[
+                (
+                    |sample| {
+                        sample.covariance =
+                            Some(Matrix3::new(0.5, 0.25, 0.0, 0.25, 1.0, 0.0, 0.0, 0.0, 2.0))
+                    },
+                    &["Attitude error covariance of [[0.5, 0.25, 0.0], [0.25, 1.0, 0.0], [0.0, 0.0, 2.0]] (rad²)."],
+                    "sequence,cov_xx,cov_xy,cov_xz,cov_yy,cov_yz,cov_zz\n7,0.5,0.25,0,1,0,2\n",
+                    "{\"sequence\":7,\"covariance\":[[0.5,0.25,0],[0.25,1,0],[0,0,2]]}\n",
+                ),
+                (
+                    |_| {},
+                    &[],
+                    "sequence,cov_xx,cov_xy,cov_xz,cov_yy,cov_yz,cov_zz\n7,,,,,,\n",
+                    "{\"sequence\":7}\n",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_sinks_angular_velocity() {
+        use crate::velocities::velocity::AngularVelocity;
+        use nalgebra::Vector3;
+
+        check_sinks(
+            Representation::AngularVelocity,
+            &[
+                (
+                    |sample| {
+                        sample.angular_velocity = Some(AngularVelocity {
+                            body: Vector3::new(0.0, 0.5, 1.0),
+                            euler_rates: Some([0.0, 0.5, 1.0]),
+                        })
+                    },
+                    &["Angular velocity of [0.0, 0.5, 1.0] rad/s about the body axes. Euler rates of 0.0 (roll), 0.5 (pitch), and 1.0 (yaw) rad/s."],
+                    "sequence,omega_x,omega_y,omega_z,roll_rate,pitch_rate,yaw_rate\n7,0,0.5,1,0,0.5,1\n",
+                    "{\"sequence\":7,\"angular_velocity\":{\"body\":[0,0.5,1],\"euler_rates\":{\"roll\":0,\"pitch\":0.5,\"yaw\":1}}}\n",
+                ),
+                // At gimbal lock only the body rates are known
+                (
+                    |sample| {
+                        sample.angular_velocity = Some(AngularVelocity {
+                            body: Vector3::new(0.0, 0.5, 1.0),
+                            euler_rates: None,
+                        })
+                    },
+                    &["Angular velocity of [0.0, 0.5, 1.0] rad/s about the body axes. Euler rates are undefined at gimbal lock."],
+                    "sequence,omega_x,omega_y,omega_z,roll_rate,pitch_rate,yaw_rate\n7,0,0.5,1,,,\n",
+                    "{\"sequence\":7,\"angular_velocity\":{\"body\":[0,0.5,1],\"euler_rates\":null}}\n",
+                ),
+                (
+                    |_| {},
+                    &[],
+                    "sequence,omega_x,omega_y,omega_z,roll_rate,pitch_rate,yaw_rate\n7,,,,,,\n",
+                    "{\"sequence\":7}\n",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_sinks_resampling() {
+        use crate::resamplings::resampling::Resampled;
+
+        check_sinks(
+            Representation::Resampling,
+            &[
+                (
+                    |sample| sample.resampled = Some(Resampled::Measured { sequence: 3 }),
+                    &["Resampled from measured sample 3."],
+                    "sequence,origin,from,to,fraction\n7,measured,3,3,0\n",
+                    "{\"sequence\":7,\"resampled\":{\"origin\":\"measured\",\"sequence\":3}}\n",
+                ),
+                (
+                    |sample| {
+                        sample.resampled = Some(Resampled::Interpolated {
+                            from: 3,
+                            to: 4,
+                            fraction: 0.25,
+                        })
+                    },
+                    &["Interpolated 0.25 of the way from sample 3 to sample 4."],
+                    "sequence,origin,from,to,fraction\n7,interpolated,3,4,0.25\n",
+                    "{\"sequence\":7,\"resampled\":{\"origin\":\"interpolated\",\"from\":3,\"to\":4,\"fraction\":0.25}}\n",
+                ),
+                (
+                    |_| {},
+                    &[],
+                    "sequence,origin,from,to,fraction\n7,,,,\n",
+                    "{\"sequence\":7}\n",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_sinks_synchronisation() {
+        use crate::synchronisations::synchronisation::{ClockEstimate, SyncInfo};
+
+        check_sinks(
+            Representation::Synchronisation,
+            &[
+                (
+                    |sample| {
+                        sample.synchronisation = Some(SyncInfo {
+                            epoch: 12,
+                            clock: ClockEstimate {
+                                offset: -3.5,
+                                drift: 0.0002,
+                            },
+                            missing: vec![SensorId(2), SensorId(4)],
+                        })
+                    },
+                    &["Epoch 12 on a clock offset by -3.5 s with a drift of 0.0002; missing sensors 2, 4."],
+                    "sequence,epoch,clock_offset,clock_drift,missing\n7,12,-3.5,0.0002,2 4\n",
+                    "{\"sequence\":7,\"synchronisation\":{\"epoch\":12,\"clock_offset\":-3.5,\"clock_drift\":0.0002,\"missing\":[2,4]}}\n",
+                ),
+                // A degenerate clock fit is null in JSON
+                (
+                    |sample| {
+                        sample.synchronisation = Some(SyncInfo {
+                            epoch: 0,
+                            clock: ClockEstimate {
+                                offset: f64::NAN,
+                                drift: f64::INFINITY,
+                            },
+                            missing: Vec::new(),
+                        })
+                    },
+                    &["Epoch 0 on a clock offset by NaN s with a drift of inf; missing sensors none."],
+                    "sequence,epoch,clock_offset,clock_drift,missing\n7,0,NaN,inf,\n",
+                    "{\"sequence\":7,\"synchronisation\":{\"epoch\":0,\"clock_offset\":null,\"clock_drift\":null,\"missing\":[]}}\n",
+                ),
+                (
+                    |_| {},
+                    &[],
+                    "sequence,epoch,clock_offset,clock_drift,missing\n7,,,,\n",
+                    "{\"sequence\":7}\n",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_sinks_frames() {
+        use crate::coordinates::coordinate::Frames;
+
+        check_sinks(
+            Representation::Frames,
+            &[(
+                |sample| sample.frames = Frames::NED_FRD,
+                &["Expressed along ned world axes and frd body axes."],
+                "sequence,world_frame,body_frame\n7,ned,frd\n",
+                "{\"sequence\":7,\"frames\":{\"world\":\"ned\",\"body\":\"frd\"}}\n",
+            )],
+        );
+    }
+
+    #[test]
+    fn test_sinks_health_events() {
+        use crate::healths::health::{Fault, HealthEvent, HealthState};
+        use crate::samples::sample::{TimeBase, Timestamp};
+        use crate::sensors::sensor::SensorId;
+        use std::time::Duration;
+
+        let event = HealthEvent {
+            sensor: SensorId(3),
+            timestamp: Timestamp {
+                source: Duration::from_millis(1500),
+                base: TimeBase::Sensor,
+                received: Duration::ZERO,
+            },
+            from: HealthState::Ok,
+            to: HealthState::Degraded,
+            faults: vec![Fault::Stuck, Fault::Noise],
+        };
+        let mut text = TextSink::new(Vec::new(), vec![Representation::Euler]);
+        text.health(&event).unwrap();
+        let recovered = HealthEvent {
+            from: HealthState::Failed,
+            to: HealthState::Ok,
+            faults: Vec::new(),
+            ..event.clone()
+        };
+        text.health(&recovered).unwrap();
+        assert_eq!(
+            String::from_utf8(text.into_inner()).unwrap(),
+            "Sensor 3 went from ok to degraded at 1.5 s (sensor): stuck, noise.\nSensor 3 went from failed to ok at 1.5 s (sensor).\n"
+        );
+
+        let mut json = JsonSink::new(Vec::new(), vec![Representation::Euler]);
+        json.health(&event).unwrap();
+        assert_eq!(
+            String::from_utf8(json.into_inner()).unwrap(),
+            "{\"event\":\"health\",\"sensor\":3,\"time\":1.5,\"from\":\"ok\",\"to\":\"degraded\",\"faults\":[\"stuck\",\"noise\"]}\n"
+        );
+
+        // Health events are comments, so they do not break the columns
+        let mut csv = CsvSink::new(Vec::new(), vec![Representation::Sensor]);
+        csv.health(&event).unwrap();
+        csv.emit(&sample()).unwrap();
+        csv.health(&recovered).unwrap();
+        assert_eq!(
+            String::from_utf8(csv.into_inner()).unwrap(),
+            "# Sensor 3 went from ok to degraded at 1.5 s (sensor): stuck, noise.\nsequence,sensor\n7,0\n# Sensor 3 went from failed to ok at 1.5 s (sensor).\n"
+        );
+    }
+}
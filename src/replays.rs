@@ -0,0 +1,481 @@
+pub mod replay {
+
+    use std::sync::{Arc, Condvar, Mutex};
+    use std::time::{Duration, Instant};
+
+    use crate::inputs::input::{Source, TimedFrame};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Speed {
+        // Ignore timing and hand frames over as fast as the pipeline takes them
+        AsFastAsPossible,
+        // Multiple of real time, between MIN_SPEED and MAX_SPEED
+        Multiplier(f64),
+    }
+
+    pub const MIN_SPEED: f64 = 0.1;
+    pub const MAX_SPEED: f64 = 100.0;
+
+    impl Default for Speed {
+        fn default() -> Self {
+            Speed::Multiplier(1.0)
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub struct ReplayConfig {
+        pub speed: Speed,
+        // Hz; paces frames that were recorded without a timestamp. With neither, frames are not paced.
+        pub nominal_rate: Option<f64>,
+        // Start over from the first frame after the last one, forever
+        pub looping: bool,
+    }
+
+    // Where a replay reads the time and lets it pass
+    pub trait Clock: Send {
+        fn now(&self) -> Instant;
+
+        // Lets `duration` pass at once, for clocks that do not follow real time. The system clock
+        // returns false, and the replay waits in real time, waking early if paused or stopped.
+        fn skip(&self, _duration: Duration) -> bool {
+            false
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct SystemClock;
+
+    impl Clock for SystemClock {
+        fn now(&self) -> Instant {
+            Instant::now()
+        }
+    }
+
+    // A clock that only moves when told to, or when a replay waits on it; clones share the time
+    #[derive(Debug, Clone)]
+    pub struct ManualClock {
+        now: Arc<Mutex<Instant>>,
+    }
+
+    impl ManualClock {
+        pub fn new() -> Self {
+            ManualClock {
+                now: Arc::new(Mutex::new(Instant::now())),
+            }
+        }
+
+        pub fn advance(&self, duration: Duration) {
+            *self.now.lock().expect("Manual clock poisoned!") += duration;
+        }
+    }
+
+    impl Default for ManualClock {
+        fn default() -> Self {
+            ManualClock::new()
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now(&self) -> Instant {
+            *self.now.lock().expect("Manual clock poisoned!")
+        }
+
+        fn skip(&self, duration: Duration) -> bool {
+            self.advance(duration);
+            true
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct ControlFlags {
+        paused: bool,
+        stopped: bool,
+    }
+
+    // Handle for pausing, resuming or stopping a replay from another thread
+    #[derive(Debug, Clone, Default)]
+    pub struct ReplayControl {
+        state: Arc<(Mutex<ControlFlags>, Condvar)>,
+    }
+
+    impl ReplayControl {
+        pub fn pause(&self) {
+            self.update(|flags| flags.paused = true);
+        }
+
+        pub fn resume(&self) {
+            self.update(|flags| flags.paused = false);
+        }
+
+        // Ends the replay at the next frame; the only way to end a looping one
+        pub fn stop(&self) {
+            self.update(|flags| flags.stopped = true);
+        }
+
+        pub fn is_paused(&self) -> bool {
+            self.state
+                .0
+                .lock()
+                .expect("Replay control poisoned!")
+                .paused
+        }
+
+        fn update(&self, change: impl FnOnce(&mut ControlFlags)) {
+            let (lock, condvar) = &*self.state;
+            change(&mut lock.lock().expect("Replay control poisoned!"));
+            condvar.notify_all();
+        }
+    }
+
+    // Replays recorded frames, paced by their timestamps (or a nominal rate)
+    pub struct ReplaySource {
+        frames: Vec<TimedFrame>,
+        config: ReplayConfig,
+        control: ReplayControl,
+        clock: Box<dyn Clock>,
+        index: usize,
+        // Added to recorded times on each pass, so that looped timestamps keep increasing
+        loop_offset: Duration,
+        // Wall-clock instant at which the first frame went out, and that frame's stream time
+        anchor: Option<(Instant, Duration)>,
+    }
+
+    impl ReplaySource {
+        pub fn new(frames: Vec<TimedFrame>, config: ReplayConfig) -> Result<Self, &'static str> {
+            if let Speed::Multiplier(speed) = config.speed {
+                if !(MIN_SPEED..=MAX_SPEED).contains(&speed) {
+                    return Err("Replay speed must be between 0.1x and 100x.");
+                }
+            }
+            if let Some(rate) = config.nominal_rate {
+                if !(rate > 0.0 && rate.is_finite()) {
+                    return Err("Nominal sample rate must be positive.");
+                }
+            }
+            Ok(ReplaySource {
+                frames,
+                config,
+                control: ReplayControl::default(),
+                clock: Box::new(SystemClock),
+                index: 0,
+                loop_offset: Duration::ZERO,
+                anchor: None,
+            })
+        }
+
+        pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+            self.clock = Box::new(clock);
+            self
+        }
+
+        pub fn control(&self) -> ReplayControl {
+            self.control.clone()
+        }
+
+        // When the frame at `index` was taken, relative to the start of one pass
+        fn stream_time(&self, index: usize) -> Option<Duration> {
+            match (self.frames[index].sensor_time, self.config.nominal_rate) {
+                (Some(time), _) => Some(time),
+                (None, Some(rate)) => Some(Duration::from_secs_f64(index as f64 / rate)),
+                (None, None) => None,
+            }
+        }
+
+        // Length of one pass, including the gap from the last frame back to the first
+        fn pass_duration(&self) -> Duration {
+            let last = self.frames.len() - 1;
+            // Recordings need not start at zero on the sensor's clock
+            let span = self
+                .stream_time(last)
+                .unwrap_or_default()
+                .saturating_sub(self.stream_time(0).unwrap_or_default());
+            let gap = match self.config.nominal_rate {
+                Some(rate) => Duration::from_secs_f64(1.0 / rate),
+                None if last > 0 => span / last as u32,
+                None => Duration::ZERO,
+            };
+            span + gap
+        }
+
+        // Blocks until the frame stamped `time` is due, or returns false if stopped meanwhile
+        fn wait_until(&mut self, time: Option<Duration>) -> bool {
+            let (lock, condvar) = &*self.control.state;
+            let mut flags = lock.lock().expect("Replay control poisoned!");
+            loop {
+                if flags.stopped {
+                    return false;
+                }
+                if flags.paused {
+                    let paused_at = self.clock.now();
+                    while flags.paused && !flags.stopped {
+                        flags = condvar.wait(flags).expect("Replay control poisoned!");
+                    }
+                    // Time spent paused does not count towards the schedule
+                    if let Some((instant, _)) = self.anchor.as_mut() {
+                        *instant += self.clock.now().saturating_duration_since(paused_at);
+                    }
+                    continue;
+                }
+
+                let (time, speed) = match (time, self.config.speed) {
+                    (Some(time), Speed::Multiplier(speed)) => (time, speed),
+                    _ => return true,
+                };
+                let clock = &self.clock;
+                let (instant, start) = *self.anchor.get_or_insert_with(|| (clock.now(), time));
+                let due = instant + time.saturating_sub(start).div_f64(speed);
+                let now = self.clock.now();
+                if now >= due {
+                    return true;
+                }
+                if self.clock.skip(due - now) {
+                    continue;
+                }
+                // Wake early if paused or stopped in the meantime
+                flags = condvar
+                    .wait_timeout(flags, due - now)
+                    .expect("Replay control poisoned!")
+                    .0;
+            }
+        }
+    }
+
+    impl Source for ReplaySource {
+        fn next_frame(&mut self) -> Option<Vec<u32>> {
+            self.next_timed_frame().map(|frame| frame.bits)
+        }
+
+        fn next_timed_frame(&mut self) -> Option<TimedFrame> {
+            if self.frames.is_empty() {
+                return None;
+            }
+            if self.index == self.frames.len() {
+                if !self.config.looping {
+                    return None;
+                }
+                self.loop_offset += self.pass_duration();
+                self.index = 0;
+            }
+
+            let time = self
+                .stream_time(self.index)
+                .map(|time| time + self.loop_offset);
+            if !self.wait_until(time) {
+                return None;
+            }
+            let frame = TimedFrame {
+                bits: self.frames[self.index].bits.clone(),
+                sensor_time: self.frames[self.index]
+                    .sensor_time
+                    .map(|time| time + self.loop_offset),
+                sensor: self.frames[self.index].sensor,
+                kind: self.frames[self.index].kind,
+            };
+            self.index += 1;
+            Some(frame)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::replay::*;
+    use crate::frames::frame::FrameKind;
+    use crate::inputs::input::{Source, TimedFrame};
+    use crate::sensors::sensor::SensorId;
+    use std::thread;
+    use std::time::Duration;
+
+    fn frames(count: usize, period_ms: u64) -> Vec<TimedFrame> {
+        (0..count)
+            .map(|i| TimedFrame {
+                bits: vec![i as u32 % 2; 32],
+                sensor_time: Some(Duration::from_millis(period_ms * i as u64)),
+                sensor: SensorId::default(),
+                kind: FrameKind::Attitude,
+            })
+            .collect()
+    }
+
+    fn drain(source: &mut ReplaySource) -> Vec<TimedFrame> {
+        std::iter::from_fn(|| source.next_timed_frame()).collect()
+    }
+
+    #[test]
+    fn test_replay_rejects_bad_speed() {
+        let config = ReplayConfig {
+            speed: Speed::Multiplier(500.0),
+            ..Default::default()
+        };
+        assert!(ReplaySource::new(frames(1, 10), config).is_err());
+        let config = ReplayConfig {
+            nominal_rate: Some(0.0),
+            ..Default::default()
+        };
+        assert!(ReplaySource::new(frames(1, 10), config).is_err());
+    }
+
+    // When each frame came out, on the replay's manual clock
+    fn schedule(source: ReplaySource, count: usize) -> Vec<Duration> {
+        let clock = ManualClock::new();
+        let start = clock.now();
+        let mut source = source.with_clock(clock.clone());
+        (0..count)
+            .map(|_| {
+                source.next_timed_frame().unwrap();
+                clock.now() - start
+            })
+            .collect()
+    }
+
+    fn millis(values: &[f64]) -> Vec<Duration> {
+        values
+            .iter()
+            .map(|ms| Duration::from_secs_f64(ms / 1000.0))
+            .collect()
+    }
+
+    fn assert_close(actual: Vec<Duration>, expected: Vec<Duration>) {
+        assert_eq!(actual.len(), expected.len());
+        for (actual, expected) in actual.iter().zip(&expected) {
+            assert!(
+                actual.abs_diff(*expected) < Duration::from_micros(1),
+                "{:?} != {:?}",
+                actual,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_replay_honours_timestamps_and_speed() {
+        // Five frames 20 ms apart span 80 ms of recording
+        let source = ReplaySource::new(frames(5, 20), ReplayConfig::default()).unwrap();
+        assert_close(schedule(source, 5), millis(&[0.0, 20.0, 40.0, 60.0, 80.0]));
+
+        let config = ReplayConfig {
+            speed: Speed::Multiplier(4.0),
+            ..Default::default()
+        };
+        let source = ReplaySource::new(frames(5, 20), config).unwrap();
+        assert_close(schedule(source, 5), millis(&[0.0, 5.0, 10.0, 15.0, 20.0]));
+
+        // A looped capture stamped from 100 s on goes straight into its next pass
+        let late: Vec<TimedFrame> = frames(3, 10)
+            .into_iter()
+            .map(|frame| TimedFrame {
+                sensor_time: frame
+                    .sensor_time
+                    .map(|time| time + Duration::from_secs(100)),
+                ..frame
+            })
+            .collect();
+        let config = ReplayConfig {
+            speed: Speed::Multiplier(100.0),
+            looping: true,
+            ..Default::default()
+        };
+        let source = ReplaySource::new(late, config).unwrap();
+        assert_close(schedule(source, 4), millis(&[0.0, 0.1, 0.2, 0.3]));
+    }
+
+    #[test]
+    fn test_replay_nominal_rate_and_as_fast_as_possible() {
+        let untimed: Vec<TimedFrame> = frames(4, 0)
+            .into_iter()
+            .map(|frame| TimedFrame {
+                sensor_time: None,
+                ..frame
+            })
+            .collect();
+        let config = ReplayConfig {
+            nominal_rate: Some(50.0),
+            ..Default::default()
+        };
+        let source = ReplaySource::new(untimed.clone(), config).unwrap();
+        assert_close(schedule(source, 4), millis(&[0.0, 20.0, 40.0, 60.0]));
+        let mut source = ReplaySource::new(untimed, config).unwrap();
+        assert!(drain(&mut source)
+            .iter()
+            .all(|frame| frame.sensor_time.is_none()));
+
+        let config = ReplayConfig {
+            speed: Speed::AsFastAsPossible,
+            ..Default::default()
+        };
+        let source = ReplaySource::new(frames(5, 1000), config).unwrap();
+        assert_eq!(schedule(source, 5), vec![Duration::ZERO; 5]);
+    }
+
+    #[test]
+    fn test_replay_looping() {
+        let config = ReplayConfig {
+            speed: Speed::AsFastAsPossible,
+            looping: true,
+            ..Default::default()
+        };
+        let mut source = ReplaySource::new(frames(3, 10), config).unwrap();
+        let times: Vec<Duration> = (0..7)
+            .map(|_| source.next_timed_frame().unwrap().sensor_time.unwrap())
+            .collect();
+        // Each pass lasts 30 ms: 20 ms of frames plus one more period
+        let expected: Vec<Duration> = [0, 10, 20, 30, 40, 50, 60]
+            .iter()
+            .map(|ms| Duration::from_millis(*ms))
+            .collect();
+        assert_eq!(times, expected);
+
+        source.control().stop();
+        assert!(source.next_timed_frame().is_none());
+
+        // A capture stamped from 100 s on loops after the same 30 ms
+        let late: Vec<TimedFrame> = frames(3, 10)
+            .into_iter()
+            .map(|frame| TimedFrame {
+                sensor_time: frame
+                    .sensor_time
+                    .map(|time| time + Duration::from_secs(100)),
+                ..frame
+            })
+            .collect();
+        let mut source = ReplaySource::new(late, config).unwrap();
+        let times: Vec<Duration> = (0..5)
+            .map(|_| source.next_timed_frame().unwrap().sensor_time.unwrap())
+            .collect();
+        let expected: Vec<Duration> = [0, 10, 20, 30, 40]
+            .iter()
+            .map(|ms| Duration::from_secs(100) + Duration::from_millis(*ms))
+            .collect();
+        assert_eq!(times, expected);
+    }
+
+    #[test]
+    fn test_replay_pause_and_resume() {
+        let clock = ManualClock::new();
+        let start = clock.now();
+        let mut source = ReplaySource::new(frames(3, 10), ReplayConfig::default())
+            .unwrap()
+            .with_clock(clock.clone());
+        let control = source.control();
+        control.pause();
+        assert!(control.is_paused());
+
+        // 60 ms pass on the clock before the replay is resumed
+        let resumer = {
+            let clock = clock.clone();
+            thread::spawn(move || {
+                clock.advance(Duration::from_millis(60));
+                control.resume();
+            })
+        };
+        let times: Vec<Duration> = (0..3)
+            .map(|_| {
+                source.next_timed_frame().unwrap();
+                clock.now() - start
+            })
+            .collect();
+        resumer.join().unwrap();
+        // Nothing comes out while paused, and the schedule starts once resumed
+        assert_close(times, millis(&[60.0, 70.0, 80.0]));
+    }
+}
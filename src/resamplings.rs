@@ -0,0 +1,239 @@
+pub mod resampling {
+
+    use std::time::Duration;
+
+    use crate::orientations::orientation::Orientation;
+    use crate::samples::sample::Sample;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct ResampleConfig {
+        // Output rate (Hz); output times are whole multiples of its period in source time
+        pub rate: f64,
+        // An output this close to a measured sample is that sample rather than an interpolation
+        pub tolerance: Duration,
+        // No outputs are made up across a gap between measured samples longer than this
+        pub max_gap: Option<Duration>,
+    }
+
+    // How a resampled sample was obtained
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Resampled {
+        // The measured sample with this sequence number, moved onto the output grid
+        Measured { sequence: u64 },
+        // Slerp `fraction` of the way from one measured sample to the next
+        Interpolated { from: u64, to: u64, fraction: f32 },
+    }
+
+    // Turns one stream of irregularly timed samples into a steady one. Each output's sequence
+    // number is its index on the output grid, so streams resampled at the same rate line up.
+    #[derive(Debug, Clone)]
+    pub struct Resampler {
+        pub config: ResampleConfig,
+        previous: Option<Sample>,
+        // Grid index of the next output
+        next_index: u64,
+    }
+
+    impl Resampler {
+        pub fn new(config: ResampleConfig) -> Result<Self, &'static str> {
+            if !(config.rate > 0.0 && config.rate.is_finite()) {
+                return Err("Resampling rate must be positive.");
+            }
+            if config.tolerance.as_secs_f64() * config.rate >= 0.5 {
+                return Err("Resampling tolerance must be under half the output period.");
+            }
+            Ok(Resampler {
+                config,
+                previous: None,
+                next_index: 0,
+            })
+        }
+
+        fn grid_time(&self, index: u64) -> Duration {
+            Duration::from_secs_f64(index as f64 / self.config.rate)
+        }
+
+        // Outputs due up to this sample's time (plus the tolerance), in order; samples that do not
+        // move time forward are dropped
+        pub fn push(&mut self, sample: Sample) -> Vec<Sample> {
+            let time = sample.timestamp.source;
+            let tolerance = self.config.tolerance;
+            let previous = match self.previous.take() {
+                Some(previous) if previous.timestamp.source >= time => {
+                    self.previous = Some(previous);
+                    return Vec::new();
+                }
+                Some(previous) => Some(previous),
+                None => {
+                    let start = time.saturating_sub(tolerance).as_secs_f64() * self.config.rate;
+                    self.next_index = start.ceil() as u64;
+                    None
+                }
+            };
+
+            let mut outputs = Vec::new();
+            loop {
+                let grid_time = self.grid_time(self.next_index);
+                if grid_time > time + tolerance {
+                    break;
+                }
+                let output = if grid_time.abs_diff(time) <= tolerance {
+                    Some(self.measured(&sample, grid_time))
+                } else {
+                    previous
+                        .as_ref()
+                        .and_then(|previous| self.interpolated(previous, &sample, grid_time))
+                };
+                outputs.extend(output);
+                self.next_index += 1;
+            }
+            self.previous = Some(sample);
+            outputs
+        }
+
+        fn measured(&self, sample: &Sample, grid_time: Duration) -> Sample {
+            let mut output = sample.clone();
+            output.sequence = self.next_index;
+            output.timestamp.source = grid_time;
+            output.resampled = Some(Resampled::Measured {
+                sequence: sample.sequence,
+            });
+            output
+        }
+
+        fn interpolated(&self, from: &Sample, to: &Sample, grid_time: Duration) -> Option<Sample> {
+            let start = from.timestamp.source;
+            let span = to.timestamp.source - start;
+            if self.config.max_gap.is_some_and(|max_gap| span > max_gap) {
+                return None;
+            }
+            let fraction = ((grid_time - start).as_secs_f64() / span.as_secs_f64()) as f32;
+            // Everything but the orientation comes from the nearer neighbour
+            let mut output = if fraction < 0.5 { from } else { to }.clone();
+            let quaternion = from
+                .orientation
+                .quaternion
+                .slerp(&to.orientation.quaternion, fraction);
+            output.orientation = Orientation::from_quaternion(quaternion);
+            output.sequence = self.next_index;
+            output.timestamp.source = grid_time;
+            output.timestamp.received = to.timestamp.received;
+            output.resampled = Some(Resampled::Interpolated {
+                from: from.sequence,
+                to: to.sequence,
+                fraction,
+            });
+            Some(output)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resampling::*;
+    use crate::orientations::orientation::Orientation;
+    use crate::samples::sample::Sample;
+    use nalgebra::{UnitQuaternion, Vector3};
+    use std::time::Duration;
+
+    fn sample(sequence: u64, millis: u64, yaw: f32) -> Sample {
+        let quaternion = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), yaw);
+        let mut sample = Sample::new(sequence, Orientation::from_quaternion(quaternion));
+        sample.timestamp.source = Duration::from_millis(millis);
+        sample
+    }
+
+    fn config(rate: f64) -> ResampleConfig {
+        ResampleConfig {
+            rate,
+            tolerance: Duration::from_millis(1),
+            max_gap: None,
+        }
+    }
+
+    #[test]
+    fn test_resampler_rejects_bad_config() {
+        assert!(Resampler::new(config(0.0)).is_err());
+        let wide = ResampleConfig {
+            tolerance: Duration::from_millis(10),
+            ..config(100.0)
+        };
+        assert!(Resampler::new(wide).is_err());
+    }
+
+    #[test]
+    fn test_resampler_upsamples_jittery_stream() {
+        // About 30 Hz with jitter, resampled to 100 Hz
+        let mut resampler = Resampler::new(config(100.0)).unwrap();
+        let inputs = [(0, 0.0), (33, 0.33), (64, 0.64), (98, 0.98)];
+        let outputs: Vec<Sample> = inputs
+            .iter()
+            .enumerate()
+            .flat_map(|(i, (millis, yaw))| resampler.push(sample(i as u64, *millis, *yaw)))
+            .collect();
+
+        // Grid points at 0, 10, ..., 90 ms; 100 ms is beyond the last measured sample
+        assert_eq!(outputs.len(), 10);
+        for (index, output) in outputs.iter().enumerate() {
+            assert_eq!(output.sequence, index as u64);
+            assert_eq!(
+                output.timestamp.source,
+                Duration::from_millis(10 * index as u64)
+            );
+            // The yaw grows linearly, so slerp recovers it exactly
+            let yaw = output.orientation.euler.angles[2];
+            assert!(
+                (yaw - 0.1 * index as f32).abs() < 1e-5,
+                "{} at {}",
+                yaw,
+                index
+            );
+        }
+        assert_eq!(
+            outputs[0].resampled,
+            Some(Resampled::Measured { sequence: 0 })
+        );
+        match outputs[4].resampled {
+            Some(Resampled::Interpolated { from, to, fraction }) => {
+                assert_eq!((from, to), (1, 2));
+                assert!((fraction - 7.0 / 31.0).abs() < 1e-5);
+            }
+            other => panic!("expected an interpolated sample, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_resampler_decimates_and_snaps_to_measured() {
+        // 200 Hz down to 50 Hz: every fourth sample lands on the grid, within the tolerance
+        let mut resampler = Resampler::new(config(50.0)).unwrap();
+        let mut outputs = Vec::new();
+        for i in 0..20u64 {
+            let jitter = if i % 2 == 0 { 0 } else { 1 };
+            outputs.extend(resampler.push(sample(i, 5 * i + jitter, 0.0)));
+        }
+        let origins: Vec<Option<Resampled>> = outputs.iter().map(|s| s.resampled).collect();
+        assert_eq!(
+            origins,
+            [0, 4, 8, 12, 16]
+                .map(|sequence| Some(Resampled::Measured { sequence }))
+                .to_vec()
+        );
+    }
+
+    #[test]
+    fn test_resampler_skips_long_gaps_and_stale_samples() {
+        let gapped = ResampleConfig {
+            max_gap: Some(Duration::from_millis(50)),
+            ..config(100.0)
+        };
+        let mut resampler = Resampler::new(gapped).unwrap();
+        assert_eq!(resampler.push(sample(0, 0, 0.0)).len(), 1);
+        // Nothing is made up across a 100 ms dropout, but the sample at its end still counts
+        let after_gap = resampler.push(sample(1, 100, 0.5));
+        assert_eq!(after_gap.len(), 1);
+        assert_eq!(after_gap[0].sequence, 10);
+        // A sample that does not move time forward yields nothing
+        assert!(resampler.push(sample(2, 100, 0.6)).is_empty());
+        assert_eq!(resampler.push(sample(3, 110, 0.6)).len(), 1);
+    }
+}
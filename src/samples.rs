@@ -0,0 +1,105 @@
+pub mod sample {
+
+    use nalgebra::{Matrix3, UnitQuaternion};
+    use std::fmt;
+    use std::time::Duration;
+
+    use crate::angles::angle::UnwrappedAngles;
+    use crate::conversions::conversion::FixedTriplet;
+    use crate::coordinates::coordinate::{Attitude, BodyFrame, Frames, WorldFrame};
+    use crate::fusions::fusion::FusionInfo;
+    use crate::orientations::orientation::Orientation;
+    use crate::resamplings::resampling::Resampled;
+    use crate::sensors::sensor::SensorId;
+    use crate::synchronisations::synchronisation::SyncInfo;
+    use crate::velocities::velocity::AngularVelocity;
+
+    // Clock the source timestamp was read from
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum TimeBase {
+        // Reported by the sensor (or the recording) with the frame
+        Sensor,
+        // Monotonic time since the start of the run at which the frame was taken from the source
+        #[default]
+        Arrival,
+        // The synchronisation stage's common clock, onto which each stream's own time is mapped
+        Reference,
+    }
+
+    impl fmt::Display for TimeBase {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                TimeBase::Sensor => f.write_str("sensor"),
+                TimeBase::Arrival => f.write_str("arrival"),
+                TimeBase::Reference => f.write_str("reference"),
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct Timestamp {
+        pub source: Duration,
+        pub base: TimeBase,
+        // Host wall-clock time at which the frame was received, since the Unix epoch
+        pub received: Duration,
+    }
+
+    // Message passed from the fusion thread to the display thread
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct FusedSample {
+        pub sensor: SensorId,
+        // Counts this sensor's samples only
+        pub sequence: u64,
+        pub timestamp: Timestamp,
+        pub angles: FixedTriplet,
+        pub quaternion: UnitQuaternion<f32>,
+        // Attitude error covariance (rad², sensor frame), from estimators that track one
+        pub covariance: Option<Matrix3<f32>>,
+    }
+
+    // A fully reconstructed sample, as handed to every sink
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Sample {
+        pub sensor: SensorId,
+        pub sequence: u64,
+        pub timestamp: Timestamp,
+        pub orientation: Orientation,
+        // World and body axes the orientation (and the covariance and angular velocity) are in
+        pub frames: Frames,
+        // Continuous Euler angles, when the unwrapping stage is enabled
+        pub unwrapped: Option<UnwrappedAngles>,
+        // Which sensors went into a fused sample; `None` for a single sensor's own samples
+        pub fusion: Option<FusionInfo>,
+        // Attitude error covariance (rad², about the body axes), from estimators that track one
+        pub covariance: Option<Matrix3<f32>>,
+        // Derived from this and the previous samples, when rate estimation is enabled
+        pub angular_velocity: Option<AngularVelocity>,
+        // Whether the sample was measured or interpolated, when the resampling stage is enabled
+        pub resampled: Option<Resampled>,
+        // Epoch and clock estimate, when the synchronisation stage is enabled
+        pub synchronisation: Option<SyncInfo>,
+    }
+
+    impl Sample {
+        pub fn new(sequence: u64, orientation: Orientation) -> Self {
+            Sample {
+                sensor: SensorId::default(),
+                sequence,
+                timestamp: Timestamp::default(),
+                orientation,
+                frames: Frames::default(),
+                unwrapped: None,
+                fusion: None,
+                covariance: None,
+                angular_velocity: None,
+                resampled: None,
+                synchronisation: None,
+            }
+        }
+
+        // The orientation in frames chosen at compile time, converted from the sample's own
+        pub fn attitude<W: WorldFrame, B: BodyFrame>(&self) -> Attitude<W, B> {
+            Attitude::from_frames(self.orientation.quaternion, self.frames)
+        }
+    }
+}
@@ -0,0 +1,138 @@
+pub mod sensor {
+
+    use crossbeam_channel::{bounded, Receiver};
+    use std::fmt;
+    use std::thread;
+
+    use crate::calibrations::calibration::Calibration;
+    use crate::coordinates::coordinate::Frames;
+    use crate::frames::frame::{FrameKind, FrameLayout};
+    use crate::healths::health::HealthState;
+    use crate::inputs::input::{Source, TimedFrame};
+    use crate::orientations::orientation::EulerConvention;
+
+    // Identifies one IMU on a rig; frames from an untagged source belong to sensor 0
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+    pub struct SensorId(pub u16);
+
+    impl fmt::Display for SensorId {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    // What the pipeline needs to know about one sensor
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub struct SensorConfig {
+        pub layout: FrameLayout,
+        // Layouts of the sensor's angular rate, accelerometer and magnetometer frames, if it sends any
+        pub rate_layout: FrameLayout,
+        pub acceleration_layout: FrameLayout,
+        pub magnetic_layout: FrameLayout,
+        // Convention of the angles this sensor sends
+        pub input_convention: EulerConvention,
+        pub calibration: Calibration,
+        // World and body axes of the attitudes this sensor sends
+        pub frames: Frames,
+    }
+
+    impl SensorConfig {
+        pub fn layout_for(&self, kind: FrameKind) -> FrameLayout {
+            match kind {
+                FrameKind::Attitude => self.layout,
+                FrameKind::AngularRate => self.rate_layout,
+                FrameKind::Acceleration => self.acceleration_layout,
+                FrameKind::MagneticField => self.magnetic_layout,
+            }
+        }
+    }
+
+    // Processing counts for one sensor
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct SensorStats {
+        pub samples: usize,
+        pub near_singularity: usize,
+        pub corrupted_frames: usize,
+        // Times this sensor was left out of a redundant fusion as an outlier
+        pub rejected: usize,
+        // Samples dropped by the synchronisation or redundant fusion stage because their epoch
+        // had already closed
+        pub late: usize,
+        // Health at the end of the run, when health monitoring is enabled
+        pub health: HealthState,
+    }
+
+    // Frames at most this far ahead of the pipeline are buffered per merged source
+    const MERGE_CAPACITY: usize = 1024;
+
+    // Reads several sources concurrently, tagging each frame with its source's sensor ID
+    pub struct MergedSource {
+        receiver: Receiver<TimedFrame>,
+    }
+
+    impl MergedSource {
+        pub fn new(sources: Vec<(SensorId, Box<dyn Source>)>) -> Self {
+            let (sender, receiver) = bounded(MERGE_CAPACITY);
+            for (sensor, mut source) in sources {
+                let sender = sender.clone();
+                // Readers stop at the end of their source, or once the merged source is dropped
+                thread::spawn(move || {
+                    while let Some(mut frame) = source.next_timed_frame() {
+                        frame.sensor = sensor;
+                        if sender.send(frame).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            MergedSource { receiver }
+        }
+    }
+
+    impl Source for MergedSource {
+        fn next_frame(&mut self) -> Option<Vec<u32>> {
+            self.next_timed_frame().map(|frame| frame.bits)
+        }
+
+        fn next_timed_frame(&mut self) -> Option<TimedFrame> {
+            self.receiver.recv().ok()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sensor::*;
+    use crate::inputs::input::Source;
+    use crossbeam_queue::SegQueue;
+
+    fn queue(frames: usize, bit: u32) -> Box<dyn Source> {
+        let queue = SegQueue::new();
+        for _ in 0..frames {
+            queue.push(vec![bit; 32]);
+        }
+        Box::new(queue)
+    }
+
+    #[test]
+    fn test_merged_source_tags_frames() {
+        let mut source =
+            MergedSource::new(vec![(SensorId(1), queue(3, 0)), (SensorId(2), queue(5, 1))]);
+        let frames: Vec<_> = std::iter::from_fn(|| source.next_timed_frame()).collect();
+        assert_eq!(frames.len(), 8);
+        for frame in &frames {
+            let expected = if frame.bits[0] == 0 {
+                SensorId(1)
+            } else {
+                SensorId(2)
+            };
+            assert_eq!(frame.sensor, expected);
+        }
+    }
+
+    #[test]
+    fn test_merged_source_empty() {
+        let mut source = MergedSource::new(Vec::new());
+        assert!(source.next_frame().is_none());
+    }
+}
@@ -1,13 +1,257 @@
 pub mod simulation {
 
-    use crossbeam_channel::unbounded;
+    use crossbeam_channel::{unbounded, RecvTimeoutError};
+    use nalgebra::UnitQuaternion;
+    use std::collections::{BTreeMap, HashMap};
+    use std::io;
+    use std::path::Path;
     use std::thread;
+    use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+    use crate::angles::angle::{wrap_angles, AngleRange, Unwrapper};
+    use crate::attitudes::attitude::{AttitudeEstimator, AttitudeFilter};
+    use crate::calibrations::calibration::{calibration_path, Calibration};
     use crate::conversions::conversion::{
-        fixed_points_triplet_from_bits_euler_angles, reconstructed_euler_angles_from_fixed_points,
+        fixed_points_triplet_from_float_euler_angles, ConversionPolicy, ConversionStats,
+        FixedTriplet, MalformedPolicy,
     };
+    use crate::coordinates::coordinate::{Frames, WorldAxes};
+    use crate::filters::filter::{AngleFilter, FilterChain};
+    use crate::frames::frame::{decode_frame, FrameLayout, CRC_MISMATCH};
+    use crate::fusions::fusion::{FusedEpoch, RedundancyConfig, RedundantFusion};
+    use crate::healths::health::{HealthConfig, HealthEvent, HealthMonitor, HealthState};
+    use crate::inputs::input::Source;
+    use crate::orientations::orientation::{
+        EulerAngles, EulerConvention, Orientation, SingularityConfig,
+    };
+    use crate::outputs::output::{ConsoleSink, Route, Sink};
+    use crate::resamplings::resampling::{ResampleConfig, Resampler};
+    use crate::samples::sample::{FusedSample, Sample, TimeBase, Timestamp};
+    use crate::sensors::sensor::{SensorConfig, SensorId, SensorStats};
+    use crate::synchronisations::synchronisation::{SyncConfig, Synchroniser};
+    use crate::velocities::velocity::{euler_rates, AngularVelocity, RateConfig, RateEstimator};
+
+    // Settings that apply to a single run of the pipeline
+    #[derive(Debug, Clone, Default)]
+    pub struct SimulationConfig {
+        // How angles are packed into the incoming frames
+        pub layout: FrameLayout,
+        // How angular rates, accelerations and magnetic fields are packed, for sensors that send them
+        pub rate_layout: FrameLayout,
+        pub acceleration_layout: FrameLayout,
+        pub magnetic_layout: FrameLayout,
+        // Used when decoded angles are converted to fixed-point for the display thread, and
+        // for frames that cannot be decoded at all
+        pub conversion_policy: ConversionPolicy,
+        // Convention of the angles the sensor sends
+        pub input_convention: EulerConvention,
+        // Convention of the Euler angles handed to the sinks
+        pub output_convention: EulerConvention,
+        // World and body axes of the sensors' attitudes, and of everything handed to the sinks
+        pub input_frames: Frames,
+        pub output_frames: Frames,
+        // When output Euler angles are flagged as near gimbal lock, and how they are resolved
+        pub singularity: SingularityConfig,
+        // Range of the (wrapped) output Euler angles
+        pub angle_range: AngleRange,
+        // Also emit continuous Euler angles that do not jump by 2π when crossing the range boundary
+        pub unwrap: bool,
+        // Per-sensor settings; sensors not listed use the layouts and convention above, uncalibrated
+        pub sensors: HashMap<SensorId, SensorConfig>,
+        // Redundant sensors to fuse into one extra stream
+        pub redundancy: Option<RedundancyConfig>,
+        // How thread 2 turns attitude and raw IMU frames into orientations
+        pub filter: AttitudeFilter,
+        // Smoothing and outlier rejection applied, in order, to each sensor's angles after that
+        pub angle_filters: Vec<AngleFilter>,
+        // Derive angular velocity from successive samples of each stream
+        pub angular_velocity: Option<RateConfig>,
+        // Resample each stream to a steady rate before its Euler angles are extracted
+        pub resampling: Option<ResampleConfig>,
+        // Put several sensors' streams on one clock and group their samples into common epochs
+        pub synchronisation: Option<SyncConfig>,
+        // Judge each sensor's input stream and report changes of its health to the sinks
+        pub health: Option<HealthConfig>,
+    }
+
+    impl SimulationConfig {
+        // Checks the settings that only make sense together
+        pub fn validate(&self) -> Result<(), &'static str> {
+            let sensor_layouts = self.sensors.values().flat_map(|sensor| {
+                [
+                    sensor.layout,
+                    sensor.rate_layout,
+                    sensor.acceleration_layout,
+                    sensor.magnetic_layout,
+                ]
+            });
+            for layout in [
+                self.layout,
+                self.rate_layout,
+                self.acceleration_layout,
+                self.magnetic_layout,
+            ]
+            .into_iter()
+            .chain(sensor_layouts)
+            {
+                layout.validate()?;
+            }
+            // Redundant fusion groups samples by sequence number, which is only a shared epoch
+            // once the synchroniser or resampler has set it; per-sensor counters drift apart
+            // as soon as one sensor drops a frame or runs at another rate
+            if self.redundancy.is_some()
+                && self.synchronisation.is_none()
+                && self.resampling.is_none()
+            {
+                return Err(
+                    "Redundant fusion needs synchronisation or resampling to line up its epochs",
+                );
+            }
+            Ok(())
+        }
+
+        pub fn sensor(&self, sensor: SensorId) -> SensorConfig {
+            self.sensors
+                .get(&sensor)
+                .copied()
+                .unwrap_or_else(|| SensorConfig {
+                    layout: self.layout,
+                    rate_layout: self.rate_layout,
+                    acceleration_layout: self.acceleration_layout,
+                    magnetic_layout: self.magnetic_layout,
+                    input_convention: self.input_convention,
+                    frames: self.input_frames,
+                    ..Default::default()
+                })
+        }
+
+        // The frames a sensor's attitudes leave thread 2 in. The AHRS works from raw IMU frames
+        // alone, so it measures against its own earth axes whatever the sensor declares.
+        pub fn stream_frames(&self, sensor: SensorId) -> Frames {
+            let frames = self.sensor(sensor).frames;
+            match self.filter {
+                AttitudeFilter::Madgwick(_) | AttitudeFilter::Mahony(_) => Frames {
+                    world: WorldAxes::Nwu,
+                    ..frames
+                },
+                _ => frames,
+            }
+        }
+
+        // Calibrates each of `sensors` from its file in `directory`, if it has one; sensors
+        // without a file stay as they are
+        pub fn load_calibrations(
+            &mut self,
+            directory: &Path,
+            sensors: &[SensorId],
+        ) -> io::Result<()> {
+            for sensor in sensors {
+                let path = calibration_path(directory, *sensor);
+                if !path.exists() {
+                    continue;
+                }
+                let calibration = Calibration::load(&path)?;
+                let config = self.sensor(*sensor);
+                self.sensors.insert(
+                    *sensor,
+                    SensorConfig {
+                        calibration,
+                        ..config
+                    },
+                );
+            }
+            Ok(())
+        }
+    }
+
+    // Summary of a completed run
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    pub struct SimulationReport {
+        pub samples: usize,
+        // Samples whose output Euler angles were flagged as near gimbal lock
+        pub near_singularity: usize,
+        pub conversion_stats: ConversionStats,
+        // Frames dropped because their CRC did not match
+        pub corrupted_frames: usize,
+        // The same counts, broken down by the sensors that sent frames
+        pub sensors: BTreeMap<SensorId, SensorStats>,
+    }
+
+    // Message passed from the fusion thread to the display thread
+    enum Fused {
+        Sample(FusedSample),
+        Health(HealthEvent),
+    }
+
+    // Display-thread state kept separately for each sensor
+    #[derive(Default)]
+    struct SensorState {
+        previous_third: Option<f32>,
+        unwrapper: Unwrapper,
+        rates: Option<RateEstimator>,
+        resampler: Option<Resampler>,
+    }
+
+    // Time on the host receive clock
+    fn host_time() -> Duration {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+    }
+
+    // Health events of the sensors, other than `except`, that have been silent for a dropout time
+    // or more as of `now`
+    fn check_silence(
+        monitors: &mut BTreeMap<SensorId, HealthMonitor>,
+        except: Option<SensorId>,
+        now: Timestamp,
+    ) -> Vec<HealthEvent> {
+        monitors
+            .values_mut()
+            .filter(|monitor| Some(monitor.sensor) != except)
+            .flat_map(|monitor| monitor.check_silence(now))
+            .collect()
+    }
 
     pub fn run_simulation(queue: crossbeam_queue::SegQueue<Vec<u32>>) {
+        run_simulation_with_config(queue, SimulationConfig::default())
+            .expect("The default configuration is valid");
+    }
+
+    pub fn run_simulation_with_config(
+        queue: crossbeam_queue::SegQueue<Vec<u32>>,
+        config: SimulationConfig,
+    ) -> Result<SimulationReport, &'static str> {
+        run_simulation_with_sinks(queue, config, vec![Box::new(ConsoleSink::default())])
+    }
+
+    pub fn run_simulation_with_sinks(
+        source: impl Source + 'static,
+        config: SimulationConfig,
+        sinks: Vec<Box<dyn Sink>>,
+    ) -> Result<SimulationReport, &'static str> {
+        run_simulation_with_routes(source, config, sinks.into_iter().map(Route::all).collect())
+    }
+
+    // Fails with the first invalid setting, before any thread starts
+    pub fn run_simulation_with_routes(
+        mut source: impl Source + 'static,
+        config: SimulationConfig,
+        mut routes: Vec<Route>,
+    ) -> Result<SimulationReport, &'static str> {
+        config.validate()?;
+        // Each sensor gets its own copy of the filter chain
+        let filter_chain = FilterChain::new(&config.angle_filters)?;
+        let resampler = config.resampling.map(Resampler::new).transpose()?;
+        let mut synchroniser = config
+            .synchronisation
+            .clone()
+            .map(Synchroniser::new)
+            .transpose()?;
+        if let Some(health) = &config.health {
+            health.validate()?;
+        }
+
         println!("Running simulation...");
 
         // Create senders and receivers for necessary channels
@@ -16,9 +260,23 @@ pub mod simulation {
 
         // Spawn a thread that sends sensor data to the first receiver
         let thread1 = thread::spawn(move || {
-            // Get data from the sensor continuously
-            while let Some(sensor_data) = queue.pop() {
-                s1.send(Some(sensor_data))
+            // Get data from the sensor continuously, stamping each frame as it arrives
+            let start = Instant::now();
+            while let Some(frame) = source.next_timed_frame() {
+                let received = host_time();
+                let timestamp = match frame.sensor_time {
+                    Some(sensor_time) => Timestamp {
+                        source: sensor_time,
+                        base: TimeBase::Sensor,
+                        received,
+                    },
+                    None => Timestamp {
+                        source: start.elapsed(),
+                        base: TimeBase::Arrival,
+                        received,
+                    },
+                };
+                s1.send(Some((frame.sensor, frame.kind, frame.bits, timestamp)))
                     .expect("Unable to send sensor data!");
             }
             // Signal the end of the queue
@@ -26,54 +284,470 @@ pub mod simulation {
         });
 
         // Spawn a thread that receives a message, processes it, and then sends to the next receiver
+        let fusion_config = config.clone();
         let thread2 = thread::spawn(move || {
-            while let Ok(sensor_data) = r1.recv() {
-                if let Some(euler_angles) = sensor_data {
-                    // Access the Euler angles (convert bits to three floating points stored as u32)
-                    let fixed_representation: Vec<u32> =
-                        fixed_points_triplet_from_bits_euler_angles(euler_angles);
-                    s2.send(Some(fixed_representation))
+            let config = fusion_config;
+            let mut conversion_stats = ConversionStats::default();
+            let mut corrupted_frames: BTreeMap<SensorId, usize> = BTreeMap::new();
+            let mut sequences: HashMap<SensorId, u64> = HashMap::new();
+            let mut estimators: HashMap<SensorId, AttitudeEstimator> = HashMap::new();
+            let mut filter_chains: HashMap<SensorId, FilterChain> = HashMap::new();
+            let mut monitors: BTreeMap<SensorId, HealthMonitor> = BTreeMap::new();
+            // A sensor that goes quiet is noticed even when no other sensor sends anything: the
+            // wait for the next frame gives up after each dropout time to look at the silence
+            let dropout = config.health.and_then(|health| health.dropout);
+            let mut latest: Option<Timestamp> = None;
+            // Silence is judged on the host clock as of now; the events carry the latest frame's
+            // source time
+            let notice_silence = |monitors: &mut BTreeMap<SensorId, HealthMonitor>,
+                                  latest: Option<Timestamp>| {
+                let Some(latest) = latest else {
+                    return;
+                };
+                let now = Timestamp {
+                    received: host_time(),
+                    ..latest
+                };
+                for event in check_silence(monitors, None, now) {
+                    s2.send(Some(Fused::Health(event)))
+                        .expect("Unable to send health event!");
+                }
+            };
+            loop {
+                let sensor_data = match dropout {
+                    Some(dropout) => match r1.recv_timeout(dropout) {
+                        Ok(sensor_data) => sensor_data,
+                        Err(RecvTimeoutError::Timeout) => {
+                            notice_silence(&mut monitors, latest);
+                            continue;
+                        }
+                        Err(RecvTimeoutError::Disconnected) => None,
+                    },
+                    None => r1.recv().unwrap_or(None),
+                };
+                if let Some((sensor, kind, euler_angles, timestamp)) = sensor_data {
+                    latest = Some(timestamp);
+                    let sensor_config = config.sensor(sensor);
+                    // Access the Euler angles, or raw IMU values (convert bits to three floating point values)
+                    let layout = sensor_config.layout_for(kind);
+                    let [x, y, z] = match decode_frame(&euler_angles, &layout) {
+                        Ok(frame) => frame.angles,
+                        // A corrupted frame is lost, like a dropped one
+                        Err(CRC_MISMATCH) => {
+                            *corrupted_frames.entry(sensor).or_default() += 1;
+                            continue;
+                        }
+                        // Malformed input is a bug unless the policy says to put up with it
+                        Err(error) => {
+                            match config.conversion_policy.malformed {
+                                MalformedPolicy::Error => {
+                                    panic!("Unable to get correct conversion from Euler angles: {error}")
+                                }
+                                MalformedPolicy::Skip => {
+                                    conversion_stats.malformed += 1;
+                                    continue;
+                                }
+                            }
+                        }
+                    };
+
+                    // Every frame is also a chance to notice that another sensor has gone quiet
+                    if let Some(health) = config.health {
+                        let mut events = check_silence(&mut monitors, Some(sensor), timestamp);
+                        let monitor = monitors.entry(sensor).or_insert_with(|| {
+                            HealthMonitor::new(sensor, health).expect("Checked before the run!")
+                        });
+                        events.extend(monitor.check_frame(kind, [x, y, z], timestamp));
+                        for event in events {
+                            s2.send(Some(Fused::Health(event)))
+                                .expect("Unable to send health event!");
+                        }
+                    }
+
+                    let estimator = estimators
+                        .entry(sensor)
+                        .or_insert_with(|| AttitudeEstimator::new(config.filter));
+                    // Not every frame yields an orientation, e.g. rates before the first attitude
+                    let Some(quaternion) = estimator.update(
+                        kind,
+                        [x, y, z],
+                        sensor_config.input_convention,
+                        timestamp.source,
+                    ) else {
+                        continue;
+                    };
+                    let covariance = estimator.attitude_covariance();
+                    // Passed-through angles are kept exactly as sent; estimates are converted back
+                    let angles = match config.filter {
+                        AttitudeFilter::Passthrough => [x, y, z],
+                        _ => sensor_config.input_convention.from_quaternion(quaternion),
+                    };
+                    let filter_chain = filter_chains
+                        .entry(sensor)
+                        .or_insert_with(|| filter_chain.clone());
+                    let (angles, quaternion) = if filter_chain.is_empty() {
+                        (angles, quaternion)
+                    } else {
+                        let filtered = filter_chain.apply(angles);
+                        let quaternion =
+                            EulerAngles::with_convention(filtered, sensor_config.input_convention)
+                                .to_quaternion();
+                        (filtered, quaternion)
+                    };
+                    let [a, b, c] = angles;
+                    let float_representation = (a, b, c);
+                    // Convert back to fixed-point for the display thread, honouring the overflow policy
+                    let fixed_representation: FixedTriplet =
+                        fixed_points_triplet_from_float_euler_angles(
+                            float_representation,
+                            config.conversion_policy,
+                            &mut conversion_stats,
+                        )
                         .expect("Unable to get correct conversion from Euler angles.");
+                    let sequence = sequences.entry(sensor).or_default();
+                    s2.send(Some(Fused::Sample(FusedSample {
+                        sensor,
+                        sequence: *sequence,
+                        timestamp,
+                        angles: fixed_representation,
+                        quaternion,
+                        covariance,
+                    })))
+                    .expect("Unable to get correct conversion from Euler angles.");
+                    *sequence += 1;
                 } else {
+                    // Judge the silence since each sensor's last frame once more, as of the end
+                    notice_silence(&mut monitors, latest);
                     // Forward the end signal to thread3
                     s2.send(None).expect("Unable to send final message!");
                     break;
                 }
             }
+            let health = monitors
+                .into_iter()
+                .map(|(sensor, monitor)| (sensor, monitor.state()))
+                .collect::<BTreeMap<_, _>>();
+            (conversion_stats, corrupted_frames, health)
         });
 
         // Spawn a thread that displays the received message
         let thread3 = thread::spawn(move || {
+            let mut stats: BTreeMap<SensorId, SensorStats> = BTreeMap::new();
+            let mut states: HashMap<SensorId, SensorState> = HashMap::new();
+            let mut redundancy = config.redundancy.clone().map(RedundantFusion::new);
             while let Ok(sensor_data) = r2.recv() {
-                if let Some(euler_angles_fixed_representation) = sensor_data {
-                    // Access the Euler angles (three u32 values used to represent the floating point values)
-                    let (roll, pitch, yaw): (f32, f32, f32) =
-                        reconstructed_euler_angles_from_fixed_points(
-                            euler_angles_fixed_representation,
+                let fused_sample = match sensor_data {
+                    Some(Fused::Sample(fused_sample)) => fused_sample,
+                    Some(Fused::Health(event)) => {
+                        for route in routes.iter_mut() {
+                            if route.accepts(event.sensor) {
+                                route.sink.health(&event).expect("Unable to write to sink!");
+                            }
+                        }
+                        continue;
+                    }
+                    // End signal received
+                    None => break,
+                };
+                let sensor_config = config.sensor(fused_sample.sensor);
+
+                // Rebuild the sensor's orientation from the fixed-point angles, and express it
+                // for the body the sensor is mounted on, in the output frames
+                let [x, y, z] = fused_sample.angles;
+                let measured = [x.to_f32(), y.to_f32(), z.to_f32()];
+                let calibration = sensor_config.calibration;
+                let input_convention = sensor_config.input_convention;
+                let frames = config.stream_frames(fused_sample.sensor);
+                let to_output = |attitude| frames.convert(attitude, config.output_frames);
+                let sample = Sample {
+                    orientation: Orientation::from_quaternion(to_output(
+                        calibration.apply_to_quaternion(fused_sample.quaternion, input_convention),
+                    )),
+                    frames: config.output_frames,
+                    timestamp: fused_sample.timestamp,
+                    sensor: fused_sample.sensor,
+                    covariance: fused_sample.covariance.map(|covariance| {
+                        frames.convert_covariance(
+                            calibration.apply_to_covariance(covariance),
+                            config.output_frames,
+                        )
+                    }),
+                    ..Sample::new(fused_sample.sequence, Orientation::identity())
+                };
+                // Synchronised streams are resampled and differenced on the common clock
+                let sample = match synchroniser.as_mut() {
+                    Some(synchroniser) => synchroniser.correct(sample),
+                    None => sample,
+                };
+                let state = states.entry(fused_sample.sensor).or_default();
+                // Resampled orientations are slerped, so they no longer match the fixed-point
+                // angles
+                let samples = match resampler.as_ref() {
+                    Some(resampler) => state
+                        .resampler
+                        .get_or_insert_with(|| resampler.clone())
+                        .push(sample)
+                        .into_iter()
+                        .map(|sample| {
+                            let attitude = sample.orientation.quaternion;
+                            (sample, attitude)
+                        })
+                        .collect(),
+                    None => vec![(
+                        sample,
+                        to_output(calibration.apply_to_angles(measured, input_convention)),
+                    )],
+                };
+
+                for (sample, attitude) in samples {
+                    let sample = reconstruct_sample(
+                        &config,
+                        states.entry(fused_sample.sensor).or_default(),
+                        stats.entry(fused_sample.sensor).or_default(),
+                        sample,
+                        attitude,
+                    );
+                    let released = match synchroniser.as_mut() {
+                        Some(synchroniser) => synchroniser.push(sample),
+                        None => vec![sample],
+                    };
+                    for sample in released {
+                        deliver(
+                            &config,
+                            &mut routes,
+                            redundancy.as_mut(),
+                            &mut states,
+                            &mut stats,
+                            sample,
                         );
-                    println!(
-                        "Observed Euler angles of {:#?} (roll), {:#?} (pitch), and {:#?} (yaw).",
-                        roll, pitch, yaw
+                    }
+                }
+            }
+            if let Some(synchroniser) = synchroniser.as_mut() {
+                for sample in synchroniser.flush() {
+                    deliver(
+                        &config,
+                        &mut routes,
+                        redundancy.as_mut(),
+                        &mut states,
+                        &mut stats,
+                        sample,
                     );
-                } else {
-                    // End signal received
-                    break;
+                }
+                for (sensor, count) in synchroniser.late() {
+                    stats.entry(*sensor).or_default().late = *count;
                 }
             }
+            if let Some(redundancy) = redundancy.as_mut() {
+                for epoch in redundancy.flush() {
+                    let fused_sensor = redundancy.config.fused_sensor;
+                    let sample =
+                        fused_epoch_sample(&config, &mut states, &mut stats, fused_sensor, epoch);
+                    emit(&mut routes, &sample);
+                }
+                for (sensor, count) in redundancy.late() {
+                    stats.entry(*sensor).or_default().late += *count;
+                }
+            }
+            for route in routes.iter_mut() {
+                route.sink.finish().expect("Unable to flush sink!");
+            }
+            stats
         });
-
         // Join threads to ensure they complete
         thread1.join().expect("Unable to join thread1!");
-        thread2.join().expect("Unable to join thread2!");
-        thread3.join().expect("Unable to join thread3!");
+        let (conversion_stats, corrupted, health) =
+            thread2.join().expect("Unable to join thread2!");
+        let mut sensors = thread3.join().expect("Unable to join thread3!");
+        for (sensor, count) in corrupted {
+            sensors.entry(sensor).or_default().corrupted_frames = count;
+        }
+        for (sensor, state) in health {
+            sensors.entry(sensor).or_default().health = state;
+        }
+        let samples = sensors.values().map(|stats| stats.samples).sum();
+        let near_singularity = sensors.values().map(|stats| stats.near_singularity).sum();
+        let corrupted_frames = sensors.values().map(|stats| stats.corrupted_frames).sum();
 
+        if conversion_stats.saturated > 0 {
+            println!(
+                "Saturated {} out-of-range value(s) during conversion.",
+                conversion_stats.saturated
+            );
+        }
+        if conversion_stats.wrapped > 0 {
+            println!(
+                "Wrapped {} out-of-range value(s) during conversion.",
+                conversion_stats.wrapped
+            );
+        }
+        if conversion_stats.nan_zeroed > 0 {
+            println!(
+                "Converted {} NaN value(s) to zero during conversion.",
+                conversion_stats.nan_zeroed
+            );
+        }
+        if conversion_stats.malformed > 0 {
+            println!(
+                "Dropped {} malformed frame(s) that could not be read.",
+                conversion_stats.malformed
+            );
+        }
+        if corrupted_frames > 0 {
+            println!(
+                "Dropped {} corrupted frame(s) that failed their CRC.",
+                corrupted_frames
+            );
+        }
+        if near_singularity > 0 {
+            println!(
+                "Flagged {} sample(s) near gimbal lock; their Euler angles are not individually reliable.",
+                near_singularity
+            );
+        }
+        for (sensor, stats) in &sensors {
+            if stats.health != HealthState::Ok {
+                println!("Sensor {} ended the run {}.", sensor, stats.health);
+            }
+        }
+        if sensors.len() > 1 {
+            for (sensor, stats) in &sensors {
+                println!("Sensor {}: {} sample(s).", sensor, stats.samples);
+                if stats.late > 0 {
+                    println!(
+                        "Sensor {}: dropped {} sample(s) that arrived too late for their epoch.",
+                        sensor, stats.late
+                    );
+                }
+            }
+        }
         println!("Simulation concluded.");
+
+        Ok(SimulationReport {
+            samples,
+            near_singularity,
+            conversion_stats,
+            corrupted_frames,
+            sensors,
+        })
+    }
+
+    fn emit(routes: &mut [Route], sample: &Sample) {
+        for route in routes.iter_mut() {
+            if route.accepts(sample.sensor) {
+                route.sink.emit(sample).expect("Unable to write to sink!");
+            }
+        }
+    }
+
+    // Hands a finished sample to the sinks; redundant sensors' samples are also combined into one
+    // estimate per epoch
+    fn deliver(
+        config: &SimulationConfig,
+        routes: &mut [Route],
+        redundancy: Option<&mut RedundantFusion>,
+        states: &mut HashMap<SensorId, SensorState>,
+        stats: &mut BTreeMap<SensorId, SensorStats>,
+        sample: Sample,
+    ) {
+        emit(routes, &sample);
+        if let Some(redundancy) = redundancy {
+            for epoch in redundancy.push(&sample) {
+                let fused_sensor = redundancy.config.fused_sensor;
+                let sample = fused_epoch_sample(config, states, stats, fused_sensor, epoch);
+                emit(routes, &sample);
+            }
+        }
+    }
+
+    // Fills in the Euler angles of `sample`, extracted from `attitude` in the output convention,
+    // and updates the sensor's display-thread state and counts
+    fn reconstruct_sample(
+        config: &SimulationConfig,
+        state: &mut SensorState,
+        stats: &mut SensorStats,
+        mut sample: Sample,
+        attitude: UnitQuaternion<f32>,
+    ) -> Sample {
+        // Access the Euler angles in the output convention
+        let mut euler_angles: EulerAngles =
+            config
+                .output_convention
+                .extract(attitude, &config.singularity, state.previous_third);
+        state.previous_third = Some(euler_angles.angles[2]);
+        if euler_angles.near_singularity {
+            stats.near_singularity += 1;
+        }
+        // Unwrap before re-ranging, so continuous angles do not depend on the range
+        sample.unwrapped = config
+            .unwrap
+            .then(|| state.unwrapper.push(euler_angles.angles));
+        euler_angles.angles = wrap_angles(euler_angles.angles, config.angle_range);
+        sample.orientation = Orientation::new(euler_angles, sample.orientation.quaternion);
+        if let Some(rate_config) = config.angular_velocity {
+            let rates = state
+                .rates
+                .get_or_insert_with(|| RateEstimator::new(rate_config));
+            sample.angular_velocity = rates
+                .push(sample.orientation.quaternion, sample.timestamp.source)
+                .map(|body| AngularVelocity {
+                    body,
+                    euler_rates: euler_rates(&sample.orientation.euler, body),
+                });
+        }
+        stats.samples += 1;
+        sample
+    }
+
+    fn fused_epoch_sample(
+        config: &SimulationConfig,
+        states: &mut HashMap<SensorId, SensorState>,
+        stats: &mut BTreeMap<SensorId, SensorStats>,
+        fused_sensor: SensorId,
+        epoch: FusedEpoch,
+    ) -> Sample {
+        for sensor in &epoch.estimate.info.rejected {
+            stats.entry(*sensor).or_default().rejected += 1;
+        }
+        let quaternion = epoch.estimate.quaternion;
+        reconstruct_sample(
+            config,
+            states.entry(fused_sensor).or_default(),
+            stats.entry(fused_sensor).or_default(),
+            Sample {
+                sensor: fused_sensor,
+                frames: config.output_frames,
+                timestamp: epoch.timestamp,
+                fusion: Some(epoch.estimate.info),
+                ..Sample::new(epoch.sequence, Orientation::from_quaternion(quaternion))
+            },
+            quaternion,
+        )
     }
 }
 #[cfg(test)]
 mod tests {
     use super::simulation::*;
+    use crate::angles::angle::{wrap_angle, AngleRange};
+    use crate::conversions::conversion::{ConversionPolicy, ConversionStats};
+    use crate::frames::frame::{Encoder, FrameLayout, SpareByte};
+    use crate::orientations::orientation::{
+        quaternion_from_euler_angles, EulerAngles, EulerConvention, EulerSequence,
+    };
+    use crate::outputs::output::Sink;
+    use crate::samples::sample::Sample;
     use crossbeam_queue::SegQueue;
+    use std::io;
+
+    // Forwards every sample so a test can inspect what reached the sinks
+    struct CollectingSink(crossbeam_channel::Sender<Sample>);
+
+    impl Sink for CollectingSink {
+        fn emit(&mut self, sample: &Sample) -> io::Result<()> {
+            self.0.send(sample.clone()).unwrap();
+            Ok(())
+        }
+    }
 
     #[test]
     fn test_run_simulation_empty_queue() {
@@ -156,4 +830,1188 @@ mod tests {
         thread2.join().expect("Unable to join thread2!");
         // No assertions needed, just checking if the simulation completes without errors
     }
+
+    #[test]
+    fn test_run_simulation_with_config_report() {
+        let queue = SegQueue::new();
+        for _ in 0..3 {
+            queue.push(vec![
+                1, 0, 1, 1, 0, 0, 1, 0, // x: 178
+                0, 0, 0, 0, 1, 0, 1, 1, // y: 11
+                1, 1, 1, 1, 0, 0, 0, 1, // z: 241
+                1, 1, 1, 0, 1, 0, 0, 0, // Last 8 bits unused
+            ]);
+        }
+
+        let config = SimulationConfig {
+            conversion_policy: ConversionPolicy::saturating(),
+            ..Default::default()
+        };
+        let report = run_simulation_with_config(queue, config).unwrap();
+        assert_eq!(report.samples, 3);
+        assert_eq!(report.conversion_stats, ConversionStats::default());
+    }
+
+    #[test]
+    fn test_run_simulation_skips_malformed_frames() {
+        let frame = vec![
+            1, 0, 1, 1, 0, 0, 1, 0, // x: 178
+            0, 0, 0, 0, 1, 0, 1, 1, // y: 11
+            1, 1, 1, 1, 0, 0, 0, 1, // z: 241
+            1, 1, 1, 0, 1, 0, 0, 0, // Last 8 bits unused
+        ];
+        let queue = SegQueue::new();
+        queue.push(frame.clone());
+        queue.push(vec![]);
+        queue.push(vec![0, 1, 2, 3]);
+        queue.push(frame);
+
+        let config = SimulationConfig {
+            conversion_policy: ConversionPolicy::saturating(),
+            ..Default::default()
+        };
+        let report = run_simulation_with_config(queue, config).unwrap();
+        assert_eq!(report.samples, 2);
+        assert_eq!(report.conversion_stats.malformed, 2);
+    }
+
+    #[test]
+    fn test_run_simulation_with_sinks() {
+        let queue = SegQueue::new();
+        queue.push(vec![
+            1, 0, 1, 1, 0, 0, 1, 0, // x: 178
+            0, 0, 0, 0, 1, 0, 1, 1, // y: 11
+            1, 1, 1, 1, 0, 0, 0, 1, // z: 241
+            1, 1, 1, 0, 1, 0, 0, 0, // Last 8 bits unused
+        ]);
+
+        let (report, samples) = collect(queue, SimulationConfig::default());
+        assert_eq!(report.samples, 1);
+
+        let sample = &samples[0];
+        assert_eq!(sample.sequence, 0);
+        let expected = quaternion_from_euler_angles(EulerAngles::new(
+            178.0 / 65536.0,
+            11.0 / 65536.0,
+            241.0 / 65536.0,
+        ));
+        assert!(sample.orientation.quaternion.angle_to(&expected) < 1e-6);
+        assert!((sample.orientation.euler.angles[0] - 178.0 / 65536.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_run_simulation_unwrap_and_range() {
+        let queue = SegQueue::new();
+        queue.push(vec![
+            1, 0, 1, 1, 0, 0, 1, 0, // x: 178
+            0, 0, 0, 0, 1, 0, 1, 1, // y: 11
+            1, 1, 1, 1, 0, 0, 0, 1, // z: 241
+            1, 1, 1, 0, 1, 0, 0, 0, // Last 8 bits unused
+        ]);
+
+        // Viewed as ZXZ, the small input angles give a first angle just below zero
+        let config = SimulationConfig {
+            output_convention: EulerConvention::intrinsic(EulerSequence::ZXZ),
+            angle_range: AngleRange::ZeroToTwoPi,
+            unwrap: true,
+            ..Default::default()
+        };
+        let (_, samples) = collect(queue, config);
+
+        let sample = &samples[0];
+        let unwrapped = sample.unwrapped.unwrap();
+        for i in 0..3 {
+            let angle = sample.orientation.euler.angles[i];
+            assert!((0.0..std::f32::consts::TAU).contains(&angle));
+            let expected = wrap_angle(unwrapped.angles[i] as f32, AngleRange::ZeroToTwoPi);
+            assert!((angle - expected).abs() < 1e-5);
+        }
+        assert_eq!(unwrapped.wraps, [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_run_simulation_with_encoded_frames() {
+        let layout = FrameLayout {
+            spare: SpareByte::Crc8,
+            ..test_layout()
+        };
+        let mut encoder = test_encoder(layout);
+        let queue = SegQueue::new();
+        queue.push(encoder.encode_angles([-0.5, 0.25, 1.5]).unwrap());
+        queue.push(encoder.encode_angles([0.1, -1.0, -2.0]).unwrap());
+
+        let config = SimulationConfig {
+            layout,
+            ..Default::default()
+        };
+        let (report, samples) = collect(queue, config);
+        assert_eq!(report.samples, 2);
+        assert_eq!(report.conversion_stats, ConversionStats::default());
+
+        // Negative angles make it through the signed layout and the fixed-point transport
+        let first = samples[0].orientation.euler.angles;
+        let expected = [-0.5, 0.25, 1.5];
+        for (angle, expected) in first.iter().zip(expected) {
+            assert!((angle - expected).abs() <= layout.quantization_bound() + 1e-5);
+        }
+        let second = samples[1].orientation.euler.angles;
+        assert!((second[0] - 0.09375).abs() < 1e-5);
+        assert!((second[2] + 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_run_simulation_with_generator_source() {
+        use crate::generators::generator::{Generator, GeneratorSource, MotionProfile};
+        use nalgebra::{UnitQuaternion, Vector3};
+
+        let layout = FrameLayout {
+            spare: SpareByte::Sequence,
+            ..test_layout()
+        };
+        let profile = MotionProfile::ConstantRate {
+            start: UnitQuaternion::identity(),
+            axis: Vector3::z_axis(),
+            rate: 0.5,
+        };
+        let source = GeneratorSource::new(
+            Generator::new(profile, 10.0, 1).unwrap(),
+            test_encoder(layout),
+            Some(20),
+        );
+
+        let config = SimulationConfig {
+            layout,
+            ..Default::default()
+        };
+        let (report, samples) = collect(source, config);
+        assert_eq!(report.samples, 20);
+
+        // After 1.9 s at 0.5 rad/s, yaw is 0.95 rad, give or take the 1/32 rad field resolution
+        let last = samples.last().unwrap();
+        assert_eq!(last.sequence, 19);
+        assert!(
+            (last.orientation.euler.angles[2] - 0.95).abs() <= layout.quantization_bound() + 1e-5
+        );
+    }
+
+    #[test]
+    fn test_run_simulation_with_sensor_errors() {
+        use crate::generators::generator::{Generator, GeneratorSource, MotionProfile};
+        use crate::noises::noise::{ErrorConfig, ErrorModel};
+        use nalgebra::UnitQuaternion;
+
+        let layout = FrameLayout {
+            spare: SpareByte::Crc8,
+            ..test_layout()
+        };
+        let errors = ErrorConfig {
+            white_noise: [0.02; 3],
+            dropout_probability: 0.1,
+            bit_flip_probability: 0.01,
+            ..Default::default()
+        };
+        let run = || {
+            let generator =
+                Generator::new(MotionProfile::Static(UnitQuaternion::identity()), 100.0, 1)
+                    .unwrap();
+            let source = GeneratorSource::new(generator, test_encoder(layout), Some(200))
+                .with_errors(ErrorModel::new(errors, 2));
+            let config = SimulationConfig {
+                layout,
+                ..Default::default()
+            };
+            run_simulation_with_sinks(source, config, Vec::new()).unwrap()
+        };
+
+        let report = run();
+        // Some frames never arrive and some fail their CRC, but the run completes
+        assert!(report.samples < 200);
+        assert!(report.corrupted_frames > 0);
+        assert!(report.samples + report.corrupted_frames < 200);
+        assert_eq!(run(), report);
+    }
+
+    #[test]
+    fn test_run_simulation_timestamps() {
+        use crate::generators::generator::{Generator, GeneratorSource, MotionProfile};
+        use crate::samples::sample::TimeBase;
+        use nalgebra::UnitQuaternion;
+        use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+        let before = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+
+        // Queued frames have no sensor time, so they are stamped on arrival
+        let queue = SegQueue::new();
+        queue.push(vec![0; 32]);
+        queue.push(vec![0; 32]);
+        let (_, samples) = collect(queue, SimulationConfig::default());
+        assert!(samples
+            .iter()
+            .all(|s| s.timestamp.base == TimeBase::Arrival));
+        assert!(samples[0].timestamp.source <= samples[1].timestamp.source);
+        assert!(samples.iter().all(|s| s.timestamp.received >= before));
+
+        // The generator stamps frames with its own sample clock
+        let layout = test_layout();
+        let generator =
+            Generator::new(MotionProfile::Static(UnitQuaternion::identity()), 4.0, 0).unwrap();
+        let source = GeneratorSource::new(generator, test_encoder(layout), Some(3));
+        let config = SimulationConfig {
+            layout,
+            ..Default::default()
+        };
+        let (_, samples) = collect(source, config);
+        assert_eq!(samples[2].timestamp.base, TimeBase::Sensor);
+        assert_eq!(samples[2].timestamp.source, Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_run_simulation_multiple_sensors() {
+        use crate::calibrations::calibration::Calibration;
+        use crate::generators::generator::{Generator, GeneratorSource, MotionProfile};
+        use crate::inputs::input::Source;
+        use crate::outputs::output::Route;
+        use crate::sensors::sensor::{MergedSource, SensorConfig, SensorId, SensorStats};
+        use nalgebra::UnitQuaternion;
+
+        let body = EulerAngles::new(0.2, -0.1, 0.4).to_quaternion();
+        // Sensor 2 sends ZYX angles with a CRC and is mounted turned 0.5 rad about its z axis
+        let mounting = UnitQuaternion::from_euler_angles(0.0, 0.0, 0.5);
+        let first = SensorConfig {
+            layout: FrameLayout {
+                spare: SpareByte::Sequence,
+                ..test_layout()
+            },
+            ..Default::default()
+        };
+        let second = SensorConfig {
+            layout: FrameLayout {
+                spare: SpareByte::Crc8,
+                ..test_layout()
+            },
+            input_convention: EulerConvention::intrinsic(EulerSequence::ZYX),
+            calibration: Calibration {
+                mounting,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let source = |config: SensorConfig, orientation, frames| -> Box<dyn Source> {
+            let generator = Generator::new(MotionProfile::Static(orientation), 100.0, 0).unwrap();
+            let encoder = Encoder::new(
+                config.layout,
+                config.input_convention,
+                ConversionPolicy::default(),
+            )
+            .unwrap();
+            Box::new(GeneratorSource::new(generator, encoder, Some(frames)))
+        };
+        let merged = MergedSource::new(vec![
+            (SensorId(1), source(first, body, 4)),
+            (SensorId(2), source(second, body * mounting, 6)),
+        ]);
+
+        let mut config = SimulationConfig::default();
+        config.sensors.insert(SensorId(1), first);
+        config.sensors.insert(SensorId(2), second);
+        let (all_sender, all_receiver) = crossbeam_channel::unbounded();
+        let (second_sender, second_receiver) = crossbeam_channel::unbounded();
+        let routes = vec![
+            Route::all(Box::new(CollectingSink(all_sender))),
+            Route::only(vec![SensorId(2)], Box::new(CollectingSink(second_sender))),
+        ];
+        let report = run_simulation_with_routes(merged, config, routes).unwrap();
+
+        assert_eq!(report.samples, 10);
+        assert_eq!(
+            report.sensors[&SensorId(1)],
+            SensorStats {
+                samples: 4,
+                ..Default::default()
+            }
+        );
+        assert_eq!(report.sensors[&SensorId(2)].samples, 6);
+
+        // Both sensors describe the same body orientation, to within the 1/32 rad field resolution
+        let samples: Vec<Sample> = all_receiver.iter().collect();
+        assert_eq!(samples.len(), 10);
+        for sample in &samples {
+            assert!(sample.orientation.quaternion.angle_to(&body) < 0.05);
+        }
+        let second_samples: Vec<Sample> = second_receiver.iter().collect();
+        assert_eq!(second_samples.len(), 6);
+        assert!(second_samples
+            .iter()
+            .all(|sample| sample.sensor == SensorId(2)));
+        let sequences: Vec<u64> = second_samples
+            .iter()
+            .map(|sample| sample.sequence)
+            .collect();
+        assert_eq!(sequences, vec![0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_run_simulation_redundant_fusion() {
+        use crate::fusions::fusion::RedundancyConfig;
+        use crate::generators::generator::{Generator, GeneratorSource, MotionProfile};
+        use crate::inputs::input::Source;
+        use crate::outputs::output::Route;
+        use crate::resamplings::resampling::ResampleConfig;
+        use crate::sensors::sensor::{MergedSource, SensorConfig, SensorId};
+
+        let layout = test_layout();
+        let truth = EulerAngles::new(0.5, 0.25, -0.5).to_quaternion();
+        let broken = EulerAngles::new(-1.0, 1.0, 2.0).to_quaternion();
+        let source = |orientation| -> Box<dyn Source> {
+            let generator = Generator::new(MotionProfile::Static(orientation), 100.0, 0).unwrap();
+            Box::new(GeneratorSource::new(
+                generator,
+                test_encoder(layout),
+                Some(5),
+            ))
+        };
+        let merged = MergedSource::new(vec![
+            (SensorId(1), source(truth)),
+            (SensorId(2), source(truth)),
+            (SensorId(3), source(broken)),
+        ]);
+
+        let mut config = SimulationConfig {
+            redundancy: Some(RedundancyConfig {
+                sensors: vec![(SensorId(1), 1.0), (SensorId(2), 1.0), (SensorId(3), 1.0)],
+                fused_sensor: SensorId(10),
+                outlier_threshold: 0.2,
+                max_lag: 8,
+            }),
+            // The generators stamp their frames every 10 ms, so the grid meets every sample
+            resampling: Some(ResampleConfig {
+                rate: 100.0,
+                tolerance: std::time::Duration::from_millis(1),
+                max_gap: None,
+            }),
+            ..Default::default()
+        };
+        for id in 1..=3 {
+            config.sensors.insert(
+                SensorId(id),
+                SensorConfig {
+                    layout,
+                    ..Default::default()
+                },
+            );
+        }
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let routes = vec![Route::only(
+            vec![SensorId(10)],
+            Box::new(CollectingSink(sender)),
+        )];
+        let report = run_simulation_with_routes(merged, config, routes).unwrap();
+
+        assert_eq!(report.sensors[&SensorId(10)].samples, 5);
+        assert_eq!(report.sensors[&SensorId(3)].rejected, 5);
+        let fused: Vec<Sample> = receiver.iter().collect();
+        assert_eq!(fused.len(), 5);
+        for (sequence, sample) in fused.iter().enumerate() {
+            assert_eq!(sample.sequence, sequence as u64);
+            let info = sample.fusion.as_ref().unwrap();
+            assert_eq!(info.contributors, vec![SensorId(1), SensorId(2)]);
+            assert_eq!(info.rejected, vec![SensorId(3)]);
+            assert!(sample.orientation.quaternion.angle_to(&truth) < 0.05);
+        }
+    }
+
+    #[test]
+    fn test_run_simulation_rejects_invalid_config_before_reading_the_source() {
+        use crate::filters::filter::AngleFilter;
+        use crate::inputs::input::Source;
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        struct WatchedSource(Arc<AtomicBool>);
+
+        impl Source for WatchedSource {
+            fn next_frame(&mut self) -> Option<Vec<u32>> {
+                self.0.store(true, Ordering::SeqCst);
+                None
+            }
+        }
+
+        let read = Arc::new(AtomicBool::new(false));
+        let config = SimulationConfig {
+            angle_filters: vec![AngleFilter::Median { window: 0 }],
+            ..Default::default()
+        };
+        let result = run_simulation_with_sinks(WatchedSource(read.clone()), config, Vec::new());
+        assert!(result.is_err());
+        assert!(!read.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_simulation_config_rejects_unaligned_redundancy() {
+        use crate::fusions::fusion::RedundancyConfig;
+        use crate::resamplings::resampling::ResampleConfig;
+        use crate::sensors::sensor::SensorId;
+
+        let mut config = SimulationConfig {
+            redundancy: Some(RedundancyConfig {
+                sensors: vec![(SensorId(1), 1.0), (SensorId(2), 1.0)],
+                fused_sensor: SensorId(10),
+                outlier_threshold: 0.2,
+                max_lag: 8,
+            }),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+        config.resampling = Some(ResampleConfig {
+            rate: 100.0,
+            tolerance: std::time::Duration::from_millis(1),
+            max_gap: None,
+        });
+        assert!(config.validate().is_ok());
+        config.resampling = None;
+        config.synchronisation = Some(Default::default());
+        assert!(config.validate().is_ok());
+
+        // A shift by 64 or more fractional bits would panic in the decoder
+        config.rate_layout.fractional_bits = 64;
+        assert!(config.validate().is_err());
+    }
+
+    // Signed fields with a 1/32 rad resolution, which most tests encode their frames with
+    fn test_layout() -> FrameLayout {
+        FrameLayout {
+            fractional_bits: 5,
+            signed: true,
+            spare: SpareByte::Unused,
+        }
+    }
+
+    fn test_encoder(layout: FrameLayout) -> Encoder {
+        Encoder::new(
+            layout,
+            EulerConvention::default(),
+            ConversionPolicy::default(),
+        )
+        .unwrap()
+    }
+
+    // Runs the pipeline with one sink that keeps every sample
+    fn collect(
+        source: impl crate::inputs::input::Source + 'static,
+        config: SimulationConfig,
+    ) -> (SimulationReport, Vec<Sample>) {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let sinks: Vec<Box<dyn Sink>> = vec![Box::new(CollectingSink(sender))];
+        let report = run_simulation_with_sinks(source, config, sinks).unwrap();
+        (report, receiver.iter().collect())
+    }
+
+    // Yaw turning at 0.5 rad/s for two seconds, with the gyro reporting at 50 Hz and the attitude at
+    // 5 Hz, packed with the layouts of `yaw_turn_config`
+    fn yaw_turn_source() -> crate::replays::replay::ReplaySource {
+        use crate::frames::frame::{encode_frame, FrameKind};
+        use crate::inputs::input::TimedFrame;
+        use crate::replays::replay::{ReplayConfig, ReplaySource, Speed};
+        use crate::sensors::sensor::SensorId;
+        use std::time::Duration;
+
+        let config = yaw_turn_config();
+        let mut frames = Vec::new();
+        let mut stats = ConversionStats::default();
+        let policy = ConversionPolicy::default();
+        for step in 0..=100u64 {
+            let time = Duration::from_millis(20 * step);
+            let frame = |bits, kind| TimedFrame {
+                bits,
+                sensor_time: Some(time),
+                sensor: SensorId::default(),
+                kind,
+            };
+            if step % 10 == 0 {
+                let angles = [0.0, 0.0, 0.5 * time.as_secs_f32()];
+                let bits = encode_frame(angles, 0, &config.layout, policy, &mut stats).unwrap();
+                frames.push(frame(bits, FrameKind::Attitude));
+            }
+            let rate = [0.0, 0.0, 0.5];
+            let bits = encode_frame(rate, 0, &config.rate_layout, policy, &mut stats).unwrap();
+            frames.push(frame(bits, FrameKind::AngularRate));
+        }
+        let replay = ReplayConfig {
+            speed: Speed::AsFastAsPossible,
+            ..Default::default()
+        };
+        ReplaySource::new(frames, replay).unwrap()
+    }
+
+    fn yaw_turn_config() -> SimulationConfig {
+        let layout = test_layout();
+        SimulationConfig {
+            layout,
+            rate_layout: FrameLayout {
+                fractional_bits: 6,
+                ..layout
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_run_simulation_complementary_filter() {
+        use crate::attitudes::attitude::{AttitudeFilter, ComplementaryConfig};
+        use std::time::Duration;
+
+        let config = SimulationConfig {
+            filter: AttitudeFilter::Complementary(ComplementaryConfig {
+                crossover_frequency: 0.1,
+            }),
+            ..yaw_turn_config()
+        };
+        let (report, samples) = collect(yaw_turn_source(), config);
+
+        // Every frame after the first attitude one yields a sample
+        assert_eq!(report.samples, 112);
+        let last = samples.last().unwrap();
+        assert_eq!(last.timestamp.source, Duration::from_secs(2));
+        // Between attitude frames the yaw keeps moving with the gyro instead of holding still
+        let yaws: Vec<f32> = samples
+            .iter()
+            .map(|s| s.orientation.euler.angles[2])
+            .collect();
+        assert!(yaws.windows(2).all(|pair| pair[1] > pair[0] - 1e-4));
+        assert!((last.orientation.euler.angles[2] - 1.0).abs() < 0.01);
+        assert!(samples.iter().all(|sample| sample.covariance.is_none()));
+    }
+
+    #[test]
+    fn test_run_simulation_ekf_covariance() {
+        use crate::attitudes::attitude::{AttitudeFilter, EkfConfig};
+        use crate::calibrations::calibration::Calibration;
+        use crate::sensors::sensor::{SensorConfig, SensorId};
+        use nalgebra::{UnitQuaternion, Vector3};
+
+        let run = |calibration| {
+            let mut config = SimulationConfig {
+                filter: AttitudeFilter::Ekf(EkfConfig::default()),
+                ..yaw_turn_config()
+            };
+            config.sensors.insert(
+                SensorId::default(),
+                SensorConfig {
+                    layout: config.layout,
+                    rate_layout: config.rate_layout,
+                    calibration,
+                    ..Default::default()
+                },
+            );
+            let (report, samples) = collect(yaw_turn_source(), config);
+            assert_eq!(report.samples, 112);
+            samples
+                .iter()
+                .map(|sample| sample.covariance.unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        let sensor = run(Calibration::default());
+        // The attitude frames make the estimate far more certain than it started out
+        let (first, last) = (sensor[0], sensor[sensor.len() - 1]);
+        assert!((0..3).all(|axis| last[(axis, axis)] < first[(axis, axis)] / 4.0));
+
+        // Mounted a quarter turn about x, the covariance is expressed about the body axes
+        let mounting =
+            UnitQuaternion::from_axis_angle(&Vector3::x_axis(), std::f32::consts::FRAC_PI_2);
+        let body = run(Calibration {
+            mounting,
+            ..Default::default()
+        });
+        let rotation = mounting.to_rotation_matrix();
+        let expected = rotation.matrix() * last * rotation.matrix().transpose();
+        assert!((body[body.len() - 1] - expected).norm() < 1e-9);
+    }
+
+    #[test]
+    fn test_run_simulation_madgwick_ahrs() {
+        use crate::attitudes::attitude::{AttitudeFilter, MadgwickConfig};
+        use crate::frames::frame::{encode_frame, FrameKind};
+        use crate::inputs::input::TimedFrame;
+        use crate::replays::replay::{ReplayConfig, ReplaySource, Speed};
+        use crate::sensors::sensor::SensorId;
+        use nalgebra::{UnitQuaternion, Vector3};
+        use std::time::Duration;
+
+        let imu_layout = FrameLayout {
+            fractional_bits: 6,
+            ..test_layout()
+        };
+        // Tilted 0.2 rad in roll and turning about the vertical at 0.3 rad/s; accelerometer
+        // (in g), magnetometer (field dipping 60°) and gyro all report at 50 Hz
+        let truth_at = |time: f32| {
+            UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 0.3 * time)
+                * UnitQuaternion::from_euler_angles(0.2, 0.0, 0.0)
+        };
+        let field = Vector3::new(0.5, 0.0, -0.866);
+        let mut frames = Vec::new();
+        let mut stats = ConversionStats::default();
+        let policy = ConversionPolicy::default();
+        for step in 0..=200u64 {
+            let time = Duration::from_millis(20 * step);
+            let truth = truth_at(time.as_secs_f32());
+            let readings = [
+                (FrameKind::Acceleration, truth.inverse() * Vector3::z()),
+                (FrameKind::MagneticField, truth.inverse() * field),
+                (
+                    FrameKind::AngularRate,
+                    truth.inverse() * Vector3::new(0.0, 0.0, 0.3),
+                ),
+            ];
+            for (kind, reading) in readings {
+                let values = [reading.x, reading.y, reading.z];
+                frames.push(TimedFrame {
+                    bits: encode_frame(values, 0, &imu_layout, policy, &mut stats).unwrap(),
+                    sensor_time: Some(time),
+                    sensor: SensorId::default(),
+                    kind,
+                });
+            }
+        }
+        let replay = ReplayConfig {
+            speed: Speed::AsFastAsPossible,
+            ..Default::default()
+        };
+        let source = ReplaySource::new(frames, replay).unwrap();
+
+        let config = SimulationConfig {
+            rate_layout: imu_layout,
+            acceleration_layout: imu_layout,
+            magnetic_layout: imu_layout,
+            filter: AttitudeFilter::Madgwick(MadgwickConfig { beta: 0.05 }),
+            ..Default::default()
+        };
+        let (report, samples) = collect(source, config);
+
+        // One sample per gyro frame
+        assert_eq!(report.samples, 201);
+        // Quantised readings cost some accuracy, but the estimate follows the turn
+        for sample in samples.iter().step_by(20) {
+            let truth = truth_at(sample.timestamp.source.as_secs_f32());
+            assert!(sample.orientation.quaternion.angle_to(&truth) < 0.05);
+        }
+    }
+
+    #[test]
+    fn test_run_simulation_angle_filters() {
+        use crate::filters::filter::AngleFilter;
+        use std::f32::consts::PI;
+
+        let layout = test_layout();
+        let mut encoder = test_encoder(layout);
+        // Yaw hovers at ±π, so the decoded angles flip sign; one frame carries a spike in roll
+        let queue = SegQueue::new();
+        for i in 0..20 {
+            let yaw = if i % 2 == 0 { 3.125 } else { -3.125 };
+            let roll = if i == 10 { 2.0 } else { 0.25 };
+            queue.push(encoder.encode_angles([roll, 0.0, yaw]).unwrap());
+        }
+
+        let config = SimulationConfig {
+            layout,
+            angle_filters: vec![
+                AngleFilter::Hampel {
+                    window: 5,
+                    threshold: 3.0,
+                },
+                AngleFilter::ExponentialMovingAverage { alpha: 0.5 },
+            ],
+            ..Default::default()
+        };
+        let (report, samples) = collect(queue, config);
+        assert_eq!(report.samples, 20);
+
+        for sample in &samples {
+            let [roll, _, yaw] = sample.orientation.euler.angles;
+            assert!((roll - 0.25).abs() < 1e-3);
+            // Averaging on the circle stays at π instead of collapsing towards zero
+            assert!(PI - yaw.abs() < 0.05);
+            assert!(
+                sample
+                    .orientation
+                    .quaternion
+                    .angle_to(&EulerAngles::new(0.25, 0.0, PI).to_quaternion())
+                    < 0.05
+            );
+        }
+    }
+
+    #[test]
+    fn test_run_simulation_angular_velocity() {
+        use crate::generators::generator::{Generator, GeneratorSource, MotionProfile};
+        use crate::velocities::velocity::RateConfig;
+        use nalgebra::Vector3;
+
+        let layout = test_layout();
+        // Rolled by 0.3 rad, then spinning about the body z axis at 0.5 rad/s, sampled at 10 Hz
+        let profile = MotionProfile::ConstantRate {
+            start: EulerAngles::new(0.3, 0.0, 0.0).to_quaternion(),
+            axis: Vector3::z_axis(),
+            rate: 0.5,
+        };
+        let source = GeneratorSource::new(
+            Generator::new(profile, 10.0, 1).unwrap(),
+            test_encoder(layout),
+            Some(40),
+        );
+
+        // The 1/32 rad field resolution is 0.3 rad/s of noise on a single 0.1 s step, so rates are
+        // taken over a second
+        let config = SimulationConfig {
+            layout,
+            unwrap: true,
+            angular_velocity: Some(RateConfig { window: 10 }),
+            ..Default::default()
+        };
+        let (_, samples) = collect(source, config);
+        assert!(samples[0].angular_velocity.is_none());
+        for (earlier, sample) in samples.iter().zip(&samples[10..]) {
+            let velocity = sample.angular_velocity.unwrap();
+            assert!((velocity.body - Vector3::new(0.0, 0.0, 0.5)).norm() < 0.05);
+            // Euler rates agree with how the Euler angles themselves moved over the window
+            let rates = velocity.euler_rates.unwrap();
+            let now = sample.unwrapped.unwrap().angles;
+            let then = earlier.unwrapped.unwrap().angles;
+            for axis in 0..3 {
+                let moved = (now[axis] - then[axis]) as f32;
+                assert!((rates[axis] - moved).abs() < 0.05);
+            }
+        }
+    }
+
+    #[test]
+    fn test_run_simulation_resampling() {
+        use crate::attitudes::attitude::{AttitudeFilter, ComplementaryConfig};
+        use crate::resamplings::resampling::{ResampleConfig, Resampled};
+        use std::time::Duration;
+
+        // Frames every 20 ms, resampled to 30 Hz: every 100 ms the grid meets a measured sample
+        let config = SimulationConfig {
+            filter: AttitudeFilter::Complementary(ComplementaryConfig {
+                crossover_frequency: 0.1,
+            }),
+            resampling: Some(ResampleConfig {
+                rate: 30.0,
+                tolerance: Duration::from_millis(1),
+                max_gap: None,
+            }),
+            ..yaw_turn_config()
+        };
+        let (report, samples) = collect(yaw_turn_source(), config);
+
+        // Grid points 0 s to 2 s
+        assert_eq!(report.samples, 61);
+        for (index, sample) in samples.iter().enumerate() {
+            assert_eq!(sample.sequence, index as u64);
+            assert_eq!(
+                sample.timestamp.source,
+                Duration::from_secs_f64(index as f64 / 30.0)
+            );
+            let time = sample.timestamp.source.as_secs_f32();
+            assert!((sample.orientation.euler.angles[2] - 0.5 * time).abs() < 0.01);
+            match sample.resampled.unwrap() {
+                Resampled::Measured { .. } => assert_eq!(index % 3, 0),
+                Resampled::Interpolated { fraction, .. } => {
+                    assert_ne!(index % 3, 0);
+                    assert!(fraction > 0.0 && fraction < 1.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_run_simulation_synchronisation() {
+        use crate::frames::frame::{encode_frame, FrameKind};
+        use crate::fusions::fusion::RedundancyConfig;
+        use crate::inputs::input::{Source, TimedFrame};
+        use crate::replays::replay::{ReplayConfig, ReplaySource};
+        use crate::samples::sample::TimeBase;
+        use crate::sensors::sensor::{MergedSource, SensorId};
+        use crate::synchronisations::synchronisation::SyncConfig;
+        use std::collections::BTreeMap;
+        use std::time::Duration;
+
+        let layout = test_layout();
+        // Replayed in real time over 0.4 s: sensor 1 at 50 Hz with its clock 100 s ahead, sensor
+        // 2 at 100 Hz with its clock 3 s ahead
+        let source = |rate: u64, clock: u64| -> Box<dyn Source> {
+            let mut stats = ConversionStats::default();
+            let frames = (0..rate * 2 / 5)
+                .map(|step| TimedFrame {
+                    bits: encode_frame([0.1, 0.2, 0.3], 0, &layout, Default::default(), &mut stats)
+                        .unwrap(),
+                    sensor_time: Some(
+                        Duration::from_secs(clock) + Duration::from_millis(step * 1000 / rate),
+                    ),
+                    sensor: SensorId::default(),
+                    kind: FrameKind::Attitude,
+                })
+                .collect();
+            Box::new(ReplaySource::new(frames, ReplayConfig::default()).unwrap())
+        };
+        let merged = MergedSource::new(vec![
+            (SensorId(1), source(50, 100)),
+            (SensorId(2), source(100, 3)),
+        ]);
+
+        let config = SimulationConfig {
+            layout,
+            synchronisation: Some(SyncConfig {
+                streams: vec![SensorId(1), SensorId(2)],
+                period: Duration::from_millis(20),
+                max_wait: Duration::from_millis(100),
+                ..Default::default()
+            }),
+            redundancy: Some(RedundancyConfig {
+                sensors: vec![(SensorId(1), 1.0), (SensorId(2), 1.0)],
+                fused_sensor: SensorId(10),
+                outlier_threshold: 0.2,
+                max_lag: 2,
+            }),
+            ..Default::default()
+        };
+        let (report, samples) = collect(merged, config);
+        assert_eq!(report.sensors[&SensorId(1)].samples, 20);
+        assert_eq!(report.sensors[&SensorId(2)].samples, 40);
+
+        // Despite their unrelated clocks, the streams meet in common epochs on the reference clock
+        let mut epochs: BTreeMap<u64, Vec<&Sample>> = BTreeMap::new();
+        for sample in samples
+            .iter()
+            .filter(|sample| sample.sensor != SensorId(10))
+        {
+            assert_eq!(sample.timestamp.base, TimeBase::Reference);
+            let info = sample.synchronisation.as_ref().unwrap();
+            assert_eq!(info.epoch, sample.sequence);
+            epochs.entry(sample.sequence).or_default().push(sample);
+        }
+        let paired: Vec<&Vec<&Sample>> = epochs.values().filter(|epoch| epoch.len() == 2).collect();
+        assert!(paired.len() >= epochs.len() * 4 / 5);
+        for epoch in paired {
+            let gap = epoch[0]
+                .timestamp
+                .source
+                .abs_diff(epoch[1].timestamp.source);
+            assert!(gap < Duration::from_millis(20));
+        }
+        // So redundant fusion combines both sensors epoch by epoch
+        let fused = samples
+            .iter()
+            .filter(|sample| sample.sensor == SensorId(10))
+            .filter(|sample| sample.fusion.as_ref().unwrap().contributors.len() == 2)
+            .count();
+        assert!(fused >= epochs.len() * 4 / 5);
+    }
+
+    #[test]
+    fn test_run_simulation_health_events() {
+        use crate::frames::frame::{encode_frame, FrameKind};
+        use crate::healths::health::{Fault, HealthConfig, HealthEvent, HealthState};
+        use crate::inputs::input::TimedFrame;
+        use crate::replays::replay::{ReplayConfig, ReplaySource, Speed};
+        use crate::sensors::sensor::SensorId;
+        use std::time::Duration;
+
+        struct EventSink(crossbeam_channel::Sender<HealthEvent>);
+
+        impl Sink for EventSink {
+            fn emit(&mut self, _sample: &Sample) -> io::Result<()> {
+                Ok(())
+            }
+
+            fn health(&mut self, event: &HealthEvent) -> io::Result<()> {
+                self.0.send(event.clone()).unwrap();
+                Ok(())
+            }
+        }
+
+        let layout = test_layout();
+        // Yaw steps by one field resolution per frame, except that it freezes from frame 40 to 60
+        let mut stats = ConversionStats::default();
+        let frames: Vec<TimedFrame> = (0..100u64)
+            .map(|step| {
+                let moved = match step {
+                    0..40 => step,
+                    40..=60 => 40,
+                    _ => step - 20,
+                };
+                let yaw = -1.0 + moved as f32 / 32.0;
+                TimedFrame {
+                    bits: encode_frame(
+                        [0.5, 0.25, yaw],
+                        0,
+                        &layout,
+                        Default::default(),
+                        &mut stats,
+                    )
+                    .unwrap(),
+                    sensor_time: Some(Duration::from_millis(10 * step)),
+                    sensor: SensorId::default(),
+                    kind: FrameKind::Attitude,
+                }
+            })
+            .collect();
+        let replay = ReplayConfig {
+            speed: Speed::AsFastAsPossible,
+            ..Default::default()
+        };
+        let source = ReplaySource::new(frames, replay).unwrap();
+
+        let config = SimulationConfig {
+            layout,
+            health: Some(HealthConfig {
+                stuck_frames: Some(10),
+                // Frames replayed as fast as possible arrive with no gaps
+                dropout: None,
+                fail_after: 5,
+                recover_after: 5,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let sinks: Vec<Box<dyn Sink>> = vec![Box::new(EventSink(sender))];
+        let report = run_simulation_with_sinks(source, config, sinks).unwrap();
+        assert_eq!(report.samples, 100);
+        assert_eq!(report.sensors[&SensorId::default()].health, HealthState::Ok);
+
+        let events: Vec<HealthEvent> = receiver.iter().collect();
+        let transitions: Vec<(HealthState, u128)> = events
+            .iter()
+            .map(|event| (event.to, event.timestamp.source.as_millis()))
+            .collect();
+        // The tenth identical frame degrades it, five faulty frames in a row fail it, and five
+        // moving frames after the freeze bring it back
+        assert_eq!(
+            transitions,
+            vec![
+                (HealthState::Degraded, 490),
+                (HealthState::Failed, 530),
+                (HealthState::Ok, 650),
+            ]
+        );
+        assert_eq!(events[0].faults, vec![Fault::Stuck]);
+    }
+
+    #[test]
+    fn test_run_simulation_notices_a_stream_that_stops() {
+        use crate::frames::frame::FrameKind;
+        use crate::healths::health::{Fault, HealthConfig, HealthEvent, HealthState};
+        use crate::inputs::input::{Source, TimedFrame};
+        use crate::sensors::sensor::SensorId;
+        use std::time::{Duration, Instant};
+
+        // Sends its frames, then goes quiet until the pipeline has failed it, or gives up
+        struct StoppingSource {
+            frames: Vec<TimedFrame>,
+            events: crossbeam_channel::Receiver<HealthEvent>,
+        }
+
+        impl Source for StoppingSource {
+            fn next_frame(&mut self) -> Option<Vec<u32>> {
+                unreachable!()
+            }
+
+            fn next_timed_frame(&mut self) -> Option<TimedFrame> {
+                if !self.frames.is_empty() {
+                    return Some(self.frames.remove(0));
+                }
+                while let Ok(event) = self.events.recv_timeout(Duration::from_secs(5)) {
+                    if event.to == HealthState::Failed {
+                        break;
+                    }
+                }
+                None
+            }
+        }
+
+        struct EventSink(Vec<crossbeam_channel::Sender<HealthEvent>>);
+
+        impl Sink for EventSink {
+            fn emit(&mut self, _sample: &Sample) -> io::Result<()> {
+                Ok(())
+            }
+
+            fn health(&mut self, event: &HealthEvent) -> io::Result<()> {
+                // The source stops listening once it has ended
+                for sender in &self.0 {
+                    sender.send(event.clone()).ok();
+                }
+                Ok(())
+            }
+        }
+
+        let frames = (0..5u64)
+            .map(|step| TimedFrame {
+                bits: Encoder::default()
+                    .encode_angles([0.001, 0.002, 0.0001 * step as f32])
+                    .unwrap(),
+                sensor_time: Some(Duration::from_millis(10 * step)),
+                sensor: SensorId::default(),
+                kind: FrameKind::Attitude,
+            })
+            .collect();
+        let (source_sender, source_receiver) = crossbeam_channel::unbounded();
+        let source = StoppingSource {
+            frames,
+            events: source_receiver,
+        };
+        let config = SimulationConfig {
+            health: Some(HealthConfig {
+                dropout: Some(Duration::from_millis(20)),
+                fail_after: 2,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let sinks: Vec<Box<dyn Sink>> = vec![Box::new(EventSink(vec![source_sender, sender]))];
+        let start = Instant::now();
+        let report = run_simulation_with_sinks(source, config, sinks).unwrap();
+
+        // No frame arrived after the stream stopped, yet the silence failed the sensor while
+        // the source was still waiting, long before it would have given up
+        assert!(start.elapsed() < Duration::from_secs(4));
+        assert_eq!(
+            report.sensors[&SensorId::default()].health,
+            HealthState::Failed
+        );
+        let events: Vec<HealthEvent> = receiver.iter().collect();
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| (event.to, event.faults.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (HealthState::Degraded, vec![Fault::Dropout]),
+                (HealthState::Failed, vec![Fault::Dropout]),
+            ]
+        );
+        // Stamped with the last frame's source time
+        assert!(events
+            .iter()
+            .all(|event| event.timestamp.source == Duration::from_millis(40)));
+    }
+
+    #[test]
+    fn test_run_simulation_calibration_file() {
+        use crate::calibrations::calibration::{calibration_path, Calibration};
+        use crate::frames::frame::{encode_frame, FrameKind};
+        use crate::inputs::input::TimedFrame;
+        use crate::replays::replay::{ReplayConfig, ReplaySource, Speed};
+        use crate::sensors::sensor::SensorId;
+        use nalgebra::UnitQuaternion;
+
+        let layout = test_layout();
+        // Sensor 2 is mounted turned 0.25 rad about z, and its angles carry bias and scale errors
+        let calibration = Calibration {
+            bias: [0.25, -0.125, 0.1875],
+            scale: [1.0, 1.25, 0.75],
+            mounting: UnitQuaternion::from_euler_angles(0.0, 0.0, 0.25),
+        };
+        let body = EulerAngles::new(0.5, 0.25, -0.5).to_quaternion();
+        let sensor = EulerConvention::default().from_quaternion(body * calibration.mounting);
+        let reported = [0, 1, 2].map(|i| calibration.scale[i] * sensor[i] + calibration.bias[i]);
+        let source = || {
+            let mut stats = ConversionStats::default();
+            let frames = (0..5)
+                .map(|_| TimedFrame {
+                    bits: encode_frame(reported, 0, &layout, Default::default(), &mut stats)
+                        .unwrap(),
+                    sensor_time: None,
+                    sensor: SensorId(2),
+                    kind: FrameKind::Attitude,
+                })
+                .collect();
+            let replay = ReplayConfig {
+                speed: Speed::AsFastAsPossible,
+                ..Default::default()
+            };
+            ReplaySource::new(frames, replay).unwrap()
+        };
+        let run = |config: SimulationConfig| collect(source(), config).1;
+
+        let directory =
+            std::env::temp_dir().join(format!("ravn-calibrations-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        calibration
+            .save(&calibration_path(&directory, SensorId(2)))
+            .unwrap();
+        let mut config = SimulationConfig {
+            layout,
+            ..Default::default()
+        };
+        let uncalibrated = run(config.clone());
+        // Sensor 7 has no file and stays uncalibrated
+        config
+            .load_calibrations(&directory, &[SensorId(2), SensorId(7)])
+            .unwrap();
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(config.sensors.len(), 1);
+        assert_eq!(config.sensors[&SensorId(2)].layout, layout);
+        let calibrated = run(config);
+
+        // Output is in the vehicle frame, to within the 1/32 rad field resolution
+        assert_eq!(calibrated.len(), 5);
+        for (calibrated, uncalibrated) in calibrated.iter().zip(&uncalibrated) {
+            assert!(calibrated.orientation.quaternion.angle_to(&body) < 0.05);
+            assert!(uncalibrated.orientation.quaternion.angle_to(&body) > 0.2);
+        }
+    }
+
+    #[test]
+    fn test_run_simulation_frames() {
+        use crate::coordinates::coordinate::{Enu, Flu, Frames, Frd, Ned};
+        use crate::frames::frame::{encode_frame, FrameKind};
+        use crate::inputs::input::TimedFrame;
+        use crate::replays::replay::{ReplayConfig, ReplaySource, Speed};
+        use crate::sensors::sensor::{SensorConfig, SensorId};
+        use std::f32::consts::FRAC_PI_2;
+
+        let layout = test_layout();
+        // Both sensors face the same way, 0.5 rad east of north: sensor 1 reports that as an
+        // aerospace heading, sensor 2 as a ROS yaw from east
+        let heading = 0.5;
+        let mut stats = ConversionStats::default();
+        let mut frame = |sensor, yaw| TimedFrame {
+            bits: encode_frame([0.0, 0.0, yaw], 0, &layout, Default::default(), &mut stats)
+                .unwrap(),
+            sensor_time: None,
+            sensor: SensorId(sensor),
+            kind: FrameKind::Attitude,
+        };
+        let frames = vec![frame(1, heading), frame(2, FRAC_PI_2 - heading)];
+        let replay = ReplayConfig {
+            speed: Speed::AsFastAsPossible,
+            ..Default::default()
+        };
+        let source = ReplaySource::new(frames, replay).unwrap();
+
+        let mut config = SimulationConfig {
+            layout,
+            input_frames: Frames::ENU_FLU,
+            output_frames: Frames::ENU_FLU,
+            ..Default::default()
+        };
+        let aerospace = SensorConfig {
+            frames: Frames::NED_FRD,
+            ..config.sensor(SensorId(1))
+        };
+        config.sensors.insert(SensorId(1), aerospace);
+        let (_, samples) = collect(source, config);
+
+        assert_eq!(samples.len(), 2);
+        for sample in &samples {
+            assert_eq!(sample.frames, Frames::ENU_FLU);
+            let ros = sample.attitude::<Enu, Flu>().quaternion().euler_angles().2;
+            assert!((ros - (FRAC_PI_2 - heading)).abs() < 0.02, "{:?}", sample);
+            let aerospace = sample.attitude::<Ned, Frd>().quaternion().euler_angles().2;
+            assert!((aerospace - heading).abs() < 0.02, "{:?}", sample);
+        }
+    }
 }
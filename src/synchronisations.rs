@@ -0,0 +1,392 @@
+pub mod synchronisation {
+
+    use std::collections::{BTreeMap, HashMap, VecDeque};
+    use std::time::Duration;
+
+    use crate::samples::sample::{Sample, TimeBase};
+    use crate::sensors::sensor::SensorId;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct SyncConfig {
+        // Streams expected in every epoch; other sensors' samples pass straight through
+        pub streams: Vec<SensorId>,
+        // Spacing of the epochs in reference time
+        pub period: Duration,
+        // An epoch is closed without its missing streams once any stream is this far past it
+        pub max_wait: Duration,
+        // Number of latest samples each stream's clock is fitted to
+        pub fit_window: usize,
+        // Largest drift believed (1e-4 is 100 ppm), so a short or bursty fit cannot run away
+        pub max_drift: f64,
+    }
+
+    impl Default for SyncConfig {
+        fn default() -> Self {
+            SyncConfig {
+                streams: Vec::new(),
+                period: Duration::from_millis(10),
+                max_wait: Duration::from_millis(50),
+                fit_window: 100,
+                max_drift: 1e-3,
+            }
+        }
+    }
+
+    // A stream's clock against the reference: reference = offset + (1 + drift) · sensor (s)
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub struct ClockEstimate {
+        pub offset: f64,
+        pub drift: f64,
+    }
+
+    impl ClockEstimate {
+        pub fn to_reference(&self, sensor: f64) -> f64 {
+            self.offset + (1.0 + self.drift) * sensor
+        }
+    }
+
+    // Least-squares line through a stream's latest pairs of sensor and reference times (s).
+    // Transport latency only shifts the offset, as long as it does not trend.
+    #[derive(Debug, Clone)]
+    pub struct ClockFit {
+        window: usize,
+        max_drift: f64,
+        pairs: VecDeque<(f64, f64)>,
+    }
+
+    impl ClockFit {
+        pub fn new(window: usize, max_drift: f64) -> Self {
+            ClockFit {
+                window,
+                max_drift,
+                pairs: VecDeque::new(),
+            }
+        }
+
+        pub fn push(&mut self, sensor: f64, reference: f64) -> ClockEstimate {
+            self.pairs.push_back((sensor, reference));
+            while self.pairs.len() > self.window.max(1) {
+                self.pairs.pop_front();
+            }
+            let count = self.pairs.len() as f64;
+            let (sensor_sum, reference_sum) = self
+                .pairs
+                .iter()
+                .fold((0.0, 0.0), |(s, r), pair| (s + pair.0, r + pair.1));
+            let (sensor_mean, reference_mean) = (sensor_sum / count, reference_sum / count);
+            let (mut spread, mut covariance) = (0.0, 0.0);
+            for (sensor, reference) in &self.pairs {
+                spread += (sensor - sensor_mean) * (sensor - sensor_mean);
+                covariance += (sensor - sensor_mean) * (reference - reference_mean);
+            }
+            // A single instant says nothing about drift
+            let drift = if spread > 0.0 {
+                (covariance / spread - 1.0).clamp(-self.max_drift, self.max_drift)
+            } else {
+                0.0
+            };
+            ClockEstimate {
+                offset: reference_mean - (1.0 + drift) * sensor_mean,
+                drift,
+            }
+        }
+    }
+
+    // Where a synchronised sample sits among the streams
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct SyncInfo {
+        pub epoch: u64,
+        // The sample's stream clock against the reference, as estimated when the epoch closed
+        pub clock: ClockEstimate,
+        // Streams the epoch was closed without
+        pub missing: Vec<SensorId>,
+    }
+
+    // Puts several streams on one clock and groups their samples into common epochs. The reference
+    // is the host clock at which frames were received, counted from the first one; each stream's
+    // own timestamps are mapped onto it through a running fit, so jittery delivery does not move
+    // samples between epochs the way arrival times alone would.
+    #[derive(Debug, Clone)]
+    pub struct Synchroniser {
+        pub config: SyncConfig,
+        origin: Option<Duration>,
+        clocks: HashMap<SensorId, (ClockFit, ClockEstimate)>,
+        // Latest reference time (s) seen from each stream
+        latest: HashMap<SensorId, f64>,
+        pending: BTreeMap<u64, BTreeMap<SensorId, Sample>>,
+        // Epochs up to this one have been released
+        closed: Option<u64>,
+        late: BTreeMap<SensorId, usize>,
+    }
+
+    impl Synchroniser {
+        pub fn new(config: SyncConfig) -> Result<Self, &'static str> {
+            if config.period.is_zero() {
+                return Err("Synchronisation period must be positive.");
+            }
+            if config.fit_window == 0 {
+                return Err("Clock fit window must hold at least one sample.");
+            }
+            if !(0.0..1.0).contains(&config.max_drift) {
+                return Err("Maximum clock drift must be in [0, 1).");
+            }
+            Ok(Synchroniser {
+                config,
+                origin: None,
+                clocks: HashMap::new(),
+                latest: HashMap::new(),
+                pending: BTreeMap::new(),
+                closed: None,
+                late: BTreeMap::new(),
+            })
+        }
+
+        fn covers(&self, sensor: SensorId) -> bool {
+            self.config.streams.contains(&sensor)
+        }
+
+        pub fn clock(&self, sensor: SensorId) -> Option<ClockEstimate> {
+            self.clocks.get(&sensor).map(|(_, estimate)| *estimate)
+        }
+
+        // Samples dropped because their epoch had already been released
+        pub fn late(&self) -> &BTreeMap<SensorId, usize> {
+            &self.late
+        }
+
+        // Moves a sample's timestamp onto the reference clock, refining its stream's clock fit
+        pub fn correct(&mut self, mut sample: Sample) -> Sample {
+            if !self.covers(sample.sensor) {
+                return sample;
+            }
+            let received = sample.timestamp.received;
+            let origin = *self.origin.get_or_insert(received);
+            let reference = received.as_secs_f64() - origin.as_secs_f64();
+            let source = sample.timestamp.source.as_secs_f64();
+            let (window, max_drift) = (self.config.fit_window, self.config.max_drift);
+            let (fit, estimate) = self
+                .clocks
+                .entry(sample.sensor)
+                .or_insert_with(|| (ClockFit::new(window, max_drift), ClockEstimate::default()));
+            *estimate = fit.push(source, reference);
+            let time = estimate.to_reference(source).max(0.0);
+            sample.timestamp.source = Duration::from_secs_f64(time);
+            sample.timestamp.base = TimeBase::Reference;
+            sample
+        }
+
+        // Samples released by this one, epoch by epoch, each numbered by its epoch. Only the
+        // sample nearest the epoch's time is kept from each stream; one for an epoch that has
+        // already been released is dropped and counted as late.
+        pub fn push(&mut self, sample: Sample) -> Vec<Sample> {
+            if !self.covers(sample.sensor) {
+                return vec![sample];
+            }
+            let period = self.config.period.as_secs_f64();
+            let time = sample.timestamp.source.as_secs_f64();
+            let epoch = (time / period).round() as u64;
+            if self.closed.is_some_and(|closed| epoch <= closed) {
+                *self.late.entry(sample.sensor).or_default() += 1;
+                return Vec::new();
+            }
+            let latest = self.latest.entry(sample.sensor).or_insert(time);
+            *latest = latest.max(time);
+
+            let target = epoch as f64 * period;
+            let distance = |sample: &Sample| (sample.timestamp.source.as_secs_f64() - target).abs();
+            let slot = self.pending.entry(epoch).or_default();
+            if slot
+                .get(&sample.sensor)
+                .is_none_or(|kept| distance(&sample) < distance(kept))
+            {
+                slot.insert(sample.sensor, sample);
+            }
+            self.release(false)
+        }
+
+        // Releases whatever is still pending, for the end of a run
+        pub fn flush(&mut self) -> Vec<Sample> {
+            self.release(true)
+        }
+
+        fn release(&mut self, everything: bool) -> Vec<Sample> {
+            let period = self.config.period.as_secs_f64();
+            let max_wait = self.config.max_wait.as_secs_f64();
+            let newest = self.latest.values().copied().fold(f64::MIN, f64::max);
+            let mut released = Vec::new();
+            while let Some(&epoch) = self.pending.keys().next() {
+                // Once every stream is past the epoch's half-period, none can send a nearer sample
+                let end = (epoch as f64 + 0.5) * period;
+                let complete = self
+                    .config
+                    .streams
+                    .iter()
+                    .all(|sensor| self.latest.get(sensor).is_some_and(|time| *time >= end));
+                if !(everything || complete || newest > end + max_wait) {
+                    break;
+                }
+                let samples = self.pending.remove(&epoch).unwrap_or_default();
+                let mut missing: Vec<SensorId> = self
+                    .config
+                    .streams
+                    .iter()
+                    .copied()
+                    .filter(|sensor| !samples.contains_key(sensor))
+                    .collect();
+                missing.sort();
+                for (sensor, mut sample) in samples {
+                    sample.sequence = epoch;
+                    sample.synchronisation = Some(SyncInfo {
+                        epoch,
+                        clock: self.clock(sensor).unwrap_or_default(),
+                        missing: missing.clone(),
+                    });
+                    released.push(sample);
+                }
+                self.closed = Some(epoch);
+            }
+            released
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::synchronisation::*;
+    use crate::generators::generator::Rng;
+    use crate::orientations::orientation::Orientation;
+    use crate::samples::sample::{Sample, TimeBase};
+    use crate::sensors::sensor::SensorId;
+    use std::time::Duration;
+
+    // A sample stamped `sensor_time` by its own clock and received at `received` (s) host time
+    fn sample(sensor: u16, sensor_time: f64, received: f64) -> Sample {
+        let mut sample = Sample::new(0, Orientation::identity());
+        sample.sensor = SensorId(sensor);
+        sample.timestamp.source = Duration::from_secs_f64(sensor_time);
+        sample.timestamp.base = TimeBase::Sensor;
+        sample.timestamp.received = Duration::from_secs_f64(received);
+        sample
+    }
+
+    #[test]
+    fn test_synchroniser_rejects_bad_config() {
+        let config = SyncConfig {
+            period: Duration::ZERO,
+            ..Default::default()
+        };
+        assert!(Synchroniser::new(config).is_err());
+        let config = SyncConfig {
+            max_drift: 1.5,
+            ..Default::default()
+        };
+        assert!(Synchroniser::new(config).is_err());
+    }
+
+    #[test]
+    fn test_clock_fit_recovers_offset_and_drift() {
+        // The sensor clock runs 200 ppm slow and started 7 s before the reference; frames take
+        // 2 ms plus up to a millisecond of jitter to arrive
+        let mut rng = Rng::new(11);
+        let mut fit = ClockFit::new(500, 1e-3);
+        let mut estimate = ClockEstimate::default();
+        for step in 0..2000 {
+            let reference = step as f64 * 0.01;
+            let sensor = (reference + 7.0) / (1.0 + 2e-4);
+            let latency = 0.002 + 0.001 * rng.next_f64();
+            estimate = fit.push(sensor, reference + latency);
+        }
+        assert!((estimate.drift - 2e-4).abs() < 2e-5);
+        let sensor = (20.0 + 7.0) / (1.0 + 2e-4);
+        assert!((estimate.to_reference(sensor) - 20.0025).abs() < 1e-3);
+
+        // A burst received all at once does not make the clock run away
+        let mut fit = ClockFit::new(10, 1e-3);
+        for step in 0..10 {
+            estimate = fit.push(step as f64 * 0.01, 5.0);
+        }
+        assert_eq!(estimate.drift, -1e-3);
+    }
+
+    #[test]
+    fn test_synchroniser_aligns_offset_clocks_into_epochs() {
+        let config = SyncConfig {
+            streams: vec![SensorId(1), SensorId(2)],
+            period: Duration::from_millis(20),
+            max_wait: Duration::from_millis(40),
+            ..Default::default()
+        };
+        let mut synchroniser = Synchroniser::new(config).unwrap();
+        // Sensor 1 at 50 Hz with its clock 100 s ahead; sensor 2 at 100 Hz with its clock 3 s
+        // behind, so their raw timestamps have nothing in common
+        let mut released = Vec::new();
+        for step in 0..=100u16 {
+            let time = step as f64 * 0.01;
+            let mut inputs = vec![sample(2, time + 3.0, 10.0 + time)];
+            if step % 2 == 0 {
+                inputs.push(sample(1, time + 100.0, 10.0 + time));
+            }
+            for input in inputs {
+                let corrected = synchroniser.correct(input);
+                assert_eq!(corrected.timestamp.base, TimeBase::Reference);
+                released.extend(synchroniser.push(corrected));
+            }
+        }
+        released.extend(synchroniser.flush());
+
+        // Every epoch holds one sample of each stream, at the same reference time
+        assert_eq!(released.len(), 102);
+        for pair in released.chunks(2) {
+            assert_eq!((pair[0].sensor, pair[1].sensor), (SensorId(1), SensorId(2)));
+            assert_eq!(pair[0].sequence, pair[1].sequence);
+            let info = pair[0].synchronisation.as_ref().unwrap();
+            assert_eq!(info.epoch, pair[0].sequence);
+            assert!(info.missing.is_empty());
+            let gap = pair[0].timestamp.source.abs_diff(pair[1].timestamp.source);
+            assert!(gap < Duration::from_micros(10));
+        }
+        let clock = synchroniser.clock(SensorId(1)).unwrap();
+        assert!((clock.offset + 100.0).abs() < 1e-6);
+        assert!(synchroniser.late().is_empty());
+    }
+
+    #[test]
+    fn test_synchroniser_closes_epochs_without_late_streams() {
+        let config = SyncConfig {
+            streams: vec![SensorId(1), SensorId(2)],
+            period: Duration::from_millis(10),
+            max_wait: Duration::from_millis(30),
+            ..Default::default()
+        };
+        let mut synchroniser = Synchroniser::new(config).unwrap();
+        let mut push = |sample: Sample| {
+            let corrected = synchroniser.correct(sample);
+            synchroniser.push(corrected)
+        };
+        assert!(push(sample(1, 0.0, 0.0)).is_empty());
+        assert!(push(sample(2, 0.0, 0.0)).is_empty());
+        // Sensor 2 stalls; sensor 1 carries on until epoch 0 has waited long enough
+        let mut released = Vec::new();
+        for step in 1..=6 {
+            released.extend(push(sample(1, step as f64 * 0.01, step as f64 * 0.01)));
+        }
+        // Epoch 0 with both sensors, then epochs 1 and 2 with sensor 1 alone
+        let epochs: Vec<u64> = released.iter().map(|sample| sample.sequence).collect();
+        assert_eq!(epochs, vec![0, 0, 1, 2]);
+        assert!(released[0]
+            .synchronisation
+            .as_ref()
+            .unwrap()
+            .missing
+            .is_empty());
+        let info = released[2].synchronisation.as_ref().unwrap();
+        assert_eq!(info.missing, vec![SensorId(2)]);
+
+        // Sensor 2's sample for a released epoch is too late; other sensors pass straight through
+        let mut stalled = sample(2, 0.01, 0.06);
+        stalled.timestamp.base = TimeBase::Reference;
+        assert!(synchroniser.push(stalled).is_empty());
+        assert_eq!(synchroniser.push(sample(9, 0.0, 0.06)).len(), 1);
+        assert_eq!(synchroniser.late().get(&SensorId(2)), Some(&1));
+    }
+}
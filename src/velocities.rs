@@ -0,0 +1,210 @@
+pub mod velocity {
+
+    use nalgebra::{Matrix3, UnitQuaternion, Vector3};
+    use std::collections::VecDeque;
+    use std::time::Duration;
+
+    use crate::attitudes::attitude::seconds_between;
+    use crate::orientations::orientation::{EulerAngles, EulerConvention, RotationFrame};
+
+    // Body-frame angular velocity (rad/s), and the rates of the Euler angles it corresponds to
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct AngularVelocity {
+        pub body: Vector3<f32>,
+        // In the convention of the sample's Euler angles; `None` at gimbal lock, where they have
+        // no well-defined rates
+        pub euler_rates: Option<[f32; 3]>,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct RateConfig {
+        // Number of sample-to-sample steps averaged into each rate; 1 differences only the latest
+        // two samples
+        pub window: usize,
+    }
+
+    impl Default for RateConfig {
+        fn default() -> Self {
+            RateConfig { window: 1 }
+        }
+    }
+
+    // The body-frame rotation from `previous` to `current` as a rotation vector, taking the short
+    // way round
+    pub fn rotation_step(
+        previous: UnitQuaternion<f32>,
+        current: UnitQuaternion<f32>,
+    ) -> Vector3<f32> {
+        let step = previous.inverse() * current;
+        if step.w < 0.0 {
+            UnitQuaternion::new_unchecked(-step.into_inner()).scaled_axis()
+        } else {
+            step.scaled_axis()
+        }
+    }
+
+    // Maps Euler angle rates to body-frame angular velocity: column i is the body-frame axis of
+    // the rotation by angle i
+    pub fn kinematic_jacobian(convention: EulerConvention, angles: [f32; 3]) -> Matrix3<f32> {
+        let axes = convention.sequence.axes();
+        let rotations: Vec<UnitQuaternion<f32>> = axes
+            .iter()
+            .zip(angles.iter())
+            .map(|(axis, angle)| UnitQuaternion::from_axis_angle(&axis.unit_vector(), *angle))
+            .collect();
+        let columns = match convention.frame {
+            // R = R1 R2 R3: each axis is seen through the rotations that follow it
+            RotationFrame::Intrinsic => [
+                (rotations[1] * rotations[2]).inverse() * axes[0].unit_vector().into_inner(),
+                rotations[2].inverse() * axes[1].unit_vector().into_inner(),
+                axes[2].unit_vector().into_inner(),
+            ],
+            // R = R3 R2 R1: each axis is seen through the rotations that precede it
+            RotationFrame::Extrinsic => [
+                axes[0].unit_vector().into_inner(),
+                rotations[0].inverse() * axes[1].unit_vector().into_inner(),
+                (rotations[1] * rotations[0]).inverse() * axes[2].unit_vector().into_inner(),
+            ],
+        };
+        Matrix3::from_columns(&columns)
+    }
+
+    // The Euler angle rates for a body-frame angular velocity, or `None` at gimbal lock
+    pub fn euler_rates(euler: &EulerAngles, body: Vector3<f32>) -> Option<[f32; 3]> {
+        if euler.near_singularity {
+            return None;
+        }
+        let rates = kinematic_jacobian(euler.convention, euler.angles).try_inverse()? * body;
+        Some([rates.x, rates.y, rates.z])
+    }
+
+    // Differences one stream's successive orientations into body-frame angular velocity
+    #[derive(Debug, Clone)]
+    pub struct RateEstimator {
+        pub config: RateConfig,
+        previous: Option<(UnitQuaternion<f32>, Duration)>,
+        // Latest steps as rotation vector and duration (s), oldest first
+        steps: VecDeque<(Vector3<f32>, f32)>,
+    }
+
+    impl RateEstimator {
+        pub fn new(config: RateConfig) -> Self {
+            RateEstimator {
+                config,
+                previous: None,
+                steps: VecDeque::new(),
+            }
+        }
+
+        // The rate over the window ending at this sample: the summed rotation over the summed
+        // time. Nothing is known after the first sample, or while no time has passed.
+        pub fn push(
+            &mut self,
+            quaternion: UnitQuaternion<f32>,
+            time: Duration,
+        ) -> Option<Vector3<f32>> {
+            let previous = self.previous.replace((quaternion, time));
+            let (previous, previous_time) = previous?;
+            let dt = seconds_between(Some(previous_time), time);
+            if dt > 0.0 {
+                self.steps
+                    .push_back((rotation_step(previous, quaternion), dt));
+                // A window of zero is treated as one
+                while self.steps.len() > self.config.window.max(1) {
+                    self.steps.pop_front();
+                }
+            }
+            let (rotation, duration) = self
+                .steps
+                .iter()
+                .fold((Vector3::zeros(), 0.0), |(rotation, duration), step| {
+                    (rotation + step.0, duration + step.1)
+                });
+            (duration > 0.0).then(|| rotation / duration)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::velocity::*;
+    use crate::orientations::orientation::{EulerAngles, EulerConvention, EulerSequence};
+    use nalgebra::{UnitQuaternion, Vector3};
+    use std::time::Duration;
+
+    #[test]
+    fn test_rotation_step_takes_short_way() {
+        let previous = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 0.1);
+        let current = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 0.3);
+        let flipped = UnitQuaternion::new_unchecked(-current.into_inner());
+        for current in [current, flipped] {
+            let step = rotation_step(previous, current);
+            assert!((step - Vector3::new(0.0, 0.0, 0.2)).norm() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_rate_estimator_body_rate_and_window() {
+        // Tilted, then spinning about the body z axis at 0.8 rad/s, sampled at 20 Hz
+        let tilt = UnitQuaternion::from_euler_angles(0.3, -0.2, 0.0);
+        let body_rate = Vector3::new(0.0, 0.0, 0.8);
+        let at = |time: f32| tilt * UnitQuaternion::from_scaled_axis(body_rate * time);
+
+        let mut estimator = RateEstimator::new(RateConfig::default());
+        assert!(estimator.push(at(0.0), Duration::ZERO).is_none());
+        // No time has passed, so there is still no rate
+        assert!(estimator.push(at(0.0), Duration::ZERO).is_none());
+        for step in 1..10u64 {
+            let time = Duration::from_millis(50 * step);
+            let rate = estimator.push(at(time.as_secs_f32()), time).unwrap();
+            assert!((rate - body_rate).norm() < 1e-4);
+        }
+
+        // A window averages out a wobble that alternates from sample to sample
+        let mut raw = RateEstimator::new(RateConfig::default());
+        let mut smoothed = RateEstimator::new(RateConfig { window: 2 });
+        let (mut raw_error, mut smoothed_error) = (0.0, 0.0);
+        for step in 0..40u64 {
+            let time = Duration::from_millis(50 * step);
+            let wobble = if step % 2 == 0 { 0.01 } else { -0.01 };
+            let q =
+                at(time.as_secs_f32()) * UnitQuaternion::from_scaled_axis(Vector3::x() * wobble);
+            if let (Some(raw_rate), Some(smoothed_rate)) =
+                (raw.push(q, time), smoothed.push(q, time))
+            {
+                raw_error += (raw_rate - body_rate).norm();
+                smoothed_error += (smoothed_rate - body_rate).norm();
+            }
+        }
+        assert!(smoothed_error < raw_error / 5.0);
+    }
+
+    #[test]
+    fn test_euler_rates_match_finite_differences() {
+        let conventions = [
+            EulerConvention::ROLL_PITCH_YAW,
+            EulerConvention::AEROSPACE,
+            EulerConvention::intrinsic(EulerSequence::ZXZ),
+            EulerConvention::extrinsic(EulerSequence::YZY),
+        ];
+        let start = [0.3f32, 0.6, -0.4];
+        let rates = [0.2f32, -0.1, 0.5];
+        let dt = 1e-3;
+        for convention in conventions {
+            let later: Vec<f32> = (0..3).map(|i| start[i] + rates[i] * dt).collect();
+            let before = convention.to_quaternion(start);
+            let after = convention.to_quaternion([later[0], later[1], later[2]]);
+            let body = rotation_step(before, after) / dt;
+
+            let euler = EulerAngles::with_convention(start, convention);
+            let recovered = euler_rates(&euler, body).unwrap();
+            for (recovered, expected) in recovered.iter().zip(rates) {
+                assert!((recovered - expected).abs() < 1e-2, "{:?}", convention);
+            }
+        }
+
+        // At gimbal lock the Euler rates are undefined
+        let locked = EulerAngles::new(0.1, std::f32::consts::FRAC_PI_2, 0.2);
+        assert!(euler_rates(&locked, Vector3::new(0.0, 0.0, 1.0)).is_none());
+    }
+}